@@ -0,0 +1,60 @@
+//
+// Copyright (c) 2022 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+use greetings_maker::GreetingsMaker;
+use prost::Message;
+use zenoh_flow::prelude::Result;
+use zf_test_harness::{Harness, NodeUnderTest};
+
+async fn greetings_maker() -> Result<NodeUnderTest<GreetingsMaker>> {
+    Harness::new()
+        .input("name", |buffer: &mut Vec<u8>, name: &String| {
+            buffer.extend_from_slice(name.as_bytes());
+            Ok(())
+        })
+        .output("greeting", |bytes| Ok(String::decode(bytes)?))
+        .operator::<GreetingsMaker>()
+        .await
+}
+
+#[async_std::test]
+async fn greets_each_name_in_its_language() -> Result<()> {
+    let node = greetings_maker().await?;
+    let greetings = [
+        ("Sofia", "Ciao, Sofia!\n"),
+        ("Leonardo", "Ciao, Leonardo!\n"),
+        ("Lucia", "¡Hola, Lucia!\n"),
+        ("Martin", "¡Hola, Martin!\n"),
+        ("Jade", "Bonjour, Jade !\n"),
+        ("Gabriel", "Bonjour, Gabriel !\n"),
+        ("Ada", "Hello, Ada!\n"),
+    ];
+
+    for (name, greeting) in greetings.iter().copied() {
+        node.send("name", name.to_string(), None).await?;
+        node.iteration().await?;
+        assert_eq!(node.recv::<String>("greeting").await?, greeting);
+    }
+    Ok(())
+}
+
+#[async_std::test]
+async fn ignores_the_trailing_newline_of_a_name() -> Result<()> {
+    let node = greetings_maker().await?;
+
+    node.send("name", "Jade\n".to_string(), None).await?;
+    node.iteration().await?;
+    assert_eq!(node.recv::<String>("greeting").await?, "Bonjour, Jade !\n");
+    Ok(())
+}
@@ -13,23 +13,67 @@
 //
 
 use async_std::{fs::File, io::WriteExt, sync::Mutex};
-use datatypes::ARKANSAS_PORT;
-use prost::Message;
+use datatypes::codec::Codec;
+use datatypes::latency::LatencyRecorder;
+use datatypes::payload;
+use datatypes::stats::Statistics;
+use datatypes::{Configurable, ARKANSAS_PORT};
+use std::io::Write;
+use std::sync::atomic::{AtomicUsize, Ordering};
 use zenoh_flow::prelude::*;
 
-static OUT_FILE: &str = "/tmp/montblanc.out";
+static OUT_FILE_KEY: &str = "out_file";
+static DEFAULT_OUT_FILE: &str = "/tmp/montblanc.out";
+static LATENCY_FILE_KEY: &str = "latency_file";
+static DEFAULT_LATENCY_FILE: &str = "/tmp/montblanc-latency.out";
+static CHECK_KEY: &str = "check";
+
+/// The nodes and outputs the strings received went through, from the last to the first.
+static ARKANSAS_PATH: &[&str] = &["geneva/arkansas", "hamburg/parana", "portsmouth/danube"];
 
 #[export_sink]
 pub struct Arequipa {
     input: Input<datatypes::data_types::String>,
+    statistics: Statistics,
     file: Mutex<File>,
+    latencies: Mutex<LatencyRecorder>,
+    latency_file: String,
+    /// The file at `latency_file`, written when the flow is stopped.
+    latency_report: std::fs::File,
+    check: bool,
+    checked: AtomicUsize,
+    /// The strings checked that did not follow `ARKANSAS_PATH`.
+    misrouted: AtomicUsize,
+    /// The strings checked whose payload was altered.
+    altered: AtomicUsize,
 }
 
 #[async_trait::async_trait]
 impl Node for Arequipa {
     async fn iteration(&self) -> Result<()> {
-        let (message, _) = self.input.recv().await?;
+        let (message, ts) = self.input.recv().await?;
         if let zenoh_flow::prelude::Message::Data(data) = message {
+            self.statistics.received(ARKANSAS_PORT);
+
+            // Each operator prefixes the string it forwards with its name and output: the value
+            // received starts with the path it followed, e.g.
+            // `geneva/arkansas:hamburg/parana:portsmouth/danube`, followed by a space and the
+            // payload of the source, which ends with its checksum.
+            let (path, payload) = data
+                .value
+                .split_once(' ')
+                .unwrap_or((data.value.as_str(), ""));
+            if self.check {
+                self.checked.fetch_add(1, Ordering::Relaxed);
+                if !path.split(':').eq(ARKANSAS_PATH.iter().copied()) {
+                    self.misrouted.fetch_add(1, Ordering::Relaxed);
+                } else if !payload::is_intact(payload) {
+                    self.altered.fetch_add(1, Ordering::Relaxed);
+                }
+            }
+            let latency = datatypes::latency::elapsed_since(ts.get_time().as_u64());
+            self.latencies.lock().await.record(path, latency);
+
             let mut file = self.file.lock().await;
             let final_data = format!("{}\n", data.value);
             file.write_all(final_data.as_bytes())
@@ -49,20 +93,117 @@ impl Node for Arequipa {
 impl Sink for Arequipa {
     async fn new(
         _context: Context,
-        _configuration: Option<Configuration>,
+        configuration: Option<Configuration>,
         mut inputs: Inputs,
     ) -> Result<Self> {
-        Ok(Self {
-            input: inputs
-                .take(ARKANSAS_PORT)
-                .unwrap_or_else(|| panic!("No Input called '{}' found", ARKANSAS_PORT))
-                .typed(|d| Ok(datatypes::data_types::String::decode(d)?)),
+        let out_file = match configuration
+            .as_ref()
+            .and_then(|configuration| configuration.get(OUT_FILE_KEY))
+        {
+            Some(value) => value.as_str().ok_or_else(|| {
+                zferror!(
+                    ErrorKind::ConfigurationError,
+                    "`{}` must be a path",
+                    OUT_FILE_KEY
+                )
+            })?,
+            None => DEFAULT_OUT_FILE,
+        };
+
+        let latency_file = match configuration
+            .as_ref()
+            .and_then(|configuration| configuration.get(LATENCY_FILE_KEY))
+        {
+            Some(value) => value
+                .as_str()
+                .ok_or_else(|| {
+                    zferror!(
+                        ErrorKind::ConfigurationError,
+                        "`{}` must be a path",
+                        LATENCY_FILE_KEY
+                    )
+                })?
+                .to_string(),
+            None => DEFAULT_LATENCY_FILE.to_string(),
+        };
+
+        let check = match configuration
+            .as_ref()
+            .and_then(|configuration| configuration.get(CHECK_KEY))
+        {
+            Some(value) => value.as_bool().ok_or_else(|| {
+                zferror!(
+                    ErrorKind::ConfigurationError,
+                    "`{}` must be a boolean",
+                    CHECK_KEY
+                )
+            })?,
+            None => false,
+        };
+
+        // The files are created right away, so that a path that cannot be written is reported
+        // when the flow starts.
+        let file = File::create(out_file).await.map_err(|e| {
+            zferror!(
+                ErrorKind::ConfigurationError,
+                "Could not create the `{}` {}: {}",
+                OUT_FILE_KEY,
+                out_file,
+                e
+            )
+        })?;
+        let latency_report = std::fs::File::create(&latency_file).map_err(|e| {
+            zferror!(
+                ErrorKind::ConfigurationError,
+                "Could not create the `{}` {}: {}",
+                LATENCY_FILE_KEY,
+                latency_file,
+                e
+            )
+        })?;
 
-            file: Mutex::new(
-                File::create(OUT_FILE)
-                    .await
-                    .unwrap_or_else(|_| panic!("Could not create {}", OUT_FILE)),
-            ),
+        let statistics =
+            Statistics::new("Arequipa", &[ARKANSAS_PORT], &[], configuration.as_ref())?;
+        Ok(Self {
+            input: datatypes::take_input(
+                &mut inputs,
+                ARKANSAS_PORT,
+                Codec::default().configure(configuration.as_ref())?,
+                &statistics,
+            )?,
+            statistics,
+            latencies: Mutex::new(LatencyRecorder::default()),
+            latency_file,
+            latency_report,
+            check,
+            checked: AtomicUsize::new(0),
+            misrouted: AtomicUsize::new(0),
+            altered: AtomicUsize::new(0),
+            file: Mutex::new(file),
         })
     }
 }
+
+impl Drop for Arequipa {
+    /// Writes the latency histograms, followed by the result of the checks, when the flow is
+    /// stopped.
+    fn drop(&mut self) {
+        let mut report = self.latencies.get_mut().report();
+        if self.check {
+            report.push_str(&format!(
+                "\nchecked\tmisrouted\taltered\n{}\t{}\t{}\n",
+                self.checked.get_mut(),
+                self.misrouted.get_mut(),
+                self.altered.get_mut()
+            ));
+        }
+
+        if let Err(e) = self.latency_report.write_all(report.as_bytes()) {
+            log::error!(
+                "Arequipa could not write the latencies to {}: {}",
+                self.latency_file,
+                e
+            );
+        }
+    }
+}
@@ -0,0 +1,58 @@
+//
+// Copyright (c) 2022 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+use arequipa::Arequipa;
+use datatypes::codec::Codec;
+use datatypes::ARKANSAS_PORT;
+use serde_json::{json, Value};
+use zenoh_flow::prelude::Result;
+use zf_test_harness::{Harness, NodeUnderTest};
+
+async fn arequipa(configuration: Value) -> Result<NodeUnderTest<Arequipa>> {
+    let codec = Codec::default();
+    Harness::new()
+        .configuration(configuration)
+        .input(
+            ARKANSAS_PORT,
+            move |buffer: &mut Vec<u8>, string: &datatypes::data_types::String| {
+                Ok(codec.encode(string, buffer)?)
+            },
+        )
+        .sink::<Arequipa>()
+        .await
+}
+
+#[async_std::test]
+async fn rejects_files_that_cannot_be_created() {
+    let dir = std::env::temp_dir().join(format!("montblanc-arequipa-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let missing = dir.join("missing").join("file.out");
+
+    let cases = [
+        json!({ "out_file": missing, "latency_file": dir.join("latency.out") }),
+        json!({ "out_file": dir.join("montblanc.out"), "latency_file": missing }),
+        json!({ "out_file": dir, "latency_file": dir.join("latency.out") }),
+    ];
+    for case in cases.iter() {
+        assert!(arequipa(case.clone()).await.is_err(), "{}", case);
+    }
+
+    let created = arequipa(json!({
+        "out_file": dir.join("montblanc.out"),
+        "latency_file": dir.join("latency.out"),
+    }))
+    .await;
+    assert!(created.is_ok());
+    std::fs::remove_dir_all(&dir).unwrap();
+}
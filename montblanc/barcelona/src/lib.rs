@@ -12,42 +12,27 @@
 //   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
 //
 
+use async_std::sync::Mutex;
 use datatypes::data_types;
+use datatypes::datagen::DataGen;
+use datatypes::stats::Statistics;
+use datatypes::Ports;
 use datatypes::{LENA_PORT, MEKONG_PORT};
 use futures::prelude::*;
 use futures::select;
-use prost::Message as pMessage;
-use rand::random;
 use zenoh_flow::prelude::*;
 
 #[export_operator]
+#[derive(Ports)]
 pub struct Barcelona {
+    #[input(MEKONG_PORT)]
     input_mekong: Input<data_types::TwistWithCovarianceStamped>,
+    #[output(LENA_PORT)]
     output_lena: Output<data_types::WrenchStamped>,
-}
-
-#[async_trait::async_trait]
-impl Operator for Barcelona {
-    async fn new(
-        _context: Context,
-        _configuration: Option<Configuration>,
-        mut inputs: Inputs,
-        mut outputs: Outputs,
-    ) -> Result<Self> {
-        Ok(Self {
-            input_mekong: inputs
-                .take(MEKONG_PORT)
-                .unwrap_or_else(|| panic!("No Input called '{}' found", MEKONG_PORT))
-                .typed(|d| Ok(data_types::TwistWithCovarianceStamped::decode(d)?)),
-            output_lena: outputs
-                .take(LENA_PORT)
-                .unwrap_or_else(|| panic!("No Output called '{}' found", LENA_PORT))
-                .typed(|buf, v: &data_types::WrenchStamped| {
-                    buf.resize(v.encoded_len(), 0);
-                    Ok(v.encode(buf)?)
-                }),
-        })
-    }
+    #[statistics]
+    statistics: Statistics,
+    #[generate]
+    datagen: Mutex<DataGen>,
 }
 
 #[async_trait::async_trait]
@@ -55,9 +40,14 @@ impl Node for Barcelona {
     async fn iteration(&self) -> Result<()> {
         select! {
             msg = self.input_mekong.recv().fuse() => {
-                if let Ok((Message::Data(data), _ts)) = msg {
+                if let Ok((Message::Data(data), ts)) = msg {
+                    self.statistics.received(MEKONG_PORT);
+                    let header = match data.header.clone() {
+                        Some(header) => header,
+                        None => self.datagen.lock().await.gen(),
+                    };
                     let value = data_types::WrenchStamped {
-                        header: Some(data.header.clone().unwrap_or(random())),
+                        header: Some(header),
                         wrench: Some(data_types::Wrench {
                             force: data
                                 .twist
@@ -79,7 +69,8 @@ impl Node for Barcelona {
                                 .clone(),
                         }),
                     };
-                    self.output_lena.send(value, None).await?;
+                    self.statistics.sent(LENA_PORT);
+                    self.output_lena.send(value, Some(ts.get_time().as_u64())).await?;
                 }
             }
         }
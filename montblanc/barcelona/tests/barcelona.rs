@@ -0,0 +1,106 @@
+//
+// Copyright (c) 2022 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+use barcelona::Barcelona;
+use datatypes::codec::Codec;
+use datatypes::data_types::{
+    Header, Twist, TwistWithCovariance, TwistWithCovarianceStamped, Vector3, WrenchStamped,
+};
+use datatypes::{LENA_PORT, MEKONG_PORT};
+use zenoh_flow::prelude::Result;
+use zf_test_harness::{Harness, NodeUnderTest};
+
+async fn barcelona() -> Result<NodeUnderTest<Barcelona>> {
+    let codec = Codec::default();
+    Harness::new()
+        .input(
+            MEKONG_PORT,
+            move |buffer: &mut Vec<u8>, twist: &TwistWithCovarianceStamped| {
+                Ok(codec.encode(twist, buffer)?)
+            },
+        )
+        .output(LENA_PORT, move |bytes| {
+            Ok(codec.decode::<WrenchStamped>(bytes)?)
+        })
+        .operator::<Barcelona>()
+        .await
+}
+
+fn twist(header: Option<Header>) -> TwistWithCovarianceStamped {
+    TwistWithCovarianceStamped {
+        header,
+        twist: Some(TwistWithCovariance {
+            twist: Some(Twist {
+                linear: Some(Vector3 {
+                    x: 1.0,
+                    y: 2.0,
+                    z: 3.0,
+                }),
+                angular: Some(Vector3 {
+                    x: -0.5,
+                    y: 0.0,
+                    z: 0.25,
+                }),
+            }),
+            covariance: vec![0.0; 36],
+        }),
+    }
+}
+
+#[async_std::test]
+async fn maps_the_linear_twist_to_the_force_and_the_angular_one_to_the_torque() -> Result<()> {
+    let node = barcelona().await?;
+    let header = Header {
+        sec: 42,
+        nanosec: 7,
+        frame_id: "mekong".to_string(),
+    };
+    let sent = twist(Some(header.clone()));
+
+    node.send(MEKONG_PORT, sent.clone(), None).await?;
+    node.iteration().await?;
+    let wrench = node.recv::<WrenchStamped>(LENA_PORT).await?;
+
+    let expected = sent.twist.and_then(|twist| twist.twist).unwrap();
+    assert_eq!(wrench.header, Some(header));
+    let wrench = wrench.wrench.unwrap();
+    assert_eq!(wrench.force, expected.linear);
+    assert_eq!(wrench.torque, expected.angular);
+    Ok(())
+}
+
+#[async_std::test]
+async fn generates_a_header_when_there_is_none() -> Result<()> {
+    let node = barcelona().await?;
+
+    node.send(MEKONG_PORT, twist(None), None).await?;
+    node.iteration().await?;
+    let wrench = node.recv::<WrenchStamped>(LENA_PORT).await?;
+
+    assert!(wrench.header.is_some());
+    Ok(())
+}
+
+#[async_std::test]
+async fn fails_without_a_twist() -> Result<()> {
+    let node = barcelona().await?;
+    let empty = TwistWithCovarianceStamped {
+        twist: None,
+        ..twist(None)
+    };
+
+    node.send(MEKONG_PORT, empty, None).await?;
+    assert!(node.iteration().await.is_err());
+    Ok(())
+}
@@ -12,42 +12,43 @@
 //   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
 //
 
+use async_std::sync::Mutex;
+use datatypes::datagen::DataGen;
+use datatypes::publication::Publication;
+use datatypes::stats::Statistics;
+use datatypes::ticker::Ticker;
+use datatypes::Ports;
 use datatypes::AMAZON_PORT;
-use prost::Message;
-use rand::random;
+use rand::Rng;
 use std::time::Duration;
 use zenoh_flow::prelude::*;
 
 #[export_source]
+#[derive(Ports)]
 pub struct Cordoba {
+    #[output(AMAZON_PORT)]
     output: Output<datatypes::data_types::Float32>,
+    #[statistics]
+    statistics: Statistics,
+    #[configuration(Ticker::every(Duration::from_millis(100)))]
+    ticker: Ticker,
+    #[configuration(Publication::without_payload())]
+    publication: Publication,
+    #[generate]
+    datagen: Mutex<DataGen>,
 }
 
 #[async_trait::async_trait]
 impl Node for Cordoba {
     async fn iteration(&self) -> Result<()> {
-        async_std::task::sleep(Duration::from_millis(100)).await;
-        let data: f32 = random::<f32>() * 1000000.0;
-        let value = datatypes::data_types::Float32 { value: data };
-        self.output.send(value, None).await
-    }
-}
-
-#[async_trait::async_trait]
-impl Source for Cordoba {
-    async fn new(
-        _context: Context,
-        _configuration: Option<Configuration>,
-        mut outputs: Outputs,
-    ) -> Result<Self> {
-        Ok(Self {
-            output: outputs
-                .take(AMAZON_PORT)
-                .unwrap_or_else(|| panic!("No Output called '{}' found", AMAZON_PORT))
-                .typed(|buf, v: &datatypes::data_types::Float32| {
-                    buf.resize(v.encoded_len(), 0);
-                    Ok(v.encode(buf)?)
-                }),
-        })
+        self.ticker.tick().await;
+        for _ in 0..self.publication.burst_size {
+            let timestamp = datatypes::latency::now();
+            let data: f32 = self.datagen.lock().await.rng().gen::<f32>() * 1000000.0;
+            let value = datatypes::data_types::Float32 { value: data };
+            self.statistics.sent(AMAZON_PORT);
+            self.output.send(value, Some(timestamp)).await?;
+        }
+        Ok(())
     }
 }
@@ -0,0 +1,346 @@
+//
+// Copyright (c) 2022 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+//! Encoding and decoding of every message of `data_types`, with protobuf and CDR.
+//!
+//! Each message is benchmarked under `<message>/<codec>/`:
+//! - `encode/fresh`: encoding into a newly allocated buffer, through the `serialize_*` function of
+//!   the message for protobuf;
+//! - `encode/reuse`: encoding into a buffer cleared after each message, as the ports do;
+//! - `decode`: decoding, through the `deserialize_*` function of the message for protobuf.
+//!
+//! The sensor messages are benchmarked at several sizes, e.g. `image/1920x1080/protobuf/decode`.
+//!
+//! ```shell
+//! cargo bench -p datatypes -- 'image/.*/decode'
+//! ```
+
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use datatypes::codec::{Codec, Payload};
+use datatypes::data_types;
+use datatypes::datagen::DataGen;
+use datatypes::sensors::{Camera, Lidar2d, Lidar3d};
+use rand::Rng;
+
+/// The encoding of a message into a new buffer, and its decoding, with protobuf.
+struct Protobuf<T> {
+    serialize: fn(&T) -> Vec<u8>,
+    deserialize: fn(&[u8]) -> Result<T, prost::DecodeError>,
+}
+
+impl<T: Payload> Protobuf<T> {
+    /// For the messages without their own `serialize_*` and `deserialize_*` functions.
+    fn generic() -> Self {
+        Self {
+            serialize: |message| message.encode_to_vec(),
+            deserialize: |buf| T::decode(buf),
+        }
+    }
+}
+
+fn bench<T: Payload>(c: &mut Criterion, name: &str, message: &T, protobuf: Protobuf<T>) {
+    let mut group = c.benchmark_group(name);
+
+    for codec in [Codec::Protobuf, Codec::Cdr] {
+        let mut encoded = Vec::new();
+        codec.encode(message, &mut encoded).unwrap();
+        group.throughput(Throughput::Bytes(encoded.len() as u64));
+
+        let id = |operation: &str| BenchmarkId::new(codec.name(), operation);
+        match codec {
+            Codec::Protobuf => {
+                group.bench_function(id("encode/fresh"), |b| {
+                    b.iter(|| (protobuf.serialize)(black_box(message)))
+                });
+            }
+            _ => {
+                group.bench_function(id("encode/fresh"), |b| {
+                    b.iter(|| {
+                        let mut buf = Vec::new();
+                        codec.encode(black_box(message), &mut buf).unwrap();
+                        buf
+                    })
+                });
+            }
+        }
+
+        let mut buf = Vec::with_capacity(encoded.len());
+        group.bench_function(id("encode/reuse"), |b| {
+            b.iter(|| {
+                buf.clear();
+                codec.encode(black_box(message), &mut buf).unwrap();
+            })
+        });
+
+        match codec {
+            Codec::Protobuf => group.bench_function(id("decode"), |b| {
+                b.iter(|| (protobuf.deserialize)(black_box(&encoded)).unwrap())
+            }),
+            _ => group.bench_function(id("decode"), |b| {
+                b.iter(|| codec.decode::<T>(black_box(&encoded)).unwrap())
+            }),
+        };
+    }
+
+    group.finish();
+}
+
+fn geometry(c: &mut Criterion) {
+    let mut datagen = DataGen::from_seed(0);
+
+    bench(
+        c,
+        "timestamp",
+        &data_types::Timestamp {
+            sec: 1_700_000_000,
+            nanosec: 123_456_789,
+        },
+        Protobuf::generic(),
+    );
+    bench(
+        c,
+        "header",
+        &datagen.gen::<data_types::Header>(),
+        Protobuf::generic(),
+    );
+
+    let point = datagen.gen();
+    let quaternion = datagen.gen();
+    let vector3 = datagen.gen::<data_types::Vector3>();
+    let header = datagen.gen::<data_types::Header>();
+    bench(
+        c,
+        "point",
+        &point,
+        Protobuf {
+            serialize: datatypes::serialize_point,
+            deserialize: datatypes::deserialize_point,
+        },
+    );
+    bench(
+        c,
+        "quaternion",
+        &quaternion,
+        Protobuf {
+            serialize: datatypes::serialize_quaternion,
+            deserialize: datatypes::deserialize_quaternion,
+        },
+    );
+    bench(
+        c,
+        "vector3",
+        &vector3,
+        Protobuf {
+            serialize: datatypes::serialize_vector3,
+            deserialize: datatypes::deserialize_vector3,
+        },
+    );
+    bench(
+        c,
+        "vector3_stamped",
+        &data_types::Vector3Stamped {
+            header: Some(header.clone()),
+            vector: Some(vector3.clone()),
+        },
+        Protobuf {
+            serialize: datatypes::serialize_vector3_stamped,
+            deserialize: datatypes::deserialize_vector3_stamped,
+        },
+    );
+    bench(
+        c,
+        "pose",
+        &data_types::Pose {
+            position: Some(point),
+            orientation: Some(quaternion),
+        },
+        Protobuf {
+            serialize: datatypes::serialize_pose,
+            deserialize: datatypes::deserialize_pose,
+        },
+    );
+
+    // The nested messages are all set, as they are in ROS 2.
+    let twist = data_types::Twist {
+        linear: Some(datagen.gen()),
+        angular: Some(datagen.gen()),
+    };
+    let twist_with_covariance = data_types::TwistWithCovariance {
+        twist: Some(twist.clone()),
+        covariance: datagen.doubles(36),
+    };
+    let wrench = data_types::Wrench {
+        force: Some(datagen.gen()),
+        torque: Some(datagen.gen()),
+    };
+    bench(
+        c,
+        "twist",
+        &twist,
+        Protobuf {
+            serialize: datatypes::serialize_twist,
+            deserialize: datatypes::deserialize_twist,
+        },
+    );
+    bench(
+        c,
+        "twist_with_covariance",
+        &twist_with_covariance,
+        Protobuf {
+            serialize: datatypes::serialize_twist_with_covariance,
+            deserialize: datatypes::deserialize_twist_with_covariance,
+        },
+    );
+    bench(
+        c,
+        "twist_with_covariance_stamped",
+        &data_types::TwistWithCovarianceStamped {
+            header: Some(header.clone()),
+            twist: Some(twist_with_covariance),
+        },
+        Protobuf {
+            serialize: datatypes::serialize_twist_with_covariance_stamped,
+            deserialize: datatypes::deserialize_twist_with_covariance_stamped,
+        },
+    );
+    bench(
+        c,
+        "wrench",
+        &wrench,
+        Protobuf {
+            serialize: datatypes::serialize_wrench,
+            deserialize: datatypes::deserialize_wrench,
+        },
+    );
+    bench(
+        c,
+        "wrench_stamped",
+        &data_types::WrenchStamped {
+            header: Some(header),
+            wrench: Some(wrench),
+        },
+        Protobuf {
+            serialize: datatypes::serialize_wrench_stamped,
+            deserialize: datatypes::deserialize_wrench_stamped,
+        },
+    );
+}
+
+fn primitives(c: &mut Criterion) {
+    let mut datagen = DataGen::from_seed(0);
+
+    bench(
+        c,
+        "float32",
+        &data_types::Float32 {
+            value: datagen.rng().gen(),
+        },
+        Protobuf::generic(),
+    );
+    bench(
+        c,
+        "float64",
+        &data_types::Float64 {
+            value: datagen.rng().gen(),
+        },
+        Protobuf::generic(),
+    );
+    bench(
+        c,
+        "int32",
+        &data_types::Int32 {
+            value: datagen.rng().gen(),
+        },
+        Protobuf::generic(),
+    );
+    bench(
+        c,
+        "int64",
+        &data_types::Int64 {
+            value: datagen.rng().gen(),
+        },
+        Protobuf::generic(),
+    );
+    // The size of the strings sent by Portsmouth, and of a larger payload.
+    for length in [256, 65536] {
+        bench(
+            c,
+            &format!("string/{}", length),
+            &data_types::String {
+                value: datagen.string(length),
+            },
+            Protobuf::generic(),
+        );
+    }
+}
+
+fn sensors(c: &mut Criterion) {
+    let mut datagen = DataGen::from_seed(0);
+
+    for (width, height) in [(320, 240), (640, 480), (1920, 1080)] {
+        let header = datagen.gen();
+        let camera = Camera {
+            width,
+            height,
+            ..Camera::default()
+        };
+        bench(
+            c,
+            &format!("image/{}x{}", width, height),
+            &camera.capture(Some(header), datagen.rng()),
+            Protobuf {
+                serialize: datatypes::serialize_image,
+                deserialize: datatypes::deserialize_image,
+            },
+        );
+    }
+
+    for (lasers, columns) in [(16, 512), (32, 1024), (64, 2048)] {
+        let header = datagen.gen();
+        let lidar = Lidar3d {
+            lasers,
+            columns,
+            ..Lidar3d::default()
+        };
+        bench(
+            c,
+            &format!("pointcloud2/{}x{}", lasers, columns),
+            &lidar.scan(Some(header), datagen.rng()),
+            Protobuf {
+                serialize: datatypes::serialize_pointcloud2,
+                deserialize: datatypes::deserialize_pointcloud2,
+            },
+        );
+    }
+
+    for beams in [360, 1024, 4096] {
+        let header = datagen.gen();
+        let lidar = Lidar2d {
+            beams,
+            ..Lidar2d::default()
+        };
+        bench(
+            c,
+            &format!("laserscan/{}", beams),
+            &lidar.scan(Some(header), datagen.rng()),
+            Protobuf {
+                serialize: datatypes::serialize_laserscan,
+                deserialize: datatypes::deserialize_laserscan,
+            },
+        );
+    }
+}
+
+criterion_group!(benches, geometry, primitives, sensors);
+criterion_main!(benches);
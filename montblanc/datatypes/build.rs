@@ -15,5 +15,9 @@
 extern crate prost_build;
 
 fn main() {
-    prost_build::compile_protos(&["src/data_types.proto"], &["src/"]).unwrap();
+    // The messages are also serialized with serde by the JSON and bincode codecs.
+    prost_build::Config::new()
+        .type_attribute(".", "#[derive(serde::Serialize, serde::Deserialize)]")
+        .compile_protos(&["src/data_types.proto"], &["src/"])
+        .unwrap();
 }
@@ -0,0 +1,432 @@
+//
+// Copyright (c) 2022 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+//! The ROS 2 serialization of the messages of `data_types`: CDR (XCDR1), preceded by its 4 bytes
+//! encapsulation header.
+//!
+//! Messages are laid out as their `geometry_msgs`, `sensor_msgs` and `std_msgs` counterparts, so
+//! that they can be exchanged with ROS 2 nodes (e.g. through `zenoh-bridge-dds` or `rmw_zenoh`).
+//! They are written in little-endian, and read in either endianness.
+//!
+//! As CDR has no optional fields, a missing message (e.g. a `header` set to `None`) is written as
+//! its default value, and is always `Some` once read. `Timestamp` has no ROS 2 counterpart: it is
+//! written as its `sec` (`uint64`) followed by its `nanosec` (`uint32`).
+
+use crate::data_types;
+use crate::data_types::point_cloud2::point_field::DataType;
+use std::convert::TryFrom;
+use std::fmt;
+
+/// The encapsulation header of little-endian CDR: `CDR_LE` without options.
+pub const ENCAPSULATION_LE: [u8; 4] = [0x00, 0x01, 0x00, 0x00];
+
+const CDR_BE: [u8; 2] = [0x00, 0x00];
+const CDR_LE: [u8; 2] = [0x00, 0x01];
+
+/// The length of the `covariance` of a `TwistWithCovariance`, a 6x6 matrix.
+pub const COVARIANCE_LENGTH: usize = 36;
+
+/// Why a message could not be written or read.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CdrError {
+    /// The buffer ends before the message does.
+    UnexpectedEnd { needed: usize, remaining: usize },
+    /// The encapsulation of the buffer is not plain CDR.
+    UnsupportedEncapsulation([u8; 2]),
+    /// A string is not NUL-terminated UTF-8.
+    InvalidString,
+    /// A fixed-size array does not have the expected length.
+    ArrayLength { expected: usize, actual: usize },
+    /// The datatype of a `PointField` has no equivalent.
+    InvalidDatatype(i32),
+    /// A sequence or a string is too long for its length to be written.
+    TooLong(usize),
+}
+
+impl fmt::Display for CdrError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CdrError::UnexpectedEnd { needed, remaining } => write!(
+                f,
+                "unexpected end of buffer: {} bytes needed, {} remaining",
+                needed, remaining
+            ),
+            CdrError::UnsupportedEncapsulation(kind) => {
+                write!(f, "unsupported encapsulation {:02x?}", kind)
+            }
+            CdrError::InvalidString => write!(f, "invalid string"),
+            CdrError::ArrayLength { expected, actual } => {
+                write!(f, "array of {} elements, expected {}", actual, expected)
+            }
+            CdrError::InvalidDatatype(datatype) => write!(f, "invalid datatype: {}", datatype),
+            CdrError::TooLong(length) => write!(f, "{} elements are too many to write", length),
+        }
+    }
+}
+
+impl std::error::Error for CdrError {}
+
+/// Appends CDR to a buffer, aligning each primitive on its size from the start of the message.
+pub struct CdrWriter<'a> {
+    buf: &'a mut Vec<u8>,
+    origin: usize,
+}
+
+impl<'a> CdrWriter<'a> {
+    /// Writes the message after the current end of `buf`, which must be the end of the
+    /// encapsulation header.
+    pub fn new(buf: &'a mut Vec<u8>) -> Self {
+        let origin = buf.len();
+        Self { buf, origin }
+    }
+
+    fn align(&mut self, size: usize) {
+        let padding = (size - (self.buf.len() - self.origin) % size) % size;
+        self.buf.resize(self.buf.len() + padding, 0);
+    }
+
+    fn primitive<const N: usize>(&mut self, bytes: [u8; N]) {
+        self.align(N);
+        self.buf.extend_from_slice(&bytes);
+    }
+
+    fn length(&mut self, length: usize) -> Result<(), CdrError> {
+        let length = u32::try_from(length).map_err(|_| CdrError::TooLong(length))?;
+        self.primitive(length.to_le_bytes());
+        Ok(())
+    }
+
+    /// Writes a `sequence<octet>`.
+    pub fn bytes(&mut self, bytes: &[u8]) -> Result<(), CdrError> {
+        self.length(bytes.len())?;
+        self.buf.extend_from_slice(bytes);
+        Ok(())
+    }
+
+    /// Writes a sequence: its length, then its elements.
+    pub fn sequence<T: Cdr>(&mut self, elements: &[T]) -> Result<(), CdrError> {
+        self.length(elements.len())?;
+        self.array(elements, elements.len())
+    }
+
+    /// Writes an array of `length` elements, which `elements` must have.
+    pub fn array<T: Cdr>(&mut self, elements: &[T], length: usize) -> Result<(), CdrError> {
+        if elements.len() != length {
+            return Err(CdrError::ArrayLength {
+                expected: length,
+                actual: elements.len(),
+            });
+        }
+        elements.iter().try_for_each(|element| element.write(self))
+    }
+}
+
+/// Reads CDR from a buffer, in the endianness of its encapsulation.
+pub struct CdrReader<'a> {
+    buf: &'a [u8],
+    position: usize,
+    big_endian: bool,
+}
+
+impl<'a> CdrReader<'a> {
+    /// Reads the message starting at the beginning of `buf`, right after the encapsulation header.
+    pub fn new(buf: &'a [u8], big_endian: bool) -> Self {
+        Self {
+            buf,
+            position: 0,
+            big_endian,
+        }
+    }
+
+    fn take(&mut self, length: usize) -> Result<&'a [u8], CdrError> {
+        let remaining = self.buf.len() - self.position;
+        if length > remaining {
+            return Err(CdrError::UnexpectedEnd {
+                needed: length,
+                remaining,
+            });
+        }
+        let bytes = &self.buf[self.position..self.position + length];
+        self.position += length;
+        Ok(bytes)
+    }
+
+    fn align(&mut self, size: usize) -> Result<(), CdrError> {
+        self.take((size - self.position % size) % size).map(|_| ())
+    }
+
+    /// The bytes of a primitive of `N` bytes, in little-endian.
+    fn primitive<const N: usize>(&mut self) -> Result<[u8; N], CdrError> {
+        self.align(N)?;
+        let mut bytes = [0; N];
+        bytes.copy_from_slice(self.take(N)?);
+        if self.big_endian {
+            bytes.reverse();
+        }
+        Ok(bytes)
+    }
+
+    fn length(&mut self) -> Result<usize, CdrError> {
+        Ok(u32::from_le_bytes(self.primitive()?) as usize)
+    }
+
+    /// Reads a `sequence<octet>`.
+    pub fn bytes(&mut self) -> Result<&'a [u8], CdrError> {
+        let length = self.length()?;
+        self.take(length)
+    }
+
+    /// Reads a sequence: its length, then its elements.
+    pub fn sequence<T: Cdr>(&mut self) -> Result<Vec<T>, CdrError> {
+        let length = self.length()?;
+        self.array(length)
+    }
+
+    /// Reads an array of `length` elements.
+    pub fn array<T: Cdr>(&mut self, length: usize) -> Result<Vec<T>, CdrError> {
+        // Each element takes at least a byte: a corrupted length cannot allocate more than the
+        // buffer.
+        let mut elements = Vec::with_capacity(length.min(self.buf.len() - self.position));
+        for _ in 0..length {
+            elements.push(T::read(self)?);
+        }
+        Ok(elements)
+    }
+}
+
+/// A value with a CDR representation.
+pub trait Cdr: Sized {
+    fn write(&self, writer: &mut CdrWriter) -> Result<(), CdrError>;
+    fn read(reader: &mut CdrReader) -> Result<Self, CdrError>;
+}
+
+macro_rules! cdr_primitive {
+    ($($primitive:ty),* $(,)?) => {
+        $(
+            impl Cdr for $primitive {
+                fn write(&self, writer: &mut CdrWriter) -> Result<(), CdrError> {
+                    writer.primitive(self.to_le_bytes());
+                    Ok(())
+                }
+
+                fn read(reader: &mut CdrReader) -> Result<Self, CdrError> {
+                    reader.primitive().map(<$primitive>::from_le_bytes)
+                }
+            }
+        )*
+    };
+}
+
+cdr_primitive!(i32, u32, i64, u64, f32, f64);
+
+impl Cdr for bool {
+    fn write(&self, writer: &mut CdrWriter) -> Result<(), CdrError> {
+        writer.primitive([*self as u8]);
+        Ok(())
+    }
+
+    fn read(reader: &mut CdrReader) -> Result<Self, CdrError> {
+        reader.primitive::<1>().map(|[byte]| byte != 0)
+    }
+}
+
+/// A string: its length, including its terminating NUL, then its bytes and the NUL.
+impl Cdr for String {
+    fn write(&self, writer: &mut CdrWriter) -> Result<(), CdrError> {
+        writer.length(self.len() + 1)?;
+        writer.buf.extend_from_slice(self.as_bytes());
+        writer.buf.push(0);
+        Ok(())
+    }
+
+    fn read(reader: &mut CdrReader) -> Result<Self, CdrError> {
+        let length = reader.length()?;
+        if length == 0 {
+            return Ok(String::new());
+        }
+        match reader.take(length)?.split_last() {
+            Some((0, bytes)) => {
+                String::from_utf8(bytes.to_vec()).map_err(|_| CdrError::InvalidString)
+            }
+            _ => Err(CdrError::InvalidString),
+        }
+    }
+}
+
+impl Cdr for Vec<u8> {
+    fn write(&self, writer: &mut CdrWriter) -> Result<(), CdrError> {
+        writer.bytes(self)
+    }
+
+    fn read(reader: &mut CdrReader) -> Result<Self, CdrError> {
+        reader.bytes().map(<[u8]>::to_vec)
+    }
+}
+
+impl<T: Cdr> Cdr for Vec<T> {
+    fn write(&self, writer: &mut CdrWriter) -> Result<(), CdrError> {
+        writer.sequence(self)
+    }
+
+    fn read(reader: &mut CdrReader) -> Result<Self, CdrError> {
+        reader.sequence()
+    }
+}
+
+/// A message that is always present in CDR, written as its default value when missing.
+impl<T: Cdr + Default> Cdr for Option<T> {
+    fn write(&self, writer: &mut CdrWriter) -> Result<(), CdrError> {
+        match self {
+            Some(message) => message.write(writer),
+            None => T::default().write(writer),
+        }
+    }
+
+    fn read(reader: &mut CdrReader) -> Result<Self, CdrError> {
+        T::read(reader).map(Some)
+    }
+}
+
+/// Implements `Cdr` for messages whose fields are written one after the other, in this order.
+macro_rules! cdr_message {
+    ($($message:ident { $($field:ident),* $(,)? })*) => {
+        $(
+            impl Cdr for data_types::$message {
+                fn write(&self, writer: &mut CdrWriter) -> Result<(), CdrError> {
+                    $(self.$field.write(writer)?;)*
+                    Ok(())
+                }
+
+                fn read(reader: &mut CdrReader) -> Result<Self, CdrError> {
+                    Ok(Self {
+                        $($field: Cdr::read(reader)?,)*
+                    })
+                }
+            }
+        )*
+    };
+}
+
+cdr_message! {
+    Timestamp { sec, nanosec }
+    // `std_msgs/Header`, whose `builtin_interfaces/Time` stamp is inlined.
+    Header { sec, nanosec, frame_id }
+    Point { x, y, z }
+    Quaternion { x, y, z, w }
+    Vector3 { x, y, z }
+    Vector3Stamped { header, vector }
+    Pose { position, orientation }
+    Twist { linear, angular }
+    TwistWithCovarianceStamped { header, twist }
+    Wrench { force, torque }
+    WrenchStamped { header, wrench }
+    Image { header, height, width, encoding, is_bigendian, step, data }
+    PointCloud2 { header, height, width, fields, is_bigendian, point_step, row_step, data, is_dense }
+    LaserScan {
+        header,
+        angle_min,
+        angle_max,
+        angle_increment,
+        time_increment,
+        scan_time,
+        range_min,
+        range_max,
+        ranges,
+        intensities,
+    }
+    Float32 { value }
+    Int64 { value }
+    Int32 { value }
+    Float64 { value }
+    String { value }
+}
+
+/// The `covariance` is a `float64[36]`, without length. An empty one, e.g. of a missing message, is
+/// written as zeros.
+impl Cdr for data_types::TwistWithCovariance {
+    fn write(&self, writer: &mut CdrWriter) -> Result<(), CdrError> {
+        self.twist.write(writer)?;
+        if self.covariance.is_empty() {
+            writer.array(&[0.0; COVARIANCE_LENGTH], COVARIANCE_LENGTH)
+        } else {
+            writer.array(&self.covariance, COVARIANCE_LENGTH)
+        }
+    }
+
+    fn read(reader: &mut CdrReader) -> Result<Self, CdrError> {
+        Ok(Self {
+            twist: Cdr::read(reader)?,
+            covariance: reader.array(COVARIANCE_LENGTH)?,
+        })
+    }
+}
+
+/// The `datatype` is a `uint8`, whose constants start at 1 (`INT8`) in `sensor_msgs/PointField`.
+impl Cdr for data_types::point_cloud2::PointField {
+    fn write(&self, writer: &mut CdrWriter) -> Result<(), CdrError> {
+        let datatype =
+            DataType::from_i32(self.datatype).ok_or(CdrError::InvalidDatatype(self.datatype))?;
+        self.name.write(writer)?;
+        self.offset.write(writer)?;
+        writer.primitive([datatype as u8 + 1]);
+        self.count.write(writer)
+    }
+
+    fn read(reader: &mut CdrReader) -> Result<Self, CdrError> {
+        let name = Cdr::read(reader)?;
+        let offset = Cdr::read(reader)?;
+        let [datatype] = reader.primitive::<1>()?;
+        let datatype = datatype as i32 - 1;
+        if DataType::from_i32(datatype).is_none() {
+            return Err(CdrError::InvalidDatatype(datatype));
+        }
+        Ok(Self {
+            name,
+            offset,
+            datatype,
+            count: Cdr::read(reader)?,
+        })
+    }
+}
+
+/// Appends the encapsulation header and the CDR of `message` to `buf`.
+pub fn encode<T: Cdr>(message: &T, buf: &mut Vec<u8>) -> Result<(), CdrError> {
+    buf.extend_from_slice(&ENCAPSULATION_LE);
+    message.write(&mut CdrWriter::new(buf))
+}
+
+/// Returns the encapsulation header and the CDR of `message`.
+pub fn serialize<T: Cdr>(message: &T) -> Result<Vec<u8>, CdrError> {
+    let mut buf = Vec::new();
+    encode(message, &mut buf)?;
+    Ok(buf)
+}
+
+/// Reads a message from `buf`, starting with its encapsulation header. Bytes after the message,
+/// e.g. padding, are ignored.
+pub fn deserialize<T: Cdr>(buf: &[u8]) -> Result<T, CdrError> {
+    if buf.len() < ENCAPSULATION_LE.len() {
+        return Err(CdrError::UnexpectedEnd {
+            needed: ENCAPSULATION_LE.len(),
+            remaining: buf.len(),
+        });
+    }
+    let (header, message) = buf.split_at(ENCAPSULATION_LE.len());
+    let big_endian = match [header[0], header[1]] {
+        CDR_LE => false,
+        CDR_BE => true,
+        other => return Err(CdrError::UnsupportedEncapsulation(other)),
+    };
+    T::read(&mut CdrReader::new(message, big_endian))
+}
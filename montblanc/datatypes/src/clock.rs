@@ -0,0 +1,19 @@
+//
+// Copyright (c) 2022 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+//! The time source of the nodes: the `zf-clock` crate, shared with the other examples and the test
+//! harness. A node reads its clock from the `clock` key of its configuration, through its `DataGen`
+//! and its `Ticker`.
+
+pub use zf_clock::{Clock, ManualClock, CLOCK_KEY};
@@ -0,0 +1,132 @@
+//
+// Copyright (c) 2022 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+//! The serialization formats of the messages exchanged by the nodes.
+//!
+//! The codec of a node is set by the `codec` of its configuration: `protobuf` (the default), `cdr`,
+//! `json` or `bincode`. The nodes at both ends of a link must use the same codec.
+
+use crate::cdr::{self, Cdr};
+use crate::Configurable;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::error::Error;
+use std::fmt;
+use zenoh_flow::prelude as zf;
+use zenoh_flow::zferror;
+
+static CODEC_KEY: &str = "codec";
+
+/// A message that every codec can encode: all the messages of `data_types`.
+pub trait Payload: prost::Message + Cdr + Serialize + DeserializeOwned + Default + 'static {}
+
+impl<T> Payload for T where
+    T: prost::Message + Cdr + Serialize + DeserializeOwned + Default + 'static
+{
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Codec {
+    #[default]
+    Protobuf,
+    /// ROS 2 serialization, see `cdr`.
+    Cdr,
+    Json,
+    Bincode,
+}
+
+/// Why a message could not be encoded or decoded.
+#[derive(Debug)]
+pub struct CodecError {
+    pub codec: Codec,
+    source: Box<dyn Error + Send + Sync>,
+}
+
+impl fmt::Display for CodecError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.codec.name(), self.source)
+    }
+}
+
+impl Error for CodecError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        Some(self.source.as_ref())
+    }
+}
+
+impl Codec {
+    pub const ALL: [Codec; 4] = [Codec::Protobuf, Codec::Cdr, Codec::Json, Codec::Bincode];
+
+    /// The name of the codec in the configuration.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Codec::Protobuf => "protobuf",
+            Codec::Cdr => "cdr",
+            Codec::Json => "json",
+            Codec::Bincode => "bincode",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.iter().copied().find(|codec| codec.name() == name)
+    }
+
+    fn error(&self, source: impl Into<Box<dyn Error + Send + Sync>>) -> CodecError {
+        CodecError {
+            codec: *self,
+            source: source.into(),
+        }
+    }
+
+    /// Appends the encoding of `message` to `buf`.
+    pub fn encode<T: Payload>(&self, message: &T, buf: &mut Vec<u8>) -> Result<(), CodecError> {
+        match self {
+            Codec::Protobuf => {
+                buf.reserve(message.encoded_len());
+                message.encode(buf).map_err(|e| self.error(e))
+            }
+            Codec::Cdr => cdr::encode(message, buf).map_err(|e| self.error(e)),
+            Codec::Json => serde_json::to_writer(buf, message).map_err(|e| self.error(e)),
+            Codec::Bincode => bincode::serialize_into(buf, message).map_err(|e| self.error(e)),
+        }
+    }
+
+    pub fn decode<T: Payload>(&self, buf: &[u8]) -> Result<T, CodecError> {
+        match self {
+            Codec::Protobuf => T::decode(buf).map_err(|e| self.error(e)),
+            Codec::Cdr => cdr::deserialize(buf).map_err(|e| self.error(e)),
+            Codec::Json => serde_json::from_slice(buf).map_err(|e| self.error(e)),
+            Codec::Bincode => bincode::deserialize(buf).map_err(|e| self.error(e)),
+        }
+    }
+}
+
+impl Configurable for Codec {
+    /// Replaces the codec by the `codec` of the configuration, if any.
+    fn configure(self, configuration: Option<&zf::Configuration>) -> zf::Result<Self> {
+        let value = match configuration.and_then(|configuration| configuration.get(CODEC_KEY)) {
+            Some(value) => value,
+            None => return Ok(self),
+        };
+        value.as_str().and_then(Codec::from_name).ok_or_else(|| {
+            zferror!(
+                zf::ErrorKind::ConfigurationError,
+                "`{}` must be one of `protobuf`, `cdr`, `json` or `bincode`, found: {}",
+                CODEC_KEY,
+                value
+            )
+            .into()
+        })
+    }
+}
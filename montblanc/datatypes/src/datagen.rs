@@ -0,0 +1,194 @@
+//
+// Copyright (c) 2022 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+//! Reproducible generation of the messages of `data_types`.
+//!
+//! A [`DataGen`] created with [`DataGen::from_seed`] always generates the same messages, their
+//! headers being stamped by a [`ManualClock`] rather than by the time of the system. The nodes
+//! get their generators through the [`Generate`] trait: if `seed` is set in their configuration,
+//! the data they send is identical from one run to the next. Their random number generator is
+//! `ChaCha8Rng`, whose output, contrary to the one of `StdRng`, does not change with the version
+//! of `rand`.
+
+use crate::clock::{Clock, ManualClock};
+use crate::Configurable;
+use rand::distributions::{Alphanumeric, Distribution};
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+use std::sync::Arc;
+use std::time::Duration;
+use zenoh_flow::prelude as zf;
+use zenoh_flow::zferror;
+
+static SEED_KEY: &str = "seed";
+
+/// The distribution of the messages of `data_types`, their headers being stamped with `clock`.
+///
+/// The `Standard` distribution of the messages is the `Sampler` of the clock of the system.
+#[derive(Debug, Clone, Copy)]
+pub struct Sampler<'a> {
+    pub clock: &'a Clock,
+}
+
+impl<'a> Sampler<'a> {
+    pub fn new(clock: &'a Clock) -> Self {
+        Self { clock }
+    }
+}
+
+pub(crate) fn sample_string<R: Rng + ?Sized>(rng: &mut R, length: usize) -> String {
+    rng.sample_iter(Alphanumeric)
+        .take(length)
+        .map(char::from)
+        .collect()
+}
+
+pub(crate) fn sample_vec<T, R: Rng + ?Sized>(rng: &mut R, length: usize) -> Vec<T>
+where
+    rand::distributions::Standard: Distribution<T>,
+{
+    (0..length).map(|_| rng.gen()).collect()
+}
+
+/// A generator of messages, seeded and stamped with an injectable clock.
+#[derive(Debug, Clone)]
+pub struct DataGen {
+    rng: ChaCha8Rng,
+    clock: Clock,
+}
+
+impl Default for DataGen {
+    fn default() -> Self {
+        Self::from_entropy()
+    }
+}
+
+impl DataGen {
+    /// A generator whose output only depends on `seed`: its clock is a `ManualClock` set to the
+    /// UNIX epoch.
+    pub fn from_seed(seed: u64) -> Self {
+        Self {
+            rng: ChaCha8Rng::seed_from_u64(seed),
+            clock: ManualClock::starting_at(Duration::ZERO).clock(),
+        }
+    }
+
+    /// A generator seeded by the operating system, stamping messages with the time of the system.
+    pub fn from_entropy() -> Self {
+        Self {
+            rng: ChaCha8Rng::from_entropy(),
+            clock: Clock::system(),
+        }
+    }
+
+    pub fn with_clock(mut self, clock: Clock) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    pub fn clock(&self) -> &Clock {
+        &self.clock
+    }
+
+    pub fn rng(&mut self) -> &mut ChaCha8Rng {
+        &mut self.rng
+    }
+
+    /// Returns an independent generator, seeded by this one and sharing its clock.
+    pub fn fork(&mut self) -> Self {
+        Self {
+            rng: ChaCha8Rng::seed_from_u64(self.rng.gen()),
+            clock: self.clock.clone(),
+        }
+    }
+
+    /// Generates a message. Other random values are obtained through `rng()`.
+    pub fn gen<T>(&mut self) -> T
+    where
+        for<'a> Sampler<'a>: Distribution<T>,
+    {
+        let sampler = Sampler::new(&self.clock);
+        self.rng.sample(sampler)
+    }
+
+    pub fn string(&mut self, length: usize) -> String {
+        sample_string(&mut self.rng, length)
+    }
+
+    pub fn bytes(&mut self, length: usize) -> Vec<u8> {
+        sample_vec(&mut self.rng, length)
+    }
+
+    pub fn floats(&mut self, length: usize) -> Vec<f32> {
+        sample_vec(&mut self.rng, length)
+    }
+
+    pub fn doubles(&mut self, length: usize) -> Vec<f64> {
+        sample_vec(&mut self.rng, length)
+    }
+}
+
+impl Configurable for DataGen {
+    /// Replaces the generator by one seeded with the `seed` of the configuration, if any, and its
+    /// clock by the `clock` of the configuration, if any.
+    fn configure(self, configuration: Option<&zf::Configuration>) -> zf::Result<Self> {
+        let datagen = match configuration.and_then(|configuration| configuration.get(SEED_KEY)) {
+            Some(seed) => {
+                let seed = seed.as_u64().ok_or_else(|| {
+                    zferror!(
+                        zf::ErrorKind::ConfigurationError,
+                        "`{}` must be an unsigned integer, found: {}",
+                        SEED_KEY,
+                        seed
+                    )
+                })?;
+                Self::from_seed(seed)
+            }
+            None => self,
+        };
+        match Clock::configured(configuration)? {
+            Some(clock) => Ok(datagen.with_clock(clock)),
+            None => Ok(datagen),
+        }
+    }
+}
+
+/// A value generated, at the creation of a node, from the `DataGen` of the node.
+pub trait Generate {
+    fn generate(datagen: &mut DataGen) -> Self;
+}
+
+impl Generate for DataGen {
+    fn generate(datagen: &mut DataGen) -> Self {
+        datagen.fork()
+    }
+}
+
+impl<T: Generate> Generate for Arc<T> {
+    fn generate(datagen: &mut DataGen) -> Self {
+        Arc::new(T::generate(datagen))
+    }
+}
+
+impl<T: Generate> Generate for std::sync::Mutex<T> {
+    fn generate(datagen: &mut DataGen) -> Self {
+        std::sync::Mutex::new(T::generate(datagen))
+    }
+}
+
+impl<T: Generate> Generate for async_std::sync::Mutex<T> {
+    fn generate(datagen: &mut DataGen) -> Self {
+        async_std::sync::Mutex::new(T::generate(datagen))
+    }
+}
@@ -0,0 +1,351 @@
+//
+// Copyright (c) 2022 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+//! The outputs of the operators, computed from their latest inputs.
+//!
+//! By default, the operators send messages generated once, when they are created, whatever they
+//! receive. With `outputs: derived` in their configuration, they compute their outputs from the
+//! latest message received on each input instead, so that the data flowing out of a node depends
+//! on the data flowing in. The stamped outputs carry the header of the input they are derived
+//! from, and the messages computed are valid (see `validation`).
+
+use crate::data_types;
+use crate::image::{self, ImageError, ImageView, Mono8, Pixel};
+use crate::pointcloud::{CloudBuilder, CloudView, PointXYZ, PointXYZI};
+use crate::Configurable;
+use std::f32::consts::FRAC_PI_2;
+use zenoh_flow::prelude as zf;
+use zenoh_flow::zferror;
+
+static OUTPUTS_KEY: &str = "outputs";
+
+/// The field of view of the scans computed from an image, in radians.
+pub const SCAN_FIELD_OF_VIEW: f32 = FRAC_PI_2;
+/// The ranges of the scans computed from an image, in meters.
+pub const SCAN_RANGE_MIN: f32 = 0.1;
+pub const SCAN_RANGE_MAX: f32 = 30.0;
+
+/// How an operator computes its outputs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Outputs {
+    /// Messages generated once, when the operator is created.
+    #[default]
+    Random,
+    /// Messages computed from the latest inputs.
+    Derived,
+}
+
+impl Outputs {
+    pub const ALL: [Outputs; 2] = [Outputs::Random, Outputs::Derived];
+
+    /// The name of the outputs in the configuration.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Outputs::Random => "random",
+            Outputs::Derived => "derived",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL
+            .iter()
+            .copied()
+            .find(|outputs| outputs.name() == name)
+    }
+}
+
+impl Configurable for Outputs {
+    /// Replaces the outputs by the `outputs` of the configuration, if any.
+    fn configure(self, configuration: Option<&zf::Configuration>) -> zf::Result<Self> {
+        let value = match configuration.and_then(|configuration| configuration.get(OUTPUTS_KEY)) {
+            Some(value) => value,
+            None => return Ok(self),
+        };
+        value.as_str().and_then(Outputs::from_name).ok_or_else(|| {
+            zferror!(
+                zf::ErrorKind::ConfigurationError,
+                "`{}` must be `random` or `derived`, found: {}",
+                OUTPUTS_KEY,
+                value
+            )
+            .into()
+        })
+    }
+}
+
+fn norm(vector: &data_types::Vector3) -> f64 {
+    (vector.x * vector.x + vector.y * vector.y + vector.z * vector.z).sqrt()
+}
+
+/// `quaternion` scaled to a norm of 1, or the identity if it cannot be.
+fn normalise(quaternion: &data_types::Quaternion) -> data_types::Quaternion {
+    let data_types::Quaternion { x, y, z, w } = *quaternion;
+    let norm = (x * x + y * y + z * z + w * w).sqrt();
+    if norm.is_normal() {
+        data_types::Quaternion {
+            x: x / norm,
+            y: y / norm,
+            z: z / norm,
+            w: w / norm,
+        }
+    } else {
+        data_types::Quaternion {
+            x: 0.0,
+            y: 0.0,
+            z: 0.0,
+            w: 1.0,
+        }
+    }
+}
+
+/// The rotation of `quaternion` as a rotation vector: its axis, scaled by its angle.
+fn rotation_vector(quaternion: &data_types::Quaternion) -> data_types::Vector3 {
+    let data_types::Quaternion { x, y, z, w } = normalise(quaternion);
+    let sin = (x * x + y * y + z * z).sqrt();
+    let scale = if sin > f64::EPSILON {
+        2.0 * sin.atan2(w) / sin
+    } else {
+        2.0
+    };
+    data_types::Vector3 {
+        x: x * scale,
+        y: y * scale,
+        z: z * scale,
+    }
+}
+
+/// The pose at `position`, facing `orientation` once normalised.
+pub fn pose(
+    orientation: &data_types::Quaternion,
+    position: &data_types::Vector3,
+) -> data_types::Pose {
+    data_types::Pose {
+        position: Some(data_types::Point {
+            x: position.x,
+            y: position.y,
+            z: position.z,
+        }),
+        orientation: Some(normalise(orientation)),
+    }
+}
+
+/// Moving at `velocity`, while turning from the identity to the orientation of `pose` in a
+/// second.
+pub fn twist(pose: &data_types::Pose, velocity: &data_types::Vector3) -> data_types::Twist {
+    let angular = pose
+        .orientation
+        .as_ref()
+        .map(rotation_vector)
+        .unwrap_or_default();
+    data_types::Twist {
+        linear: Some(velocity.clone()),
+        angular: Some(angular),
+    }
+}
+
+/// `twist` with a diagonal covariance: the standard deviation of its linear components is
+/// `linear_deviation`, and of its angular ones `angular_deviation`.
+pub fn twist_with_covariance(
+    header: Option<data_types::Header>,
+    twist: data_types::Twist,
+    linear_deviation: f64,
+    angular_deviation: f64,
+) -> data_types::TwistWithCovarianceStamped {
+    let mut covariance = vec![0.0; 36];
+    for axis in 0..3 {
+        covariance[axis * 7] = linear_deviation * linear_deviation;
+        covariance[(axis + 3) * 7] = angular_deviation * angular_deviation;
+    }
+    data_types::TwistWithCovarianceStamped {
+        header,
+        twist: Some(data_types::TwistWithCovariance {
+            twist: Some(twist),
+            covariance,
+        }),
+    }
+}
+
+/// The norm of the linear velocity of `twist`.
+pub fn speed(twist: &data_types::Twist) -> data_types::Float32 {
+    data_types::Float32 {
+        value: twist.linear.as_ref().map(norm).unwrap_or_default() as f32,
+    }
+}
+
+/// The work of `force` along `displacement`.
+pub fn work(
+    force: &data_types::Vector3,
+    displacement: &data_types::Vector3,
+) -> data_types::Float64 {
+    data_types::Float64 {
+        value: force.x * displacement.x + force.y * displacement.y + force.z * displacement.z,
+    }
+}
+
+/// The gray levels of the middle row of `image`, which may be of any encoding supported by
+/// `image::convert`.
+fn middle_row(image: &data_types::Image) -> Result<Vec<u8>, ImageError> {
+    let mono = image::convert(image, Mono8::ENCODING)?;
+    let view = ImageView::<Mono8>::new(&mono)?;
+    if view.height() == 0 {
+        return Ok(Vec::new());
+    }
+    Ok(view.row(view.height() / 2).to_vec())
+}
+
+/// A scan of the middle row of `image`: a beam per column, spread over `SCAN_FIELD_OF_VIEW` from
+/// right to left, whose range grows with the gray level of its pixel.
+///
+/// A scan has at least one beam, so an image without pixels is an error.
+pub fn scan(image: &data_types::Image) -> Result<data_types::LaserScan, ImageError> {
+    let row = middle_row(image)?;
+    if row.is_empty() {
+        return Err(ImageError::Empty);
+    }
+    let beams = row.len();
+    let angle_increment = SCAN_FIELD_OF_VIEW / beams as f32;
+    let angle_min = -SCAN_FIELD_OF_VIEW / 2.0;
+    let (ranges, intensities) = row
+        .iter()
+        .map(|&gray| {
+            let range =
+                SCAN_RANGE_MIN + (SCAN_RANGE_MAX - SCAN_RANGE_MIN) * gray as f32 / u8::MAX as f32;
+            (range, gray as f32)
+        })
+        .unzip();
+
+    Ok(data_types::LaserScan {
+        header: image.header.clone(),
+        angle_min,
+        angle_max: angle_min + (beams - 1) as f32 * angle_increment,
+        angle_increment,
+        time_increment: 0.0,
+        scan_time: 0.0,
+        range_min: SCAN_RANGE_MIN,
+        range_max: SCAN_RANGE_MAX,
+        ranges,
+        intensities,
+    })
+}
+
+/// The points hit by the beams of `scan`, in its plane, with their index. The ranges out of the
+/// bounds of the scan are not hits.
+fn hits(scan: &data_types::LaserScan) -> impl Iterator<Item = (usize, PointXYZI)> + '_ {
+    scan.ranges
+        .iter()
+        .enumerate()
+        .filter(move |(_, range)| (scan.range_min..=scan.range_max).contains(*range))
+        .map(move |(beam, range)| {
+            let angle = scan.angle_min + beam as f32 * scan.angle_increment;
+            let point = PointXYZI {
+                x: range * angle.cos(),
+                y: range * angle.sin(),
+                z: 0.0,
+                intensity: scan.intensities.get(beam).copied().unwrap_or_default(),
+            };
+            (beam, point)
+        })
+}
+
+/// The points hit by the beams of `scan`, stamped with its header.
+pub fn scan_cloud(scan: &data_types::LaserScan) -> data_types::PointCloud2 {
+    CloudBuilder::<PointXYZI>::new()
+        .header(scan.header.clone())
+        .extend(hits(scan).map(|(_, point)| point))
+        .build()
+}
+
+/// The points hit by the beams of `scan`, the intensity of each being the gray level of the
+/// column of the middle row of `image` its beam goes through: the first beam going through the
+/// first column and the last beam through the last column.
+pub fn colored_cloud(
+    scan: &data_types::LaserScan,
+    image: &data_types::Image,
+) -> Result<data_types::PointCloud2, ImageError> {
+    let row = middle_row(image)?;
+    let beams = scan.ranges.len();
+    Ok(CloudBuilder::<PointXYZI>::new()
+        .header(scan.header.clone())
+        .extend(hits(scan).map(|(beam, point)| {
+            PointXYZI {
+                intensity: row
+                    .get(beam * row.len() / beams)
+                    .map(|&gray| gray as f32)
+                    .unwrap_or_default(),
+                ..point
+            }
+        }))
+        .build())
+}
+
+fn stamp(header: &data_types::Header) -> (i32, u32) {
+    (header.sec, header.nanosec)
+}
+
+/// The points of all the `clouds`, stamped with the latest of their headers. The points of a
+/// cloud without intensities have an intensity of 0, and the invalid clouds or the clouds whose
+/// points have no coordinates are left out.
+pub fn merge(clouds: &[&data_types::PointCloud2]) -> data_types::PointCloud2 {
+    let header = clouds
+        .iter()
+        .filter_map(|cloud| cloud.header.as_ref())
+        .max_by_key(|header| stamp(header))
+        .cloned();
+
+    let mut points = Vec::new();
+    for view in clouds.iter().filter_map(|cloud| CloudView::new(cloud).ok()) {
+        match view.read::<PointXYZI>() {
+            Some(read) => points.extend(read),
+            None => {
+                if let Some(read) = view.read::<PointXYZ>() {
+                    points.extend(read.map(|PointXYZ { x, y, z }| PointXYZI {
+                        x,
+                        y,
+                        z,
+                        intensity: 0.0,
+                    }))
+                }
+            }
+        }
+    }
+
+    CloudBuilder::<PointXYZI>::new()
+        .header(header)
+        .dense(
+            points
+                .iter()
+                .all(|point| !(point.x.is_nan() || point.y.is_nan() || point.z.is_nan())),
+        )
+        .extend(points)
+        .build()
+}
+
+/// The ranges of `scan` as a single row `mono8` image stamped with its header, the nearest hits
+/// being the brightest and the missed beams black.
+pub fn range_image(scan: &data_types::LaserScan) -> data_types::Image {
+    let span = scan.range_max - scan.range_min;
+    let pixels = scan.ranges.iter().map(|range| {
+        if span > 0.0 && (scan.range_min..=scan.range_max).contains(range) {
+            Mono8((u8::MAX as f32 * (scan.range_max - range) / span).round() as u8)
+        } else {
+            Mono8(0)
+        }
+    });
+    image::from_pixels(
+        scan.header.clone(),
+        scan.ranges.len() as u32,
+        1.min(scan.ranges.len() as u32),
+        pixels,
+    )
+}
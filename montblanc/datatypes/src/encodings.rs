@@ -0,0 +1,81 @@
+//
+// Copyright (c) 2022 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+//! The encodings of the `data` of an `Image`, as in `sensor_msgs/image_encodings.h`.
+
+pub static RGB8: &str = "rgb8";
+pub static RGBA8: &str = "rgba8";
+pub static RGB16: &str = "rgb16";
+pub static RGBA16: &str = "rgba16";
+pub static BGR8: &str = "bgr8";
+pub static BGRA8: &str = "bgra8";
+pub static BGR16: &str = "bgr16";
+pub static BGRA16: &str = "bgra16";
+pub static MONO8: &str = "mono8";
+pub static MONO16: &str = "mono16";
+pub static YUV422: &str = "yuv422";
+
+/// The named encodings and the size of their pixels, in bytes.
+static PIXEL_SIZES: &[(&str, u32)] = &[
+    ("rgb8", 3),
+    ("rgba8", 4),
+    ("rgb16", 6),
+    ("rgba16", 8),
+    ("bgr8", 3),
+    ("bgra8", 4),
+    ("bgr16", 6),
+    ("bgra16", 8),
+    ("mono8", 1),
+    ("mono16", 2),
+    ("yuv422", 2),
+    ("bayer_rggb8", 1),
+    ("bayer_bggr8", 1),
+    ("bayer_gbrg8", 1),
+    ("bayer_grbg8", 1),
+    ("bayer_rggb16", 2),
+    ("bayer_bggr16", 2),
+    ("bayer_gbrg16", 2),
+    ("bayer_grbg16", 2),
+];
+
+/// The depths of the OpenCV-like encodings, e.g. `32FC1`, and the size of a channel, in bytes.
+static CHANNEL_SIZES: &[(&str, u32)] = &[
+    ("8U", 1),
+    ("8S", 1),
+    ("16U", 2),
+    ("16S", 2),
+    ("32S", 4),
+    ("32F", 4),
+    ("64F", 8),
+];
+
+/// The maximum number of channels of an OpenCV-like encoding.
+const MAX_CHANNELS: u32 = 512;
+
+/// Returns the size of a pixel, in bytes, or `None` if `encoding` is unknown.
+pub fn pixel_size(encoding: &str) -> Option<u32> {
+    if let Some((_, size)) = PIXEL_SIZES.iter().find(|(name, _)| *name == encoding) {
+        return Some(*size);
+    }
+
+    let (depth, channels) = encoding.split_once('C')?;
+    let channel_size = CHANNEL_SIZES
+        .iter()
+        .find(|(name, _)| *name == depth)
+        .map(|(_, size)| *size)?;
+    match channels.parse::<u32>() {
+        Ok(channels) if (1..=MAX_CHANNELS).contains(&channels) => Some(channel_size * channels),
+        _ => None,
+    }
+}
@@ -0,0 +1,467 @@
+//
+// Copyright (c) 2022 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+//! Typed access to the pixels of an `Image`, and conversions between encodings.
+//!
+//! An [`ImageView`] reads the pixels of an image in place, as one of the [`Pixel`] types matching
+//! its `encoding`. [`convert`] changes the encoding of an image, [`crop`] and [`resize`] its size.
+//!
+//! ```ignore
+//! let view = ImageView::<Rgb8>::new(&image)?;
+//! let Rgb8([r, g, b]) = view.get(0, 0);
+//!
+//! let gray = convert(&image, encodings::MONO8)?;
+//! let thumbnail = resize(&crop(&image, 0, 0, 320, 240)?, 160, 120)?;
+//! ```
+
+use crate::data_types;
+use crate::encodings;
+use crate::validation::{Validate, ValidationError};
+use std::fmt;
+use std::marker::PhantomData;
+
+/// Why an image could not be read or transformed.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ImageError {
+    Invalid(ValidationError),
+    /// The encoding of the image is not the one expected, or is not supported.
+    UnsupportedEncoding(String),
+    /// The region to crop is not within the image.
+    OutOfBounds {
+        x: u32,
+        y: u32,
+        width: u32,
+        height: u32,
+    },
+    /// The image has no pixels.
+    Empty,
+}
+
+impl fmt::Display for ImageError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ImageError::Invalid(error) => write!(f, "invalid image: {}", error),
+            ImageError::UnsupportedEncoding(encoding) => {
+                write!(f, "unsupported encoding `{}`", encoding)
+            }
+            ImageError::OutOfBounds {
+                x,
+                y,
+                width,
+                height,
+            } => write!(
+                f,
+                "the region of {}x{} pixels at ({}, {}) is not within the image",
+                width, height, x, y
+            ),
+            ImageError::Empty => write!(f, "the image has no pixels"),
+        }
+    }
+}
+
+impl std::error::Error for ImageError {}
+
+impl From<ValidationError> for ImageError {
+    fn from(error: ValidationError) -> Self {
+        ImageError::Invalid(error)
+    }
+}
+
+/// A color, each channel being scaled to 16 bits. Conversions between encodings go through it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Color {
+    pub r: u16,
+    pub g: u16,
+    pub b: u16,
+    pub a: u16,
+}
+
+impl Color {
+    /// An opaque color.
+    pub fn rgb(r: u16, g: u16, b: u16) -> Self {
+        Self {
+            r,
+            g,
+            b,
+            a: u16::MAX,
+        }
+    }
+
+    /// The luma of the color, as defined by ITU-R BT.601.
+    pub fn luma(&self) -> u16 {
+        ((299 * self.r as u32 + 587 * self.g as u32 + 114 * self.b as u32) / 1000) as u16
+    }
+}
+
+fn widen(value: u8) -> u16 {
+    value as u16 * 257
+}
+
+fn narrow(value: u16) -> u8 {
+    ((value as u32 + 128) / 257) as u8
+}
+
+/// A pixel of an image of a given encoding.
+pub trait Pixel: Copy {
+    const ENCODING: &'static str;
+    /// The size of the pixel, in bytes.
+    const SIZE: usize;
+
+    fn decode(bytes: &[u8], big_endian: bool) -> Self;
+    fn encode(&self, bytes: &mut Vec<u8>);
+    fn to_color(&self) -> Color;
+    fn from_color(color: Color) -> Self;
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Rgb8(pub [u8; 3]);
+
+impl Pixel for Rgb8 {
+    const ENCODING: &'static str = "rgb8";
+    const SIZE: usize = 3;
+
+    fn decode(bytes: &[u8], _big_endian: bool) -> Self {
+        Rgb8([bytes[0], bytes[1], bytes[2]])
+    }
+
+    fn encode(&self, bytes: &mut Vec<u8>) {
+        bytes.extend_from_slice(&self.0);
+    }
+
+    fn to_color(&self) -> Color {
+        let [r, g, b] = self.0;
+        Color::rgb(widen(r), widen(g), widen(b))
+    }
+
+    fn from_color(color: Color) -> Self {
+        Rgb8([narrow(color.r), narrow(color.g), narrow(color.b)])
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Bgr8(pub [u8; 3]);
+
+impl Pixel for Bgr8 {
+    const ENCODING: &'static str = "bgr8";
+    const SIZE: usize = 3;
+
+    fn decode(bytes: &[u8], _big_endian: bool) -> Self {
+        Bgr8([bytes[0], bytes[1], bytes[2]])
+    }
+
+    fn encode(&self, bytes: &mut Vec<u8>) {
+        bytes.extend_from_slice(&self.0);
+    }
+
+    fn to_color(&self) -> Color {
+        let [b, g, r] = self.0;
+        Color::rgb(widen(r), widen(g), widen(b))
+    }
+
+    fn from_color(color: Color) -> Self {
+        Bgr8([narrow(color.b), narrow(color.g), narrow(color.r)])
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Rgba8(pub [u8; 4]);
+
+impl Pixel for Rgba8 {
+    const ENCODING: &'static str = "rgba8";
+    const SIZE: usize = 4;
+
+    fn decode(bytes: &[u8], _big_endian: bool) -> Self {
+        Rgba8([bytes[0], bytes[1], bytes[2], bytes[3]])
+    }
+
+    fn encode(&self, bytes: &mut Vec<u8>) {
+        bytes.extend_from_slice(&self.0);
+    }
+
+    fn to_color(&self) -> Color {
+        let [r, g, b, a] = self.0;
+        Color {
+            r: widen(r),
+            g: widen(g),
+            b: widen(b),
+            a: widen(a),
+        }
+    }
+
+    fn from_color(color: Color) -> Self {
+        Rgba8([
+            narrow(color.r),
+            narrow(color.g),
+            narrow(color.b),
+            narrow(color.a),
+        ])
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Mono8(pub u8);
+
+impl Pixel for Mono8 {
+    const ENCODING: &'static str = "mono8";
+    const SIZE: usize = 1;
+
+    fn decode(bytes: &[u8], _big_endian: bool) -> Self {
+        Mono8(bytes[0])
+    }
+
+    fn encode(&self, bytes: &mut Vec<u8>) {
+        bytes.push(self.0);
+    }
+
+    fn to_color(&self) -> Color {
+        let value = widen(self.0);
+        Color::rgb(value, value, value)
+    }
+
+    fn from_color(color: Color) -> Self {
+        Mono8(narrow(color.luma()))
+    }
+}
+
+/// A 16 bits gray level, encoded in little-endian.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Mono16(pub u16);
+
+impl Pixel for Mono16 {
+    const ENCODING: &'static str = "mono16";
+    const SIZE: usize = 2;
+
+    fn decode(bytes: &[u8], big_endian: bool) -> Self {
+        let bytes = [bytes[0], bytes[1]];
+        Mono16(if big_endian {
+            u16::from_be_bytes(bytes)
+        } else {
+            u16::from_le_bytes(bytes)
+        })
+    }
+
+    fn encode(&self, bytes: &mut Vec<u8>) {
+        bytes.extend_from_slice(&self.0.to_le_bytes());
+    }
+
+    fn to_color(&self) -> Color {
+        Color::rgb(self.0, self.0, self.0)
+    }
+
+    fn from_color(color: Color) -> Self {
+        Mono16(color.luma())
+    }
+}
+
+/// Read access to the pixels of a valid image encoded as `P`, without copying its data.
+#[derive(Debug, Clone, Copy)]
+pub struct ImageView<'a, P> {
+    image: &'a data_types::Image,
+    pixel: PhantomData<P>,
+}
+
+impl<'a, P: Pixel> ImageView<'a, P> {
+    pub fn new(image: &'a data_types::Image) -> Result<Self, ImageError> {
+        if image.encoding != P::ENCODING {
+            return Err(ImageError::UnsupportedEncoding(image.encoding.clone()));
+        }
+        image.validate()?;
+        Ok(Self {
+            image,
+            pixel: PhantomData,
+        })
+    }
+
+    pub fn width(&self) -> u32 {
+        self.image.width
+    }
+
+    pub fn height(&self) -> u32 {
+        self.image.height
+    }
+
+    /// The bytes of the pixels of row `y`, without padding.
+    pub fn row(&self, y: u32) -> &'a [u8] {
+        let start = y as usize * self.image.step as usize;
+        &self.image.data[start..start + self.image.width as usize * P::SIZE]
+    }
+
+    /// The pixel of column `x` and row `y`.
+    ///
+    /// # Panics
+    ///
+    /// If the pixel is not within the image.
+    pub fn get(&self, x: u32, y: u32) -> P {
+        assert!(x < self.image.width, "column {} out of the image", x);
+        let start = x as usize * P::SIZE;
+        P::decode(&self.row(y)[start..], self.image.is_bigendian)
+    }
+
+    /// The pixels of the image, row after row.
+    pub fn pixels(&self) -> impl Iterator<Item = P> + 'a
+    where
+        P: 'a,
+    {
+        let (image, view) = (self.image, *self);
+        (0..image.height).flat_map(move |y| {
+            view.row(y)
+                .chunks_exact(P::SIZE)
+                .map(move |bytes| P::decode(bytes, image.is_bigendian))
+        })
+    }
+}
+
+/// Writes an image of `width` by `height` pixels, without padding, from `pixels`.
+pub fn from_pixels<P: Pixel>(
+    header: Option<data_types::Header>,
+    width: u32,
+    height: u32,
+    pixels: impl IntoIterator<Item = P>,
+) -> data_types::Image {
+    let mut data = Vec::with_capacity(width as usize * height as usize * P::SIZE);
+    pixels
+        .into_iter()
+        .take(width as usize * height as usize)
+        .for_each(|pixel| pixel.encode(&mut data));
+    data.resize(width as usize * height as usize * P::SIZE, 0);
+
+    data_types::Image {
+        header,
+        height,
+        width,
+        encoding: P::ENCODING.to_string(),
+        is_bigendian: false,
+        step: width * P::SIZE as u32,
+        data,
+    }
+}
+
+fn convert_to<P: Pixel, Q: Pixel>(
+    image: &data_types::Image,
+) -> Result<data_types::Image, ImageError> {
+    let view = ImageView::<P>::new(image)?;
+    Ok(from_pixels(
+        image.header.clone(),
+        image.width,
+        image.height,
+        view.pixels().map(|pixel| Q::from_color(pixel.to_color())),
+    ))
+}
+
+fn convert_from<P: Pixel>(
+    image: &data_types::Image,
+    encoding: &str,
+) -> Result<data_types::Image, ImageError> {
+    match encoding {
+        "rgb8" => convert_to::<P, Rgb8>(image),
+        "bgr8" => convert_to::<P, Bgr8>(image),
+        "rgba8" => convert_to::<P, Rgba8>(image),
+        "mono8" => convert_to::<P, Mono8>(image),
+        "mono16" => convert_to::<P, Mono16>(image),
+        _ => Err(ImageError::UnsupportedEncoding(encoding.to_string())),
+    }
+}
+
+/// Converts `image` to `encoding`. Both encodings must be one of `rgb8`, `bgr8`, `rgba8`, `mono8`
+/// or `mono16`. Color images are converted to gray levels by their luma.
+pub fn convert(image: &data_types::Image, encoding: &str) -> Result<data_types::Image, ImageError> {
+    match image.encoding.as_str() {
+        "rgb8" => convert_from::<Rgb8>(image, encoding),
+        "bgr8" => convert_from::<Bgr8>(image, encoding),
+        "rgba8" => convert_from::<Rgba8>(image, encoding),
+        "mono8" => convert_from::<Mono8>(image, encoding),
+        "mono16" => convert_from::<Mono16>(image, encoding),
+        _ => Err(ImageError::UnsupportedEncoding(image.encoding.clone())),
+    }
+}
+
+/// Checks that `image` is valid, returning the size of its pixels.
+fn pixel_size(image: &data_types::Image) -> Result<usize, ImageError> {
+    image.validate()?;
+    encodings::pixel_size(&image.encoding)
+        .map(|size| size as usize)
+        .ok_or_else(|| ImageError::UnsupportedEncoding(image.encoding.clone()))
+}
+
+/// Copies the region of `width` by `height` pixels whose top left corner is at `(x, y)`. Any
+/// known encoding is supported.
+pub fn crop(
+    image: &data_types::Image,
+    x: u32,
+    y: u32,
+    width: u32,
+    height: u32,
+) -> Result<data_types::Image, ImageError> {
+    let size = pixel_size(image)?;
+    if x as u64 + width as u64 > image.width as u64
+        || y as u64 + height as u64 > image.height as u64
+    {
+        return Err(ImageError::OutOfBounds {
+            x,
+            y,
+            width,
+            height,
+        });
+    }
+
+    let row_bytes = width as usize * size;
+    let mut data = Vec::with_capacity(row_bytes * height as usize);
+    for row in y..y + height {
+        let start = row as usize * image.step as usize + x as usize * size;
+        data.extend_from_slice(&image.data[start..start + row_bytes]);
+    }
+
+    Ok(data_types::Image {
+        header: image.header.clone(),
+        height,
+        width,
+        encoding: image.encoding.clone(),
+        is_bigendian: image.is_bigendian,
+        step: row_bytes as u32,
+        data,
+    })
+}
+
+/// Scales `image` to `width` by `height` pixels, taking the nearest pixel. Any known encoding is
+/// supported.
+pub fn resize(
+    image: &data_types::Image,
+    width: u32,
+    height: u32,
+) -> Result<data_types::Image, ImageError> {
+    let size = pixel_size(image)?;
+    let row_bytes = width as usize * size;
+    let mut data = Vec::with_capacity(row_bytes * height as usize);
+    if image.width > 0 && image.height > 0 {
+        for row in 0..height as u64 {
+            let source_row = (row * image.height as u64 / height as u64) as usize;
+            let source = &image.data[source_row * image.step as usize..];
+            for column in 0..width as u64 {
+                let start = (column * image.width as u64 / width as u64) as usize * size;
+                data.extend_from_slice(&source[start..start + size]);
+            }
+        }
+    }
+    data.resize(row_bytes * height as usize, 0);
+
+    Ok(data_types::Image {
+        header: image.header.clone(),
+        height,
+        width,
+        encoding: image.encoding.clone(),
+        is_bigendian: image.is_bigendian,
+        step: row_bytes as u32,
+        data,
+    })
+}
@@ -0,0 +1,160 @@
+//
+// Copyright (c) 2022 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+//! End-to-end latency measurement.
+//!
+//! Sources stamp their messages with [`now`] and operators forward the timestamp of the message
+//! that triggered their output. Sinks can then compute, with [`elapsed_since`], how long ago the
+//! data they receive entered the graph, and record it in a [`LatencyRecorder`].
+//!
+//! Timestamps are the `u64` representation of the NTP64 time used by zenoh-flow: the seconds
+//! since the UNIX epoch in the 32 high bits, the fraction of second in the 32 low bits.
+
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+const FRAC_PER_SEC: u64 = 1 << 32;
+const NANOS_PER_SEC: u64 = 1_000_000_000;
+
+/// Returns the current time, in the format expected by `Output::send`.
+pub fn now() -> u64 {
+    let since_epoch = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default();
+    (since_epoch.as_secs() << 32)
+        + (since_epoch.subsec_nanos() as u64 * FRAC_PER_SEC) / NANOS_PER_SEC
+}
+
+/// Returns the time elapsed since `timestamp`, as returned by `Timestamp::get_time().as_u64()`.
+///
+/// A timestamp in the future, which can happen when the clocks of two machines are not
+/// synchronised, gives a zero duration.
+pub fn elapsed_since(timestamp: u64) -> Duration {
+    let secs = timestamp >> 32;
+    let nanos = ((timestamp & (FRAC_PER_SEC - 1)) * NANOS_PER_SEC) / FRAC_PER_SEC;
+    let stamped = Duration::new(secs, nanos as u32);
+    let since_epoch = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default();
+    since_epoch.saturating_sub(stamped)
+}
+
+/// The number of buckets per power of two: values are recorded with a relative error below 1/16.
+const SUB_BUCKETS: usize = 16;
+const SUB_BUCKETS_BITS: u32 = 4;
+
+/// A histogram of durations, recorded in microseconds with a bounded relative error.
+#[derive(Debug, Clone, Default)]
+pub struct Histogram {
+    counts: Vec<u64>,
+    count: u64,
+    max: u64,
+}
+
+impl Histogram {
+    fn bucket(value: u64) -> usize {
+        if value < SUB_BUCKETS as u64 {
+            return value as usize;
+        }
+        let shift = 63 - value.leading_zeros() - SUB_BUCKETS_BITS;
+        let mantissa = (value >> shift) as usize;
+        (shift as usize + 1) * SUB_BUCKETS + mantissa - SUB_BUCKETS
+    }
+
+    /// Returns the lowest value falling in the bucket at `index`.
+    fn lowest(index: usize) -> u64 {
+        if index < SUB_BUCKETS {
+            return index as u64;
+        }
+        let shift = index / SUB_BUCKETS - 1;
+        let mantissa = (SUB_BUCKETS + index % SUB_BUCKETS) as u64;
+        mantissa << shift
+    }
+
+    pub fn record(&mut self, latency: Duration) {
+        let value = latency.as_micros().min(u64::MAX as u128) as u64;
+        let bucket = Self::bucket(value);
+        if self.counts.len() <= bucket {
+            self.counts.resize(bucket + 1, 0);
+        }
+        self.counts[bucket] += 1;
+        self.count += 1;
+        self.max = self.max.max(value);
+    }
+
+    pub fn count(&self) -> u64 {
+        self.count
+    }
+
+    pub fn max(&self) -> Duration {
+        Duration::from_micros(self.max)
+    }
+
+    /// Returns the value below which `quantile` (between 0 and 1) of the recorded values fall.
+    pub fn quantile(&self, quantile: f64) -> Duration {
+        let rank = ((quantile.clamp(0.0, 1.0) * self.count as f64).ceil() as u64).max(1);
+        let mut seen = 0;
+        for (index, count) in self.counts.iter().enumerate() {
+            seen += count;
+            if seen >= rank {
+                let highest = Self::lowest(index + 1) - 1;
+                return Duration::from_micros(highest.min(self.max));
+            }
+        }
+        self.max()
+    }
+}
+
+/// Latency histograms, one per path through the graph.
+#[derive(Debug, Clone, Default)]
+pub struct LatencyRecorder {
+    paths: BTreeMap<String, Histogram>,
+}
+
+impl LatencyRecorder {
+    pub fn record(&mut self, path: &str, latency: Duration) {
+        match self.paths.get_mut(path) {
+            Some(histogram) => histogram.record(latency),
+            None => {
+                let mut histogram = Histogram::default();
+                histogram.record(latency);
+                self.paths.insert(path.to_string(), histogram);
+            }
+        }
+    }
+
+    pub fn histogram(&self, path: &str) -> Option<&Histogram> {
+        self.paths.get(path)
+    }
+
+    /// Returns a table with, for each path, the number of messages received and their p50, p99
+    /// and maximum latencies in microseconds.
+    pub fn report(&self) -> String {
+        let mut report = String::from("path\tcount\tp50_us\tp99_us\tmax_us\n");
+        for (path, histogram) in &self.paths {
+            // Writing to a String cannot fail.
+            let _ = writeln!(
+                report,
+                "{}\t{}\t{}\t{}\t{}",
+                path,
+                histogram.count(),
+                histogram.quantile(0.5).as_micros(),
+                histogram.quantile(0.99).as_micros(),
+                histogram.max().as_micros()
+            );
+        }
+        report
+    }
+}
@@ -12,11 +12,32 @@
 //   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
 //
 
+use codec::{Codec, Payload};
+use datagen::{sample_string, sample_vec, Sampler};
+pub use ports_derive::Ports;
 use prost::Message;
-use rand::distributions::{Alphanumeric, Distribution, Standard};
-use rand::{random, Rng};
+use rand::distributions::{Distribution, Standard};
+use rand::Rng;
+use stats::Statistics;
 use std::io::Cursor;
-use std::time::{SystemTime, UNIX_EPOCH};
+use zenoh_flow::prelude as zf;
+use zenoh_flow::zferror;
+
+pub mod cdr;
+pub mod clock;
+pub mod codec;
+pub mod datagen;
+pub mod derived;
+pub mod encodings;
+pub mod image;
+pub mod latency;
+pub mod payload;
+pub mod pointcloud;
+pub mod publication;
+pub mod sensors;
+pub mod stats;
+pub mod ticker;
+pub mod validation;
 
 pub static AMAZON_PORT: &str = "Amazon";
 pub static DANUBE_PORT: &str = "Danube";
@@ -42,20 +63,197 @@ pub static VOLGA_PORT: &str = "Volga";
 pub static MURRAY_PORT: &str = "Murray";
 pub static LENA_PORT: &str = "Lena";
 
+/// Declares the `data_types` message exchanged on each port.
+///
+/// Every message is checked to exist in `data_types`, so that a renamed or removed message does
+/// not leave the registry silently out of date.
+macro_rules! port_types {
+    ($($port:ident => $message:ident),* $(,)?) => {
+        /// The name of the `data_types` message exchanged on each port.
+        pub static PORT_TYPES: &[(&str, &str)] = &[$(($port, stringify!($message))),*];
+
+        $(const _: Option<data_types::$message> = None;)*
+    };
+}
+
+port_types! {
+    AMAZON_PORT => Float32,
+    DANUBE_PORT => String,
+    GANGES_PORT => Int64,
+    NILE_PORT => Int32,
+    TIGRIS_PORT => Float32,
+    PARANA_PORT => String,
+    COLUMBIA_PORT => Image,
+    COLORADO_PORT => Image,
+    SALWEEN_PORT => PointCloud2,
+    GODAVARI_PORT => LaserScan,
+    CHENAB_PORT => Quaternion,
+    LOIRE_PORT => PointCloud2,
+    YAMUNA_PORT => Vector3,
+    BRAZOS_PORT => PointCloud2,
+    TAGUS_PORT => Pose,
+    MISSOURI_PORT => Image,
+    CONGO_PORT => Twist,
+    MEKONG_PORT => TwistWithCovarianceStamped,
+    ARKANSAS_PORT => String,
+    OHIO_PORT => Float32,
+    VOLGA_PORT => Float64,
+    MURRAY_PORT => Vector3Stamped,
+    LENA_PORT => WrenchStamped,
+}
+
+/// Returns the name of the `data_types` message exchanged on `port`, if it is a known port.
+pub fn port_type(port: &str) -> Option<&'static str> {
+    PORT_TYPES
+        .iter()
+        .find(|(name, _)| *name == port)
+        .map(|(_, message)| *message)
+}
+
+/// Checks that `T` is the message exchanged on `port`, if it is a known port.
+pub fn check_port_type<T: Named>(port: &str) -> zf::Result<()> {
+    match port_type(port) {
+        Some(message) if message != T::NAME => Err(zferror!(
+            zf::ErrorKind::InvalidData,
+            "The port '{}' exchanges `{}` messages, not `{}`",
+            port,
+            message,
+            T::NAME
+        )
+        .into()),
+        _ => Ok(()),
+    }
+}
+
+/// A message of `data_types` the nodes can exchange, named as in `PORT_TYPES`.
+pub trait Named {
+    const NAME: &'static str;
+}
+
+/// Declares the `data_types` messages the nodes can exchange.
+///
+/// Every message is checked to be generated by `DataGen`, so that a node can send any of them.
+macro_rules! messages {
+    ($($message:ident),* $(,)?) => {
+        /// The name of the `data_types` messages the nodes can exchange.
+        pub static MESSAGES: &[&str] = &[$(stringify!($message)),*];
+
+        $(const _: fn(&mut datagen::DataGen) -> data_types::$message = datagen::DataGen::gen;)*
+
+        $(
+            impl Named for data_types::$message {
+                const NAME: &'static str = stringify!($message);
+            }
+        )*
+    };
+}
+
+messages! {
+    Timestamp,
+    Header,
+    Point,
+    Quaternion,
+    Vector3,
+    Vector3Stamped,
+    Pose,
+    Twist,
+    TwistWithCovariance,
+    TwistWithCovarianceStamped,
+    Wrench,
+    WrenchStamped,
+    Image,
+    PointCloud2,
+    LaserScan,
+    Float32,
+    Float64,
+    Int32,
+    Int64,
+    String,
+}
+
+/// Takes the input called `port` and decodes, with `codec`, the messages received on it. The
+/// bytes decoded are counted by `statistics`.
+///
+/// Contrary to `Inputs::take`, an error is returned if there is no such input, or if `T` is not
+/// the message `PORT_TYPES` declares for it.
+pub fn take_input<T: Payload + Named>(
+    inputs: &mut zf::Inputs,
+    port: &str,
+    codec: Codec,
+    statistics: &Statistics,
+) -> zf::Result<zf::Input<T>> {
+    check_port_type::<T>(port)?;
+    let input = inputs.take(port).ok_or_else(|| {
+        zferror!(
+            zf::ErrorKind::MissingInput(port.into()),
+            "No Input called '{}' found",
+            port
+        )
+    })?;
+
+    let (port, statistics) = (port.to_string(), statistics.clone());
+    Ok(input.typed(move |buf| {
+        statistics.decoded(&port, buf.len());
+        Ok(codec.decode(buf)?)
+    }))
+}
+
+/// Takes the output called `port` and encodes, with `codec`, the messages sent on it. The bytes
+/// encoded are counted by `statistics`.
+///
+/// Contrary to `Outputs::take`, an error is returned if there is no such output, or if `T` is not
+/// the message `PORT_TYPES` declares for it.
+pub fn take_output<T: Payload + Named>(
+    outputs: &mut zf::Outputs,
+    port: &str,
+    codec: Codec,
+    statistics: &Statistics,
+) -> zf::Result<zf::Output<T>> {
+    check_port_type::<T>(port)?;
+    let output = outputs.take(port).ok_or_else(|| {
+        zferror!(
+            zf::ErrorKind::MissingOutput(port.into()),
+            "No Output called '{}' found",
+            port
+        )
+    })?;
+
+    let (port, statistics) = (port.to_string(), statistics.clone());
+    Ok(output.typed(move |buf, v: &T| {
+        let start = buf.len();
+        codec.encode(v, buf)?;
+        statistics.encoded(&port, buf.len() - start);
+        Ok(())
+    }))
+}
+
+/// A value whose defaults can be overridden by the configuration of a node.
+pub trait Configurable: Sized {
+    fn configure(self, configuration: Option<&zf::Configuration>) -> zf::Result<Self>;
+}
+
+/// Returns the YAML descriptor of the node `id`, whose shared library is located at `uri`.
+pub fn node_descriptor(id: &str, uri: &str, inputs: &[&str], outputs: &[&str]) -> String {
+    let mut descriptor = format!("id: {}\nuri: {}\n", id, uri);
+    if !inputs.is_empty() {
+        descriptor.push_str(&format!("inputs: [{}]\n", inputs.join(", ")));
+    }
+    if !outputs.is_empty() {
+        descriptor.push_str(&format!("outputs: [{}]\n", outputs.join(", ")));
+    }
+    descriptor
+}
+
 pub mod data_types {
     include!(concat!(env!("OUT_DIR"), "/datatypes.data_types.rs"));
 }
 
 pub fn random_string(length: usize) -> String {
-    rand::thread_rng()
-        .sample_iter(Alphanumeric)
-        .take(length)
-        .map(char::from)
-        .collect()
+    sample_string(&mut rand::thread_rng(), length)
 }
 
 pub fn random_bytes(length: usize) -> Vec<u8> {
-    (0..length).map(|_| rand::random::<u8>()).collect()
+    sample_vec(&mut rand::thread_rng(), length)
 }
 
 pub fn empty_bytes() -> Vec<u8> {
@@ -63,28 +261,87 @@ pub fn empty_bytes() -> Vec<u8> {
 }
 
 pub fn random_floats(length: usize) -> Vec<f32> {
-    (0..length).map(|_| rand::random::<f32>()).collect()
+    sample_vec(&mut rand::thread_rng(), length)
 }
 
 pub fn random_doubles(length: usize) -> Vec<f64> {
-    (0..length).map(|_| rand::random::<f64>()).collect()
+    sample_vec(&mut rand::thread_rng(), length)
+}
+
+/// Like `Standard`, an optional message is `Some` half of the time.
+impl<'a, T> Distribution<Option<T>> for Sampler<'a>
+where
+    Sampler<'a>: Distribution<T>,
+{
+    fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> Option<T> {
+        if rng.gen() {
+            Some(self.sample(rng))
+        } else {
+            None
+        }
+    }
 }
 
-impl Distribution<data_types::Header> for Standard {
-    fn sample<R: Rng + ?Sized>(&self, _rng: &mut R) -> data_types::Header {
-        let now = SystemTime::now();
-        let now_as_duration = now
-            .duration_since(UNIX_EPOCH)
-            .expect("System time went backwards");
+/// Implements `Standard` for messages, sampling them with the clock of the system.
+macro_rules! standard_with_system_clock {
+    ($($message:ty),* $(,)?) => {
+        $(
+            impl Distribution<$message> for Standard {
+                fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> $message {
+                    Sampler::new(&clock::Clock::system()).sample(rng)
+                }
+            }
+        )*
+    };
+}
+
+standard_with_system_clock! {
+    data_types::Timestamp,
+    data_types::Header,
+    data_types::Point,
+    data_types::Quaternion,
+    data_types::Vector3,
+    data_types::Vector3Stamped,
+    data_types::Pose,
+    data_types::Twist,
+    data_types::TwistWithCovariance,
+    data_types::TwistWithCovarianceStamped,
+    data_types::Wrench,
+    data_types::WrenchStamped,
+    data_types::Image,
+    data_types::point_cloud2::point_field::DataType,
+    data_types::point_cloud2::PointField,
+    data_types::PointCloud2,
+    data_types::LaserScan,
+    data_types::Float32,
+    data_types::Float64,
+    data_types::Int32,
+    data_types::Int64,
+    data_types::String,
+}
+
+impl Distribution<data_types::Timestamp> for Sampler<'_> {
+    fn sample<R: Rng + ?Sized>(&self, _rng: &mut R) -> data_types::Timestamp {
+        let now = self.clock.since_epoch();
+        data_types::Timestamp {
+            sec: now.as_secs(),
+            nanosec: now.subsec_nanos(),
+        }
+    }
+}
+
+impl Distribution<data_types::Header> for Sampler<'_> {
+    fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> data_types::Header {
+        let now = self.clock.since_epoch();
         data_types::Header {
-            sec: now_as_duration.as_secs() as i32,
-            nanosec: now_as_duration.subsec_nanos(),
-            frame_id: random_string(16),
+            sec: now.as_secs() as i32,
+            nanosec: now.subsec_nanos(),
+            frame_id: sample_string(rng, 16),
         }
     }
 }
 
-impl Distribution<data_types::Point> for Standard {
+impl Distribution<data_types::Point> for Sampler<'_> {
     fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> data_types::Point {
         data_types::Point {
             x: rng.gen(),
@@ -95,8 +352,7 @@ impl Distribution<data_types::Point> for Standard {
 }
 
 pub fn serialize_point(point: &data_types::Point) -> Vec<u8> {
-    let mut buf = Vec::new();
-    buf.resize(point.encoded_len(), 0);
+    let mut buf = Vec::with_capacity(point.encoded_len());
     point.encode(&mut buf).unwrap();
     buf
 }
@@ -105,20 +361,26 @@ pub fn deserialize_point(buf: &[u8]) -> Result<data_types::Point, prost::DecodeE
     data_types::Point::decode(&mut Cursor::new(buf))
 }
 
-impl Distribution<data_types::Quaternion> for Standard {
+/// A rotation drawn uniformly at random: the quaternion is normalised.
+impl Distribution<data_types::Quaternion> for Sampler<'_> {
     fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> data_types::Quaternion {
+        let (u1, u2, u3): (f64, f64, f64) = rng.gen();
+        let (a, b) = ((1.0 - u1).sqrt(), u1.sqrt());
+        let (u2, u3) = (
+            2.0 * std::f64::consts::PI * u2,
+            2.0 * std::f64::consts::PI * u3,
+        );
         data_types::Quaternion {
-            x: rng.gen(),
-            y: rng.gen(),
-            z: rng.gen(),
-            w: rng.gen(),
+            x: a * u2.sin(),
+            y: a * u2.cos(),
+            z: b * u3.sin(),
+            w: b * u3.cos(),
         }
     }
 }
 
 pub fn serialize_quaternion(quat: &data_types::Quaternion) -> Vec<u8> {
-    let mut buf = Vec::new();
-    buf.resize(quat.encoded_len(), 0);
+    let mut buf = Vec::with_capacity(quat.encoded_len());
     quat.encode(&mut buf).unwrap();
     buf
 }
@@ -127,7 +389,7 @@ pub fn deserialize_quaternion(buf: &[u8]) -> Result<data_types::Quaternion, pros
     data_types::Quaternion::decode(&mut Cursor::new(buf))
 }
 
-impl Distribution<data_types::Vector3> for Standard {
+impl Distribution<data_types::Vector3> for Sampler<'_> {
     fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> data_types::Vector3 {
         data_types::Vector3 {
             x: rng.gen(),
@@ -138,8 +400,7 @@ impl Distribution<data_types::Vector3> for Standard {
 }
 
 pub fn serialize_vector3(vec3: &data_types::Vector3) -> Vec<u8> {
-    let mut buf = Vec::new();
-    buf.resize(vec3.encoded_len(), 0);
+    let mut buf = Vec::with_capacity(vec3.encoded_len());
     vec3.encode(&mut buf).unwrap();
     buf
 }
@@ -148,18 +409,17 @@ pub fn deserialize_vector3(buf: &[u8]) -> Result<data_types::Vector3, prost::Dec
     data_types::Vector3::decode(&mut Cursor::new(buf))
 }
 
-impl Distribution<data_types::Vector3Stamped> for Standard {
+impl Distribution<data_types::Vector3Stamped> for Sampler<'_> {
     fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> data_types::Vector3Stamped {
         data_types::Vector3Stamped {
-            header: rng.gen(),
-            vector: rng.gen(),
+            header: self.sample(rng),
+            vector: self.sample(rng),
         }
     }
 }
 
 pub fn serialize_vector3_stamped(vec3s: &data_types::Vector3Stamped) -> Vec<u8> {
-    let mut buf = Vec::new();
-    buf.resize(vec3s.encoded_len(), 0);
+    let mut buf = Vec::with_capacity(vec3s.encoded_len());
     vec3s.encode(&mut buf).unwrap();
     buf
 }
@@ -170,18 +430,17 @@ pub fn deserialize_vector3_stamped(
     data_types::Vector3Stamped::decode(&mut Cursor::new(buf))
 }
 
-impl Distribution<data_types::Pose> for Standard {
+impl Distribution<data_types::Pose> for Sampler<'_> {
     fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> data_types::Pose {
         data_types::Pose {
-            position: rng.gen(),
-            orientation: rng.gen(),
+            position: self.sample(rng),
+            orientation: self.sample(rng),
         }
     }
 }
 
 pub fn serialize_pose(pose: &data_types::Pose) -> Vec<u8> {
-    let mut buf = Vec::new();
-    buf.resize(pose.encoded_len(), 0);
+    let mut buf = Vec::with_capacity(pose.encoded_len());
     pose.encode(&mut buf).unwrap();
     buf
 }
@@ -190,18 +449,17 @@ pub fn deserialize_pose(buf: &[u8]) -> Result<data_types::Pose, prost::DecodeErr
     data_types::Pose::decode(&mut Cursor::new(buf))
 }
 
-impl Distribution<data_types::Twist> for Standard {
+impl Distribution<data_types::Twist> for Sampler<'_> {
     fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> data_types::Twist {
         data_types::Twist {
-            linear: rng.gen(),
-            angular: rng.gen(),
+            linear: self.sample(rng),
+            angular: self.sample(rng),
         }
     }
 }
 
 pub fn serialize_twist(twist: &data_types::Twist) -> Vec<u8> {
-    let mut buf = Vec::new();
-    buf.resize(twist.encoded_len(), 0);
+    let mut buf = Vec::with_capacity(twist.encoded_len());
     twist.encode(&mut buf).unwrap();
     buf
 }
@@ -210,11 +468,11 @@ pub fn deserialize_twist(buf: &[u8]) -> Result<data_types::Twist, prost::DecodeE
     data_types::Twist::decode(&mut Cursor::new(buf))
 }
 
-impl Distribution<data_types::TwistWithCovariance> for Standard {
+impl Distribution<data_types::TwistWithCovariance> for Sampler<'_> {
     fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> data_types::TwistWithCovariance {
         data_types::TwistWithCovariance {
-            twist: rng.gen(),
-            covariance: random_doubles(36),
+            twist: self.sample(rng),
+            covariance: sample_vec(rng, 36),
         }
     }
 }
@@ -222,8 +480,7 @@ impl Distribution<data_types::TwistWithCovariance> for Standard {
 pub fn serialize_twist_with_covariance(
     twist_with_cov: &data_types::TwistWithCovariance,
 ) -> Vec<u8> {
-    let mut buf = Vec::new();
-    buf.resize(twist_with_cov.encoded_len(), 0);
+    let mut buf = Vec::with_capacity(twist_with_cov.encoded_len());
     twist_with_cov.encode(&mut buf).unwrap();
     buf
 }
@@ -234,11 +491,11 @@ pub fn deserialize_twist_with_covariance(
     data_types::TwistWithCovariance::decode(&mut Cursor::new(buf))
 }
 
-impl Distribution<data_types::TwistWithCovarianceStamped> for Standard {
+impl Distribution<data_types::TwistWithCovarianceStamped> for Sampler<'_> {
     fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> data_types::TwistWithCovarianceStamped {
         data_types::TwistWithCovarianceStamped {
-            header: random(),
-            twist: rng.gen(),
+            header: self.sample(rng),
+            twist: self.sample(rng),
         }
     }
 }
@@ -246,8 +503,7 @@ impl Distribution<data_types::TwistWithCovarianceStamped> for Standard {
 pub fn serialize_twist_with_covariance_stamped(
     twist_with_cov: &data_types::TwistWithCovarianceStamped,
 ) -> Vec<u8> {
-    let mut buf = Vec::new();
-    buf.resize(twist_with_cov.encoded_len(), 0);
+    let mut buf = Vec::with_capacity(twist_with_cov.encoded_len());
     twist_with_cov.encode(&mut buf).unwrap();
     buf
 }
@@ -258,18 +514,17 @@ pub fn deserialize_twist_with_covariance_stamped(
     data_types::TwistWithCovarianceStamped::decode(&mut Cursor::new(buf))
 }
 
-impl Distribution<data_types::Wrench> for Standard {
+impl Distribution<data_types::Wrench> for Sampler<'_> {
     fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> data_types::Wrench {
         data_types::Wrench {
-            force: rng.gen(),
-            torque: rng.gen(),
+            force: self.sample(rng),
+            torque: self.sample(rng),
         }
     }
 }
 
 pub fn serialize_wrench(wrench: &data_types::Wrench) -> Vec<u8> {
-    let mut buf = Vec::new();
-    buf.resize(wrench.encoded_len(), 0);
+    let mut buf = Vec::with_capacity(wrench.encoded_len());
     wrench.encode(&mut buf).unwrap();
     buf
 }
@@ -278,18 +533,17 @@ pub fn deserialize_wrench(buf: &[u8]) -> Result<data_types::Wrench, prost::Decod
     data_types::Wrench::decode(&mut Cursor::new(buf))
 }
 
-impl Distribution<data_types::WrenchStamped> for Standard {
+impl Distribution<data_types::WrenchStamped> for Sampler<'_> {
     fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> data_types::WrenchStamped {
         data_types::WrenchStamped {
-            header: rng.gen(),
-            wrench: rng.gen(),
+            header: self.sample(rng),
+            wrench: self.sample(rng),
         }
     }
 }
 
 pub fn serialize_wrench_stamped(wrench_stamped: &data_types::WrenchStamped) -> Vec<u8> {
-    let mut buf = Vec::new();
-    buf.resize(wrench_stamped.encoded_len(), 0);
+    let mut buf = Vec::with_capacity(wrench_stamped.encoded_len());
     wrench_stamped.encode(&mut buf).unwrap();
     buf
 }
@@ -300,24 +554,15 @@ pub fn deserialize_wrench_stamped(
     data_types::WrenchStamped::decode(&mut Cursor::new(buf))
 }
 
-impl Distribution<data_types::Image> for Standard {
+/// A frame of the default `sensors::Camera`.
+impl Distribution<data_types::Image> for Sampler<'_> {
     fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> data_types::Image {
-        data_types::Image {
-            header: rng.gen(),
-            height: rng.gen(),
-            width: rng.gen(),
-            encoding: random_string(32),
-            is_bigendian: rng.gen(),
-            step: rng.gen(),
-            //data: random_bytes(1920 * 1080 * 3),
-            data: empty_bytes(),
-        }
+        sensors::Camera::default().capture(Some(self.sample(rng)), rng)
     }
 }
 
 pub fn serialize_image(img: &data_types::Image) -> Vec<u8> {
-    let mut buf = Vec::new();
-    buf.resize(img.encoded_len(), 0);
+    let mut buf = Vec::with_capacity(img.encoded_len());
     img.encode(&mut buf).unwrap();
     buf
 }
@@ -326,7 +571,7 @@ pub fn deserialize_image(buf: &[u8]) -> Result<data_types::Image, prost::DecodeE
     data_types::Image::decode(&mut Cursor::new(buf))
 }
 
-impl Distribution<data_types::point_cloud2::point_field::DataType> for Standard {
+impl Distribution<data_types::point_cloud2::point_field::DataType> for Sampler<'_> {
     fn sample<R: Rng + ?Sized>(
         &self,
         rng: &mut R,
@@ -345,10 +590,10 @@ impl Distribution<data_types::point_cloud2::point_field::DataType> for Standard
     }
 }
 
-impl Distribution<data_types::point_cloud2::PointField> for Standard {
+impl Distribution<data_types::point_cloud2::PointField> for Sampler<'_> {
     fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> data_types::point_cloud2::PointField {
         data_types::point_cloud2::PointField {
-            name: random_string(32),
+            name: sample_string(rng, 32),
             offset: rng.gen(),
             datatype: rng.gen(),
             count: rng.gen(),
@@ -356,32 +601,15 @@ impl Distribution<data_types::point_cloud2::PointField> for Standard {
     }
 }
 
-fn random_point_fields(length: usize) -> Vec<data_types::point_cloud2::PointField> {
-    (0..length)
-        .map(|_| rand::random::<data_types::point_cloud2::PointField>())
-        .collect()
-}
-
-impl Distribution<data_types::PointCloud2> for Standard {
+/// A scan of the default `sensors::Lidar3d`.
+impl Distribution<data_types::PointCloud2> for Sampler<'_> {
     fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> data_types::PointCloud2 {
-        data_types::PointCloud2 {
-            header: rng.gen(),
-            height: rng.gen(),
-            width: rng.gen(),
-            fields: random_point_fields(3),
-            is_bigendian: rng.gen(),
-            point_step: rng.gen(),
-            row_step: rng.gen(),
-            //data: random_bytes(4 * 4 * 4 * 1280 * 960),
-            data: empty_bytes(),
-            is_dense: rng.gen(),
-        }
+        sensors::Lidar3d::default().scan(Some(self.sample(rng)), rng)
     }
 }
 
 pub fn serialize_pointcloud2(pc: &data_types::PointCloud2) -> Vec<u8> {
-    let mut buf = Vec::new();
-    buf.resize(pc.encoded_len(), 0);
+    let mut buf = Vec::with_capacity(pc.encoded_len());
     pc.encode(&mut buf).unwrap();
     buf
 }
@@ -390,26 +618,15 @@ pub fn deserialize_pointcloud2(buf: &[u8]) -> Result<data_types::PointCloud2, pr
     data_types::PointCloud2::decode(&mut Cursor::new(buf))
 }
 
-impl Distribution<data_types::LaserScan> for Standard {
+/// A scan of the default `sensors::Lidar2d`.
+impl Distribution<data_types::LaserScan> for Sampler<'_> {
     fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> data_types::LaserScan {
-        data_types::LaserScan {
-            header: rng.gen(),
-            angle_min: rng.gen(),
-            angle_max: rng.gen(),
-            angle_increment: rng.gen(),
-            time_increment: rng.gen(),
-            scan_time: rng.gen(),
-            range_min: rng.gen(),
-            range_max: rng.gen(),
-            ranges: random_floats(1024),
-            intensities: random_floats(1024),
-        }
+        sensors::Lidar2d::default().scan(Some(self.sample(rng)), rng)
     }
 }
 
 pub fn serialize_laserscan(ls: &data_types::LaserScan) -> Vec<u8> {
-    let mut buf = Vec::new();
-    buf.resize(ls.encoded_len(), 0);
+    let mut buf = Vec::with_capacity(ls.encoded_len());
     ls.encode(&mut buf).unwrap();
     buf
 }
@@ -417,3 +634,27 @@ pub fn serialize_laserscan(ls: &data_types::LaserScan) -> Vec<u8> {
 pub fn deserialize_laserscan(buf: &[u8]) -> Result<data_types::LaserScan, prost::DecodeError> {
     data_types::LaserScan::decode(&mut Cursor::new(buf))
 }
+
+/// The primitive messages hold a single value drawn by `Standard`.
+macro_rules! primitive_distribution {
+    ($($message:ident),* $(,)?) => {
+        $(
+            impl Distribution<data_types::$message> for Sampler<'_> {
+                fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> data_types::$message {
+                    data_types::$message { value: rng.gen() }
+                }
+            }
+        )*
+    };
+}
+
+primitive_distribution!(Float32, Float64, Int32, Int64);
+
+/// 16 alphanumeric characters, as the `frame_id` of a `Header`.
+impl Distribution<data_types::String> for Sampler<'_> {
+    fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> data_types::String {
+        data_types::String {
+            value: sample_string(rng, 16),
+        }
+    }
+}
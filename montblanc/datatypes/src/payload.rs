@@ -0,0 +1,53 @@
+//
+// Copyright (c) 2022 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+//! The payloads of the strings sent by Portsmouth, which Arequipa checks at the end of their path.
+//!
+//! A payload is random alphanumeric characters followed by their checksum, so that a payload
+//! altered on its way, truncated or mixed with another, is not intact anymore.
+
+use crate::datagen::DataGen;
+
+/// The length of the checksum ending a payload, in hexadecimal digits.
+pub const CHECKSUM_LEN: usize = 8;
+
+/// The 32-bit FNV-1a hash of `data`.
+fn checksum(data: &str) -> u32 {
+    data.bytes().fold(0x811c_9dc5, |hash, byte| {
+        (hash ^ byte as u32).wrapping_mul(0x0100_0193)
+    })
+}
+
+/// The checksum of `data`, as it ends a payload.
+fn checksum_of(data: &str) -> String {
+    format!("{:08x}", checksum(data))
+}
+
+/// A payload of `length` characters, or of `CHECKSUM_LEN` characters if `length` is shorter.
+pub fn generate(datagen: &mut DataGen, length: usize) -> String {
+    let mut payload = datagen.string(length.saturating_sub(CHECKSUM_LEN));
+    payload.push_str(&checksum_of(&payload));
+    payload
+}
+
+/// Whether `payload` ends with the checksum of the characters before it.
+pub fn is_intact(payload: &str) -> bool {
+    match payload.len().checked_sub(CHECKSUM_LEN) {
+        Some(end) if payload.is_char_boundary(end) => {
+            let (data, expected) = payload.split_at(end);
+            expected == checksum_of(data)
+        }
+        _ => false,
+    }
+}
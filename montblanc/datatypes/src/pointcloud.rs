@@ -0,0 +1,517 @@
+//
+// Copyright (c) 2022 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+//! Typed access to the points of a `PointCloud2`.
+//!
+//! A [`CloudView`] reads the fields of the points in place, by name, whatever their `DataType` and
+//! byte order. A [`CloudBuilder`] writes a cloud from values implementing [`Point`].
+//!
+//! ```ignore
+//! let view = CloudView::new(&cloud)?;
+//! let max_z = view.values::<f32>("z").map(|z| z.fold(f32::MIN, f32::max));
+//!
+//! let cloud = CloudBuilder::<PointXYZI>::new()
+//!     .header(header)
+//!     .extend(points)
+//!     .build();
+//! ```
+
+use crate::data_types;
+use crate::data_types::point_cloud2::point_field::DataType;
+use crate::data_types::point_cloud2::PointField;
+use crate::validation::{Validate, ValidationError};
+use std::convert::TryInto;
+use std::marker::PhantomData;
+
+/// The size of a value of `datatype`, in bytes.
+pub fn datatype_size(datatype: DataType) -> usize {
+    match datatype {
+        DataType::Int8 | DataType::Uint8 => 1,
+        DataType::Int16 | DataType::Uint16 => 2,
+        DataType::Int32 | DataType::Uint32 | DataType::Float32 => 4,
+        DataType::Float64 => 8,
+    }
+}
+
+/// Decodes the value of `datatype` at the start of `bytes`.
+fn decode(datatype: DataType, bytes: &[u8], big_endian: bool) -> f64 {
+    macro_rules! decode {
+        ($type:ty) => {{
+            let bytes = bytes[..std::mem::size_of::<$type>()].try_into().unwrap();
+            if big_endian {
+                <$type>::from_be_bytes(bytes) as f64
+            } else {
+                <$type>::from_le_bytes(bytes) as f64
+            }
+        }};
+    }
+
+    match datatype {
+        DataType::Int8 => decode!(i8),
+        DataType::Uint8 => decode!(u8),
+        DataType::Int16 => decode!(i16),
+        DataType::Uint16 => decode!(u16),
+        DataType::Int32 => decode!(i32),
+        DataType::Uint32 => decode!(u32),
+        DataType::Float32 => decode!(f32),
+        DataType::Float64 => decode!(f64),
+    }
+}
+
+/// Appends `value`, converted to `datatype`, to `data`.
+fn encode(datatype: DataType, value: f64, data: &mut Vec<u8>, big_endian: bool) {
+    macro_rules! encode {
+        ($type:ty) => {{
+            let value = value as $type;
+            if big_endian {
+                data.extend_from_slice(&value.to_be_bytes())
+            } else {
+                data.extend_from_slice(&value.to_le_bytes())
+            }
+        }};
+    }
+
+    match datatype {
+        DataType::Int8 => encode!(i8),
+        DataType::Uint8 => encode!(u8),
+        DataType::Int16 => encode!(i16),
+        DataType::Uint16 => encode!(u16),
+        DataType::Int32 => encode!(i32),
+        DataType::Uint32 => encode!(u32),
+        DataType::Float32 => encode!(f32),
+        DataType::Float64 => encode!(f64),
+    }
+}
+
+/// A type the values of a field can be read as, or written from.
+///
+/// Values are converted as with `as`: reading a `Float64` field as `u8` saturates, reading an
+/// `Int32` field as `f32` may round.
+pub trait Scalar: Copy {
+    /// The `DataType` of the fields holding values of this type.
+    const DATATYPE: DataType;
+
+    fn from_f64(value: f64) -> Self;
+    fn to_f64(self) -> f64;
+}
+
+macro_rules! scalar {
+    ($($type:ty => $datatype:ident),* $(,)?) => {
+        $(
+            impl Scalar for $type {
+                const DATATYPE: DataType = DataType::$datatype;
+
+                fn from_f64(value: f64) -> Self {
+                    value as $type
+                }
+
+                fn to_f64(self) -> f64 {
+                    self as f64
+                }
+            }
+        )*
+    };
+}
+
+scalar! {
+    i8 => Int8,
+    u8 => Uint8,
+    i16 => Int16,
+    u16 => Uint16,
+    i32 => Int32,
+    u32 => Uint32,
+    f32 => Float32,
+    f64 => Float64,
+}
+
+/// A field of the points of a cloud, resolved from its name by [`CloudView::field`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Field {
+    offset: usize,
+    datatype: DataType,
+    count: usize,
+}
+
+impl Field {
+    pub fn datatype(&self) -> DataType {
+        self.datatype
+    }
+
+    /// The number of values of the field in each point.
+    pub fn count(&self) -> usize {
+        self.count
+    }
+}
+
+/// The bytes of a point of a cloud.
+#[derive(Debug, Clone, Copy)]
+pub struct PointView<'a> {
+    bytes: &'a [u8],
+    big_endian: bool,
+}
+
+impl<'a> PointView<'a> {
+    /// The first value of `field`.
+    pub fn get<T: Scalar>(&self, field: &Field) -> T {
+        self.decode(field, 0)
+    }
+
+    /// The value of `field` at `index`, if `index` is below its `count`.
+    pub fn element<T: Scalar>(&self, field: &Field, index: usize) -> Option<T> {
+        (index < field.count).then(|| self.decode(field, index))
+    }
+
+    fn decode<T: Scalar>(&self, field: &Field, index: usize) -> T {
+        let offset = field.offset + index * datatype_size(field.datatype);
+        T::from_f64(decode(
+            field.datatype,
+            &self.bytes[offset..],
+            self.big_endian,
+        ))
+    }
+}
+
+/// Read access to the points of a valid `PointCloud2`, without copying its data.
+#[derive(Debug, Clone, Copy)]
+pub struct CloudView<'a> {
+    cloud: &'a data_types::PointCloud2,
+}
+
+impl<'a> CloudView<'a> {
+    /// Checks that the fields and the data of `cloud` are consistent, so that its points can be
+    /// read.
+    pub fn new(cloud: &'a data_types::PointCloud2) -> Result<Self, ValidationError> {
+        cloud.validate()?;
+        Ok(Self { cloud })
+    }
+
+    pub fn width(&self) -> u32 {
+        self.cloud.width
+    }
+
+    pub fn height(&self) -> u32 {
+        self.cloud.height
+    }
+
+    /// The number of points of the cloud.
+    pub fn len(&self) -> usize {
+        self.cloud.width as usize * self.cloud.height as usize
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// The field called `name`, if the points have one.
+    pub fn field(&self, name: &str) -> Option<Field> {
+        self.cloud
+            .fields
+            .iter()
+            .find(|field| field.name == name)
+            .map(|field| Field {
+                offset: field.offset as usize,
+                // The cloud is valid: its datatypes are known.
+                datatype: DataType::from_i32(field.datatype).unwrap_or(DataType::Uint8),
+                count: field.count as usize,
+            })
+    }
+
+    /// The point at `index`, in row-major order.
+    pub fn point(&self, index: usize) -> Option<PointView<'a>> {
+        if index >= self.len() {
+            return None;
+        }
+
+        let width = self.cloud.width as usize;
+        let start = (index / width) * self.cloud.row_step as usize
+            + (index % width) * self.cloud.point_step as usize;
+        Some(PointView {
+            bytes: &self.cloud.data[start..start + self.cloud.point_step as usize],
+            big_endian: self.cloud.is_bigendian,
+        })
+    }
+
+    /// The points of the cloud, in row-major order.
+    pub fn points(&self) -> impl ExactSizeIterator<Item = PointView<'a>> + 'a {
+        let view = *self;
+        (0..self.len()).map(move |index| view.point(index).expect("index within the cloud"))
+    }
+
+    /// The first value of the field called `name` of every point, if the points have such a field.
+    pub fn values<T: Scalar + 'a>(
+        &self,
+        name: &str,
+    ) -> Option<impl ExactSizeIterator<Item = T> + 'a> {
+        let field = self.field(name)?;
+        Some(self.points().map(move |point| point.get(&field)))
+    }
+
+    /// The points of the cloud, read as `P`, if the points have all the fields of `P`.
+    pub fn read<P: Point + 'a>(&self) -> Option<impl ExactSizeIterator<Item = P> + 'a> {
+        let fields = P::FIELDS
+            .iter()
+            .map(|(name, _)| self.field(name))
+            .collect::<Option<Vec<_>>>()?;
+        Some(self.points().map(move |point| {
+            P::read(&mut PointReader {
+                point,
+                fields: &fields,
+            })
+        }))
+    }
+}
+
+/// Reads the fields of a point, in the order of `Point::FIELDS`.
+pub struct PointReader<'a> {
+    point: PointView<'a>,
+    fields: &'a [Field],
+}
+
+impl PointReader<'_> {
+    /// Reads the next field.
+    ///
+    /// # Panics
+    ///
+    /// If all the fields of `Point::FIELDS` have already been read.
+    pub fn read<T: Scalar>(&mut self) -> T {
+        let (field, fields) = self
+            .fields
+            .split_first()
+            .expect("more fields read than declared");
+        self.fields = fields;
+        self.point.get(field)
+    }
+}
+
+/// Writes the fields of a point, in the order of `Point::FIELDS`.
+pub struct PointWriter<'a> {
+    data: &'a mut Vec<u8>,
+    fields: &'static [(&'static str, DataType)],
+    big_endian: bool,
+}
+
+impl PointWriter<'_> {
+    /// Writes the next field, converting `value` to its `DataType`.
+    ///
+    /// # Panics
+    ///
+    /// If all the fields of `Point::FIELDS` have already been written.
+    pub fn put<T: Scalar>(&mut self, value: T) {
+        let ((_, datatype), fields) = self
+            .fields
+            .split_first()
+            .expect("more fields written than declared");
+        self.fields = fields;
+        encode(*datatype, value.to_f64(), self.data, self.big_endian);
+    }
+}
+
+/// A point whose fields, each holding a single value, are laid out one after the other.
+pub trait Point: Sized {
+    /// The name and type of the fields, in the order they are laid out.
+    const FIELDS: &'static [(&'static str, DataType)];
+
+    /// Reads the fields, in the order of `FIELDS`.
+    fn read(reader: &mut PointReader<'_>) -> Self;
+
+    /// Writes all the fields, in the order of `FIELDS`.
+    fn write(&self, writer: &mut PointWriter<'_>);
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct PointXYZ {
+    pub x: f32,
+    pub y: f32,
+    pub z: f32,
+}
+
+impl Point for PointXYZ {
+    const FIELDS: &'static [(&'static str, DataType)] = &[
+        ("x", DataType::Float32),
+        ("y", DataType::Float32),
+        ("z", DataType::Float32),
+    ];
+
+    fn read(reader: &mut PointReader<'_>) -> Self {
+        Self {
+            x: reader.read(),
+            y: reader.read(),
+            z: reader.read(),
+        }
+    }
+
+    fn write(&self, writer: &mut PointWriter<'_>) {
+        writer.put(self.x);
+        writer.put(self.y);
+        writer.put(self.z);
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct PointXYZI {
+    pub x: f32,
+    pub y: f32,
+    pub z: f32,
+    pub intensity: f32,
+}
+
+impl Point for PointXYZI {
+    const FIELDS: &'static [(&'static str, DataType)] = &[
+        ("x", DataType::Float32),
+        ("y", DataType::Float32),
+        ("z", DataType::Float32),
+        ("intensity", DataType::Float32),
+    ];
+
+    fn read(reader: &mut PointReader<'_>) -> Self {
+        Self {
+            x: reader.read(),
+            y: reader.read(),
+            z: reader.read(),
+            intensity: reader.read(),
+        }
+    }
+
+    fn write(&self, writer: &mut PointWriter<'_>) {
+        writer.put(self.x);
+        writer.put(self.y);
+        writer.put(self.z);
+        writer.put(self.intensity);
+    }
+}
+
+/// Writes a `PointCloud2` of points of type `P`.
+#[derive(Debug, Clone)]
+pub struct CloudBuilder<P> {
+    header: Option<data_types::Header>,
+    big_endian: bool,
+    is_dense: bool,
+    data: Vec<u8>,
+    len: usize,
+    point: PhantomData<P>,
+}
+
+impl<P: Point> Default for CloudBuilder<P> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<P: Point> CloudBuilder<P> {
+    /// A little-endian, dense and empty cloud.
+    pub fn new() -> Self {
+        Self {
+            header: None,
+            big_endian: false,
+            is_dense: true,
+            data: Vec::new(),
+            len: 0,
+            point: PhantomData,
+        }
+    }
+
+    /// The fields of the points, packed in the order of `P::FIELDS`.
+    pub fn fields() -> Vec<PointField> {
+        let mut offset = 0;
+        P::FIELDS
+            .iter()
+            .map(|(name, datatype)| {
+                let field = PointField {
+                    name: name.to_string(),
+                    offset,
+                    datatype: *datatype as i32,
+                    count: 1,
+                };
+                offset += datatype_size(*datatype) as u32;
+                field
+            })
+            .collect()
+    }
+
+    /// The size of a point, in bytes.
+    pub fn point_step() -> u32 {
+        P::FIELDS
+            .iter()
+            .map(|(_, datatype)| datatype_size(*datatype) as u32)
+            .sum()
+    }
+
+    pub fn header(mut self, header: impl Into<Option<data_types::Header>>) -> Self {
+        self.header = header.into();
+        self
+    }
+
+    /// Must be set before any point is pushed.
+    pub fn big_endian(mut self, big_endian: bool) -> Self {
+        debug_assert!(self.data.is_empty(), "points were already written");
+        self.big_endian = big_endian;
+        self
+    }
+
+    /// Whether all the points are valid, i.e. have no NaN coordinates.
+    pub fn dense(mut self, is_dense: bool) -> Self {
+        self.is_dense = is_dense;
+        self
+    }
+
+    pub fn push(mut self, point: P) -> Self {
+        self.write(&point);
+        self
+    }
+
+    pub fn extend(mut self, points: impl IntoIterator<Item = P>) -> Self {
+        points.into_iter().for_each(|point| self.write(&point));
+        self
+    }
+
+    fn write(&mut self, point: &P) {
+        point.write(&mut PointWriter {
+            data: &mut self.data,
+            fields: P::FIELDS,
+            big_endian: self.big_endian,
+        });
+        self.len += 1;
+    }
+
+    /// An unorganized cloud: a single row holding all the points.
+    pub fn build(self) -> data_types::PointCloud2 {
+        let len = self.len as u32;
+        self.build_rows(1.min(len), len)
+    }
+
+    /// An organized cloud of `height` rows, or `None` if the points cannot be split evenly in
+    /// `height` rows.
+    pub fn build_organized(self, height: u32) -> Option<data_types::PointCloud2> {
+        let len = self.len as u32;
+        if height == 0 {
+            return (len == 0).then(|| self.build_rows(0, 0));
+        }
+        (len % height == 0).then(|| self.build_rows(height, len / height))
+    }
+
+    fn build_rows(self, height: u32, width: u32) -> data_types::PointCloud2 {
+        let point_step = Self::point_step();
+        data_types::PointCloud2 {
+            header: self.header,
+            height,
+            width,
+            fields: Self::fields(),
+            is_bigendian: self.big_endian,
+            point_step,
+            row_step: point_step * width,
+            data: self.data,
+            is_dense: self.is_dense,
+        }
+    }
+}
@@ -0,0 +1,101 @@
+//
+// Copyright (c) 2022 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+use crate::Configurable;
+use zenoh_flow::prelude as zf;
+use zenoh_flow::zferror;
+
+static PAYLOAD_BYTES_KEY: &str = "payload_bytes";
+static BURST_SIZE_KEY: &str = "burst_size";
+
+/// How much a source publishes at each tick of its `Ticker`.
+///
+/// At each tick, the source sends `burst_size` messages back to back, each carrying
+/// `payload_bytes` of data.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Publication {
+    pub payload_bytes: usize,
+    pub burst_size: usize,
+    /// Whether the messages of the source can carry a payload, `payload_bytes` being rejected
+    /// otherwise.
+    payload: bool,
+}
+
+impl Default for Publication {
+    /// A single message, without payload.
+    fn default() -> Self {
+        Self {
+            payload_bytes: 0,
+            burst_size: 1,
+            payload: true,
+        }
+    }
+}
+
+impl Publication {
+    /// A single message, for a source whose messages cannot carry a payload (e.g. a `Float32`):
+    /// setting `payload_bytes` in its configuration is an error.
+    pub fn without_payload() -> Self {
+        Self {
+            payload: false,
+            ..Self::default()
+        }
+    }
+}
+
+impl Configurable for Publication {
+    /// Overrides the publication with the `payload_bytes` and `burst_size` of the configuration.
+    fn configure(mut self, configuration: Option<&zf::Configuration>) -> zf::Result<Self> {
+        let configuration = match configuration {
+            Some(configuration) => configuration,
+            None => return Ok(self),
+        };
+
+        if let Some(payload_bytes) = configuration.get(PAYLOAD_BYTES_KEY) {
+            if !self.payload {
+                return Err(zferror!(
+                    zf::ErrorKind::ConfigurationError,
+                    "`{}` is not supported: the messages of this source carry no payload",
+                    PAYLOAD_BYTES_KEY
+                )
+                .into());
+            }
+            self.payload_bytes = payload_bytes.as_u64().ok_or_else(|| {
+                zferror!(
+                    zf::ErrorKind::ConfigurationError,
+                    "`{}` must be a number of bytes, found: {}",
+                    PAYLOAD_BYTES_KEY,
+                    payload_bytes
+                )
+            })? as usize;
+        }
+
+        if let Some(burst_size) = configuration.get(BURST_SIZE_KEY) {
+            self.burst_size = match burst_size.as_u64() {
+                Some(burst_size) if burst_size > 0 => burst_size as usize,
+                _ => {
+                    return Err(zferror!(
+                        zf::ErrorKind::ConfigurationError,
+                        "`{}` must be a positive number of messages, found: {}",
+                        BURST_SIZE_KEY,
+                        burst_size
+                    )
+                    .into())
+                }
+            };
+        }
+
+        Ok(self)
+    }
+}
@@ -0,0 +1,315 @@
+//
+// Copyright (c) 2022 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+//! Models of the sensors feeding the graph.
+//!
+//! Unlike uniform noise, the messages they generate are internally consistent: the ranges of a
+//! `LaserScan` lie within its bounds and match its angles, the `data` of a `PointCloud2` is
+//! described by its `fields`, `point_step` and `row_step`, and the `data` of an `Image` is
+//! `height * step` bytes long. Every message is a measure of a rectangular room drawn at random,
+//! the sensor standing somewhere inside it.
+
+use crate::data_types;
+use crate::encodings;
+use crate::pointcloud::{CloudBuilder, PointXYZI};
+use rand::Rng;
+use std::f32::consts::PI;
+
+/// The amplitude of the noise added to the measured ranges, in meters.
+const RANGE_NOISE: f32 = 0.01;
+
+/// A rectangular room centered on the origin, seen from `sensor`.
+struct Room {
+    half_extents: [f32; 2],
+    height: f32,
+    sensor: [f32; 3],
+    /// The reflectivity of the walls along -x, +x, -y, +y, of the floor and of the ceiling.
+    reflectivity: [f32; 6],
+}
+
+impl Room {
+    fn sample<R: Rng + ?Sized>(rng: &mut R) -> Self {
+        let half_extents = [rng.gen_range(3.0..15.0), rng.gen_range(3.0..15.0)];
+        let height = rng.gen_range(2.5..4.0);
+        let sensor = [
+            rng.gen_range(-0.8..0.8) * half_extents[0],
+            rng.gen_range(-0.8..0.8) * half_extents[1],
+            rng.gen_range(0.3..height - 0.3),
+        ];
+        let mut reflectivity = [0.0; 6];
+        reflectivity
+            .iter_mut()
+            .for_each(|surface| *surface = rng.gen_range(0.2..1.0));
+
+        Self {
+            half_extents,
+            height,
+            sensor,
+            reflectivity,
+        }
+    }
+
+    /// The distance to the first surface hit in the direction `(azimuth, elevation)`, and the
+    /// reflectivity of that surface.
+    fn hit(&self, azimuth: f32, elevation: f32) -> (f32, f32) {
+        let direction = [
+            elevation.cos() * azimuth.cos(),
+            elevation.cos() * azimuth.sin(),
+            elevation.sin(),
+        ];
+        let bounds = [
+            (-self.half_extents[0], self.half_extents[0]),
+            (-self.half_extents[1], self.half_extents[1]),
+            (0.0, self.height),
+        ];
+
+        let mut hit = (f32::INFINITY, 0.0);
+        for (axis, (low, high)) in bounds.iter().enumerate() {
+            let (distance, surface) = if direction[axis] > f32::EPSILON {
+                ((high - self.sensor[axis]) / direction[axis], 2 * axis + 1)
+            } else if direction[axis] < -f32::EPSILON {
+                ((low - self.sensor[axis]) / direction[axis], 2 * axis)
+            } else {
+                continue;
+            };
+            if distance < hit.0 {
+                hit = (distance, self.reflectivity[surface]);
+            }
+        }
+        hit
+    }
+
+    /// Measures the range in the direction `(azimuth, elevation)`, within `[min, max]`, and its
+    /// intensity.
+    fn measure<R: Rng + ?Sized>(
+        &self,
+        rng: &mut R,
+        azimuth: f32,
+        elevation: f32,
+        min: f32,
+        max: f32,
+    ) -> (f32, f32) {
+        let (distance, reflectivity) = self.hit(azimuth, elevation);
+        let range = (distance + rng.gen_range(-RANGE_NOISE..RANGE_NOISE)).clamp(min, max);
+        (range, 255.0 * reflectivity / (1.0 + 0.1 * range))
+    }
+}
+
+/// A 2D lidar, sweeping a full turn around its vertical axis at each scan.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Lidar2d {
+    pub beams: u32,
+    pub range_min: f32,
+    pub range_max: f32,
+    /// The duration of a turn, in seconds.
+    pub scan_time: f32,
+}
+
+impl Default for Lidar2d {
+    fn default() -> Self {
+        Self {
+            beams: 1024,
+            range_min: 0.1,
+            range_max: 30.0,
+            scan_time: 0.1,
+        }
+    }
+}
+
+impl Lidar2d {
+    /// Scans a room, starting at `-π` and turning counterclockwise.
+    pub fn scan<R: Rng + ?Sized>(
+        &self,
+        header: Option<data_types::Header>,
+        rng: &mut R,
+    ) -> data_types::LaserScan {
+        let room = Room::sample(rng);
+        let angle_increment = 2.0 * PI / self.beams.max(1) as f32;
+        let (ranges, intensities) = (0..self.beams)
+            .map(|beam| {
+                let angle = -PI + beam as f32 * angle_increment;
+                room.measure(rng, angle, 0.0, self.range_min, self.range_max)
+            })
+            .unzip();
+
+        data_types::LaserScan {
+            header,
+            angle_min: -PI,
+            angle_max: -PI + self.beams.saturating_sub(1) as f32 * angle_increment,
+            angle_increment,
+            time_increment: self.scan_time / self.beams.max(1) as f32,
+            scan_time: self.scan_time,
+            range_min: self.range_min,
+            range_max: self.range_max,
+            ranges,
+            intensities,
+        }
+    }
+}
+
+/// A rotating 3D lidar producing organized XYZI point clouds: one row per laser, one column per
+/// firing.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Lidar3d {
+    pub lasers: u32,
+    pub columns: u32,
+    /// The vertical field of view, centered on the horizon, in radians.
+    pub vertical_fov: f32,
+    pub range_min: f32,
+    pub range_max: f32,
+}
+
+impl Default for Lidar3d {
+    fn default() -> Self {
+        Self {
+            lasers: 16,
+            columns: 512,
+            vertical_fov: 30.0_f32.to_radians(),
+            range_min: 0.1,
+            range_max: 100.0,
+        }
+    }
+}
+
+impl Lidar3d {
+    /// Scans a room. All the points are valid: ranges out of bounds are clamped.
+    pub fn scan<R: Rng + ?Sized>(
+        &self,
+        header: Option<data_types::Header>,
+        rng: &mut R,
+    ) -> data_types::PointCloud2 {
+        let room = Room::sample(rng);
+        let azimuth_increment = 2.0 * PI / self.columns.max(1) as f32;
+        let elevation_increment = if self.lasers > 1 {
+            self.vertical_fov / (self.lasers - 1) as f32
+        } else {
+            0.0
+        };
+
+        let mut points = Vec::with_capacity(self.lasers as usize * self.columns as usize);
+        for laser in 0..self.lasers {
+            let elevation = if self.lasers > 1 {
+                -self.vertical_fov / 2.0 + laser as f32 * elevation_increment
+            } else {
+                0.0
+            };
+            for column in 0..self.columns {
+                let azimuth = -PI + column as f32 * azimuth_increment;
+                let (range, intensity) =
+                    room.measure(rng, azimuth, elevation, self.range_min, self.range_max);
+                points.push(PointXYZI {
+                    x: range * elevation.cos() * azimuth.cos(),
+                    y: range * elevation.cos() * azimuth.sin(),
+                    z: range * elevation.sin(),
+                    intensity,
+                });
+            }
+        }
+
+        CloudBuilder::new()
+            .header(header)
+            .extend(points)
+            .build_organized(self.lasers)
+            .expect("a row of points per laser")
+    }
+}
+
+/// The encodings of the images of `Camera`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Encoding {
+    Rgb8,
+    Mono8,
+}
+
+impl Encoding {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Encoding::Rgb8 => encodings::RGB8,
+            Encoding::Mono8 => encodings::MONO8,
+        }
+    }
+
+    /// The number of bytes of a pixel.
+    pub fn channels(&self) -> u32 {
+        match self {
+            Encoding::Rgb8 => 3,
+            Encoding::Mono8 => 1,
+        }
+    }
+}
+
+/// A camera producing images without padding: `step` is `width` times the size of a pixel.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Camera {
+    pub width: u32,
+    pub height: u32,
+    pub encoding: Encoding,
+}
+
+impl Default for Camera {
+    fn default() -> Self {
+        Self {
+            width: 640,
+            height: 480,
+            encoding: Encoding::Rgb8,
+        }
+    }
+}
+
+impl Camera {
+    /// Resizes the images, keeping their aspect ratio, so that their data holds at least `bytes`,
+    /// rounded up to a whole number of rows. `0` keeps the current size.
+    pub fn with_payload(mut self, bytes: usize) -> Self {
+        if bytes > 0 {
+            let channels = self.encoding.channels() as usize;
+            let pixels = (bytes + channels - 1) / channels;
+            let aspect_ratio = self.width.max(1) as f64 / self.height.max(1) as f64;
+            self.width = ((pixels as f64 * aspect_ratio).sqrt().round() as u32).max(1);
+            let width = self.width as usize;
+            self.height = ((pixels + width - 1) / width) as u32;
+        }
+        self
+    }
+
+    /// Captures a frame: a gradient whose phase is drawn at random.
+    pub fn capture<R: Rng + ?Sized>(
+        &self,
+        header: Option<data_types::Header>,
+        rng: &mut R,
+    ) -> data_types::Image {
+        let channels = self.encoding.channels();
+        let step = self.width * channels;
+        let phase: u8 = rng.gen();
+        let span = (self.width + self.height).max(1) as usize;
+        let mut data = Vec::with_capacity(self.height as usize * step as usize);
+        for row in 0..self.height as usize {
+            for column in 0..self.width as usize {
+                let shade = ((row + column) * 255 / span) as u8;
+                (0..channels as u8).for_each(|channel| {
+                    data.push(shade.wrapping_add(phase).wrapping_add(channel * 85))
+                });
+            }
+        }
+
+        data_types::Image {
+            header,
+            height: self.height,
+            width: self.width,
+            encoding: self.encoding.name().to_string(),
+            is_bigendian: false,
+            step,
+            data,
+        }
+    }
+}
@@ -0,0 +1,290 @@
+//
+// Copyright (c) 2022 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+//! Throughput and gap statistics of the ports of a node.
+//!
+//! Nodes count the messages they receive and send through a [`Statistics`], and the ports taken
+//! with `take_input` and `take_output` the bytes their codec decodes and encodes. If the
+//! configuration of the node contains a `stats_dir`, the throughput of each port is appended to
+//! `<stats_dir>/<node>.csv` every `stats_interval_ms` (1000 by default), as measured by the
+//! [`Clock`] of the node.
+//!
+//! Zenoh-Flow only encodes the messages sent to another runtime: the bytes of the messages
+//! exchanged within a runtime are not counted.
+
+use crate::clock::Clock;
+use async_std::fs::File;
+use async_std::io::WriteExt;
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, Weak};
+use std::time::{Duration, Instant};
+use zenoh_flow::prelude as zf;
+use zenoh_flow::zferror;
+
+static STATS_DIR_KEY: &str = "stats_dir";
+static STATS_INTERVAL_MS_KEY: &str = "stats_interval_ms";
+const DEFAULT_STATS_INTERVAL: Duration = Duration::from_millis(1000);
+
+/// An interval between two messages longer than `GAP_FACTOR` times the average interval is
+/// counted as a gap.
+const GAP_FACTOR: f64 = 2.0;
+/// The number of intervals to observe before the average is trusted to detect gaps.
+const GAP_WARMUP: u64 = 8;
+/// The weight of the last interval in the (exponential moving) average interval.
+const GAP_ALPHA: f64 = 0.125;
+
+#[derive(Debug, Default)]
+struct Arrivals {
+    last: Option<Instant>,
+    mean_interval: f64,
+    intervals: u64,
+}
+
+impl Arrivals {
+    /// Records an arrival and returns whether it follows a gap.
+    fn record(&mut self, now: Instant) -> bool {
+        let last = self.last.replace(now);
+        let interval = match last {
+            Some(last) => now.duration_since(last).as_secs_f64(),
+            None => return false,
+        };
+
+        let gap = self.intervals >= GAP_WARMUP && interval > GAP_FACTOR * self.mean_interval;
+        if self.intervals == 0 {
+            self.mean_interval = interval;
+        } else {
+            self.mean_interval += GAP_ALPHA * (interval - self.mean_interval);
+        }
+        self.intervals += 1;
+        gap
+    }
+}
+
+/// The counters of a port, reset at each report.
+#[derive(Debug, Default)]
+struct PortCounters {
+    messages: AtomicU64,
+    bytes: AtomicU64,
+    gaps: AtomicU64,
+    arrivals: Mutex<Arrivals>,
+}
+
+impl PortCounters {
+    fn record(&self, now: Instant) {
+        self.messages.fetch_add(1, Ordering::Relaxed);
+
+        let gap = match self.arrivals.lock() {
+            Ok(mut arrivals) => arrivals.record(now),
+            Err(_) => false,
+        };
+        if gap {
+            self.gaps.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    fn record_bytes(&self, bytes: usize) {
+        self.bytes.fetch_add(bytes as u64, Ordering::Relaxed);
+    }
+
+    /// Returns the messages, bytes and gaps counted since the last call.
+    fn take(&self) -> (u64, u64, u64) {
+        (
+            self.messages.swap(0, Ordering::Relaxed),
+            self.bytes.swap(0, Ordering::Relaxed),
+            self.gaps.swap(0, Ordering::Relaxed),
+        )
+    }
+}
+
+#[derive(Debug, Default)]
+struct Ports {
+    inputs: HashMap<String, PortCounters>,
+    outputs: HashMap<String, PortCounters>,
+}
+
+/// The message counters of the ports of a node.
+#[derive(Debug, Clone, Default)]
+pub struct Statistics {
+    ports: Arc<Ports>,
+    clock: Clock,
+}
+
+impl Statistics {
+    /// Creates the counters of the `inputs` and `outputs` of `node` and, if `stats_dir` is set in
+    /// its configuration, starts reporting them.
+    ///
+    /// The report stops when the `Statistics`, and all its clones, are dropped. An error is
+    /// returned if its file cannot be created, the errors writing to it are logged.
+    pub fn new(
+        node: &str,
+        inputs: &[&str],
+        outputs: &[&str],
+        configuration: Option<&zf::Configuration>,
+    ) -> zf::Result<Self> {
+        let counters = |ports: &[&str]| {
+            ports
+                .iter()
+                .map(|port| (port.to_string(), PortCounters::default()))
+                .collect()
+        };
+        let statistics = Self {
+            ports: Arc::new(Ports {
+                inputs: counters(inputs),
+                outputs: counters(outputs),
+            }),
+            clock: Clock::from_configuration(configuration)?,
+        };
+
+        let configuration = match configuration {
+            Some(configuration) => configuration,
+            None => return Ok(statistics),
+        };
+
+        let dir = match configuration.get(STATS_DIR_KEY) {
+            Some(dir) => dir.as_str().ok_or_else(|| {
+                zferror!(
+                    zf::ErrorKind::ConfigurationError,
+                    "`{}` must be a path",
+                    STATS_DIR_KEY
+                )
+            })?,
+            None => return Ok(statistics),
+        };
+
+        let interval = match configuration.get(STATS_INTERVAL_MS_KEY) {
+            Some(interval) => match interval.as_u64() {
+                Some(interval) if interval > 0 => Duration::from_millis(interval),
+                _ => {
+                    return Err(zferror!(
+                        zf::ErrorKind::ConfigurationError,
+                        "`{}` must be a positive number of milliseconds",
+                        STATS_INTERVAL_MS_KEY
+                    )
+                    .into())
+                }
+            },
+            None => DEFAULT_STATS_INTERVAL,
+        };
+
+        let path = PathBuf::from(dir).join(format!("{}.csv", node));
+        let file = std::fs::File::create(&path).map_err(|e| {
+            zferror!(
+                zf::ErrorKind::IOError,
+                "Could not create {}: {}",
+                path.display(),
+                e
+            )
+        })?;
+        async_std::task::spawn(report(
+            Arc::downgrade(&statistics.ports),
+            statistics.clock.clone(),
+            File::from(file),
+            path,
+            interval,
+        ));
+
+        Ok(statistics)
+    }
+
+    /// Counts a message received on the input `port`.
+    pub fn received(&self, port: &str) {
+        if let Some(counters) = self.ports.inputs.get(port) {
+            counters.record(self.clock.now());
+        }
+    }
+
+    /// Counts a message sent on the output `port`.
+    pub fn sent(&self, port: &str) {
+        if let Some(counters) = self.ports.outputs.get(port) {
+            counters.record(self.clock.now());
+        }
+    }
+
+    /// Counts the `bytes` the codec of the input `port` decoded.
+    pub fn decoded(&self, port: &str, bytes: usize) {
+        if let Some(counters) = self.ports.inputs.get(port) {
+            counters.record_bytes(bytes);
+        }
+    }
+
+    /// Counts the `bytes` the codec of the output `port` encoded.
+    pub fn encoded(&self, port: &str, bytes: usize) {
+        if let Some(counters) = self.ports.outputs.get(port) {
+            counters.record_bytes(bytes);
+        }
+    }
+}
+
+/// Appends, every `interval`, the throughput of each port to `file`, the CSV file at `path`.
+///
+/// The report stops at the first error writing to the file.
+async fn report(
+    ports: Weak<Ports>,
+    clock: Clock,
+    mut file: File,
+    path: PathBuf,
+    interval: Duration,
+) {
+    let mut report =
+        String::from("time_ms,direction,port,messages,messages_per_sec,bytes_per_sec,gaps\n");
+    let mut last_report = clock.now();
+
+    loop {
+        clock.sleep(interval).await;
+        let ports = match ports.upgrade() {
+            Some(ports) => ports,
+            None => return,
+        };
+
+        let now = clock.now();
+        let elapsed = now.duration_since(last_report).as_secs_f64();
+        last_report = now;
+        let time_ms = clock.since_epoch().as_millis();
+
+        for (direction, counters) in [("input", &ports.inputs), ("output", &ports.outputs)] {
+            let mut counters = counters.iter().collect::<Vec<_>>();
+            counters.sort_by(|(a, _), (b, _)| a.cmp(b));
+            for (port, counters) in counters {
+                let (messages, bytes, gaps) = counters.take();
+                report.push_str(&format!(
+                    "{},{},{},{},{:.2},{:.2},{}\n",
+                    time_ms,
+                    direction,
+                    port,
+                    messages,
+                    messages as f64 / elapsed,
+                    bytes as f64 / elapsed,
+                    gaps
+                ));
+            }
+        }
+
+        if let Err(e) = write(&mut file, &report).await {
+            log::error!(
+                "Could not write the statistics to {}, stopping their report: {}",
+                path.display(),
+                e
+            );
+            return;
+        }
+        report.clear();
+    }
+}
+
+async fn write(file: &mut File, report: &str) -> std::io::Result<()> {
+    file.write_all(report.as_bytes()).await?;
+    file.flush().await
+}
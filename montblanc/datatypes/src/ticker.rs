@@ -0,0 +1,195 @@
+//
+// Copyright (c) 2022 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+//! Periodic ticks at absolute deadlines, for the nodes publishing at a fixed rate.
+//!
+//! Contrary to sleeping for a period between two publications, the time taken to publish does not
+//! delay the next tick. And as the deadline of the next tick is kept by the `Ticker`, a `tick()`
+//! that is dropped before it completes, e.g. when another branch of a `select!` wins, does not
+//! postpone it either.
+//!
+//! The time is read from a [`Clock`], the one named by the configuration of the node if any.
+
+use crate::clock::Clock;
+use crate::Configurable;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use zenoh_flow::prelude as zf;
+use zenoh_flow::zferror;
+
+static RATE_HZ_KEY: &str = "rate_hz";
+/// The highest rate, whose period is 1ns.
+const MAX_RATE_HZ: f64 = 1e9;
+static MISSED_TICKS_KEY: &str = "missed_ticks";
+
+/// What a `Ticker` does when a whole period, or more, elapsed after a deadline before it ticked.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum MissedTicks {
+    /// The missed ticks are dropped: the next tick is at the next deadline still to come.
+    #[default]
+    Skip,
+    /// The missed ticks happen right away, one after the other, until the ticker is back on time.
+    Burst,
+    /// The deadlines are shifted: the next tick is a period after the late one.
+    Delay,
+}
+
+impl MissedTicks {
+    pub const ALL: [MissedTicks; 3] = [MissedTicks::Skip, MissedTicks::Burst, MissedTicks::Delay];
+
+    /// The name of the policy in the configuration.
+    pub fn name(&self) -> &'static str {
+        match self {
+            MissedTicks::Skip => "skip",
+            MissedTicks::Burst => "burst",
+            MissedTicks::Delay => "delay",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL
+            .iter()
+            .copied()
+            .find(|missed_ticks| missed_ticks.name() == name)
+    }
+}
+
+/// Ticks every `period`, the first tick being a period after the first call to `tick()`.
+#[derive(Debug)]
+pub struct Ticker {
+    period: Duration,
+    missed_ticks: MissedTicks,
+    clock: Clock,
+    next: Mutex<Option<Instant>>,
+}
+
+impl Ticker {
+    /// # Panics
+    ///
+    /// If `period` is zero.
+    pub fn every(period: Duration) -> Self {
+        assert!(!period.is_zero(), "A ticker cannot tick every 0s");
+        Self {
+            period,
+            missed_ticks: MissedTicks::default(),
+            clock: Clock::system(),
+            next: Mutex::new(None),
+        }
+    }
+
+    pub fn with_clock(mut self, clock: Clock) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    pub fn with_missed_ticks(mut self, missed_ticks: MissedTicks) -> Self {
+        self.missed_ticks = missed_ticks;
+        self
+    }
+
+    pub fn period(&self) -> Duration {
+        self.period
+    }
+
+    pub fn missed_ticks(&self) -> MissedTicks {
+        self.missed_ticks
+    }
+
+    /// Waits for the next deadline, and returns it.
+    pub async fn tick(&self) -> Instant {
+        let deadline = *self
+            .next
+            .lock()
+            .expect("The lock of a ticker was poisoned")
+            .get_or_insert_with(|| self.clock.now() + self.period);
+
+        if deadline > self.clock.now() {
+            self.clock.sleep_until(deadline).await;
+        }
+
+        let next = self.next_deadline(deadline, self.clock.now());
+        *self.next.lock().expect("The lock of a ticker was poisoned") = Some(next);
+        deadline
+    }
+
+    /// The deadline following `deadline`, the tick of which happened at `now`.
+    fn next_deadline(&self, deadline: Instant, now: Instant) -> Instant {
+        let late = now.saturating_duration_since(deadline);
+        if late < self.period {
+            return deadline + self.period;
+        }
+
+        match self.missed_ticks {
+            MissedTicks::Skip => {
+                let missed = late.as_nanos() / self.period.as_nanos();
+                deadline + Duration::from_nanos(((missed + 1) * self.period.as_nanos()) as u64)
+            }
+            MissedTicks::Burst => deadline + self.period,
+            MissedTicks::Delay => now + self.period,
+        }
+    }
+}
+
+impl Configurable for Ticker {
+    /// Overrides the period with the `rate_hz` of the configuration, the behaviour on missed ticks
+    /// with its `missed_ticks`: `skip` (the default), `burst` or `delay`, and the clock with its
+    /// `clock`.
+    fn configure(mut self, configuration: Option<&zf::Configuration>) -> zf::Result<Self> {
+        let configuration = match configuration {
+            Some(configuration) => configuration,
+            None => return Ok(self),
+        };
+
+        if let Some(rate) = configuration.get(RATE_HZ_KEY) {
+            // Above 1GHz, the period would be shorter than the resolution of a `Duration`.
+            let period = rate
+                .as_f64()
+                .filter(|rate| *rate > 0.0 && *rate <= MAX_RATE_HZ)
+                .and_then(|rate| Duration::try_from_secs_f64(1.0 / rate).ok())
+                .filter(|period| !period.is_zero());
+            self.period = match period {
+                Some(period) => period,
+                None => {
+                    return Err(zferror!(
+                        zf::ErrorKind::ConfigurationError,
+                        "`{}` must be a positive number of at most 1e9, found: {}",
+                        RATE_HZ_KEY,
+                        rate
+                    )
+                    .into())
+                }
+            };
+        }
+
+        if let Some(missed_ticks) = configuration.get(MISSED_TICKS_KEY) {
+            self.missed_ticks = missed_ticks
+                .as_str()
+                .and_then(MissedTicks::from_name)
+                .ok_or_else(|| {
+                    zferror!(
+                        zf::ErrorKind::ConfigurationError,
+                        "`{}` must be `skip`, `burst` or `delay`, found: {}",
+                        MISSED_TICKS_KEY,
+                        missed_ticks
+                    )
+                })?;
+        }
+
+        if let Some(clock) = Clock::configured(Some(configuration))? {
+            self.clock = clock;
+        }
+
+        Ok(self)
+    }
+}
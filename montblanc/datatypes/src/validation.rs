@@ -0,0 +1,238 @@
+//
+// Copyright (c) 2022 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+//! Checks that the messages of `data_types` are internally consistent.
+
+use crate::data_types;
+use crate::data_types::point_cloud2::point_field::DataType;
+use crate::encodings;
+use crate::pointcloud::datatype_size;
+use std::fmt;
+
+/// How far from 1 the norm of a `Quaternion` may be.
+pub const QUATERNION_TOLERANCE: f64 = 1e-6;
+
+/// The reason why a message is invalid.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ValidationError {
+    /// The `encoding` of an `Image` is not one of `encodings`.
+    UnknownEncoding(String),
+    /// A row of an `Image` is shorter than `width` pixels.
+    StepTooSmall { step: u32, row_bytes: u64 },
+    /// The `data` of an `Image` or a `PointCloud2` is not `height` rows long.
+    DataLength { expected: u64, actual: usize },
+    /// A field of a `PointCloud2` has an unknown `datatype`.
+    UnknownDatatype { field: String, datatype: i32 },
+    /// A field of a `PointCloud2` does not fit in `point_step`.
+    FieldOutOfPoint {
+        field: String,
+        end: u64,
+        point_step: u32,
+    },
+    /// The `row_step` of a `PointCloud2` is not `point_step * width`.
+    RowStep { row_step: u32, expected: u64 },
+    /// The `angle_increment` of a `LaserScan` does not go from `angle_min` to `angle_max`.
+    AngleIncrement {
+        angle_min: f32,
+        angle_max: f32,
+        angle_increment: f32,
+    },
+    /// The `range_min` and `range_max` of a `LaserScan` are not an interval of positive numbers.
+    RangeBounds { range_min: f32, range_max: f32 },
+    /// There is not a range per angle in the span of a `LaserScan`.
+    RangeCount { expected: usize, actual: usize },
+    /// There are intensities, but not one per range, in a `LaserScan`.
+    IntensityCount { expected: usize, actual: usize },
+    /// The norm of a `Quaternion` is not 1.
+    NotNormalised { norm: f64 },
+}
+
+impl fmt::Display for ValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ValidationError::UnknownEncoding(encoding) => {
+                write!(f, "unknown encoding `{}`", encoding)
+            }
+            ValidationError::StepTooSmall { step, row_bytes } => write!(
+                f,
+                "step is {} bytes but a row of pixels takes {} bytes",
+                step, row_bytes
+            ),
+            ValidationError::DataLength { expected, actual } => {
+                write!(f, "data is {} bytes, expected {}", actual, expected)
+            }
+            ValidationError::UnknownDatatype { field, datatype } => {
+                write!(f, "field `{}` has an unknown datatype: {}", field, datatype)
+            }
+            ValidationError::FieldOutOfPoint {
+                field,
+                end,
+                point_step,
+            } => write!(
+                f,
+                "field `{}` ends at byte {}, beyond the point step of {} bytes",
+                field, end, point_step
+            ),
+            ValidationError::RowStep { row_step, expected } => {
+                write!(f, "row step is {} bytes, expected {}", row_step, expected)
+            }
+            ValidationError::AngleIncrement {
+                angle_min,
+                angle_max,
+                angle_increment,
+            } => write!(
+                f,
+                "an increment of {} does not go from {} to {}",
+                angle_increment, angle_min, angle_max
+            ),
+            ValidationError::RangeBounds {
+                range_min,
+                range_max,
+            } => write!(f, "invalid range bounds [{}, {}]", range_min, range_max),
+            ValidationError::RangeCount { expected, actual } => {
+                write!(f, "{} ranges, expected {}", actual, expected)
+            }
+            ValidationError::IntensityCount { expected, actual } => {
+                write!(f, "{} intensities, expected 0 or {}", actual, expected)
+            }
+            ValidationError::NotNormalised { norm } => {
+                write!(f, "the norm of the quaternion is {}", norm)
+            }
+        }
+    }
+}
+
+impl std::error::Error for ValidationError {}
+
+/// A message whose fields can be checked against each other.
+pub trait Validate {
+    /// Returns the first inconsistency found in the message, if any.
+    fn validate(&self) -> Result<(), ValidationError>;
+}
+
+impl Validate for data_types::Image {
+    fn validate(&self) -> Result<(), ValidationError> {
+        let pixel_size = encodings::pixel_size(&self.encoding)
+            .ok_or_else(|| ValidationError::UnknownEncoding(self.encoding.clone()))?;
+
+        let row_bytes = self.width as u64 * pixel_size as u64;
+        if (self.step as u64) < row_bytes {
+            return Err(ValidationError::StepTooSmall {
+                step: self.step,
+                row_bytes,
+            });
+        }
+
+        let expected = self.step as u64 * self.height as u64;
+        if expected != self.data.len() as u64 {
+            return Err(ValidationError::DataLength {
+                expected,
+                actual: self.data.len(),
+            });
+        }
+
+        Ok(())
+    }
+}
+
+impl Validate for data_types::PointCloud2 {
+    fn validate(&self) -> Result<(), ValidationError> {
+        for field in &self.fields {
+            let datatype = DataType::from_i32(field.datatype).ok_or_else(|| {
+                ValidationError::UnknownDatatype {
+                    field: field.name.clone(),
+                    datatype: field.datatype,
+                }
+            })?;
+            let end = field.offset as u64 + datatype_size(datatype) as u64 * field.count as u64;
+            if end > self.point_step as u64 {
+                return Err(ValidationError::FieldOutOfPoint {
+                    field: field.name.clone(),
+                    end,
+                    point_step: self.point_step,
+                });
+            }
+        }
+
+        let expected = self.point_step as u64 * self.width as u64;
+        if self.row_step as u64 != expected {
+            return Err(ValidationError::RowStep {
+                row_step: self.row_step,
+                expected,
+            });
+        }
+
+        let expected = self.row_step as u64 * self.height as u64;
+        if expected != self.data.len() as u64 {
+            return Err(ValidationError::DataLength {
+                expected,
+                actual: self.data.len(),
+            });
+        }
+
+        Ok(())
+    }
+}
+
+impl Validate for data_types::LaserScan {
+    fn validate(&self) -> Result<(), ValidationError> {
+        if !(0.0..=self.range_max).contains(&self.range_min) {
+            return Err(ValidationError::RangeBounds {
+                range_min: self.range_min,
+                range_max: self.range_max,
+            });
+        }
+
+        // The number of increments between the first and the last angle, which must be whole (up
+        // to the precision of `f32`) and positive.
+        let increments =
+            (self.angle_max as f64 - self.angle_min as f64) / self.angle_increment as f64;
+        if !increments.is_finite()
+            || increments < 0.0
+            || (increments - increments.round()).abs() > 1e-2
+        {
+            return Err(ValidationError::AngleIncrement {
+                angle_min: self.angle_min,
+                angle_max: self.angle_max,
+                angle_increment: self.angle_increment,
+            });
+        }
+
+        let expected = increments.round() as usize + 1;
+        if self.ranges.len() != expected {
+            return Err(ValidationError::RangeCount {
+                expected,
+                actual: self.ranges.len(),
+            });
+        }
+        if !self.intensities.is_empty() && self.intensities.len() != expected {
+            return Err(ValidationError::IntensityCount {
+                expected,
+                actual: self.intensities.len(),
+            });
+        }
+
+        Ok(())
+    }
+}
+
+impl Validate for data_types::Quaternion {
+    fn validate(&self) -> Result<(), ValidationError> {
+        let norm = (self.x * self.x + self.y * self.y + self.z * self.z + self.w * self.w).sqrt();
+        if (norm - 1.0).abs() > QUATERNION_TOLERANCE || norm.is_nan() {
+            return Err(ValidationError::NotNormalised { norm });
+        }
+        Ok(())
+    }
+}
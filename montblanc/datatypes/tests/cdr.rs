@@ -0,0 +1,432 @@
+//
+// Copyright (c) 2022 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+//! The CDR of the messages of `data_types`: decoded as it was encoded, laid out as ROS 2 does, and
+//! truncated or corrupted buffers rejected rather than panicking.
+
+mod strategies;
+
+use datatypes::cdr::{self, CdrError, COVARIANCE_LENGTH};
+use datatypes::data_types;
+use datatypes::data_types::point_cloud2::point_field::DataType;
+use datatypes::data_types::point_cloud2::PointField;
+use proptest::collection::vec;
+use proptest::prelude::*;
+use proptest::sample::Index;
+use strategies::*;
+
+/// A message as it is read back from CDR, which has no optional fields.
+trait Filled {
+    fn filled(self) -> Self;
+}
+
+/// Implements `Filled` for messages whose optional fields are the listed ones.
+macro_rules! filled {
+    ($($message:ident { $($field:ident),* })*) => {
+        $(
+            impl Filled for data_types::$message {
+                #[allow(unused_mut)]
+                fn filled(mut self) -> Self {
+                    $(self.$field = Some(self.$field.unwrap_or_default().filled());)*
+                    self
+                }
+            }
+        )*
+    };
+}
+
+filled! {
+    Timestamp {}
+    Header {}
+    Point {}
+    Quaternion {}
+    Vector3 {}
+    Vector3Stamped { header, vector }
+    Pose { position, orientation }
+    Twist { linear, angular }
+    TwistWithCovarianceStamped { header, twist }
+    Wrench { force, torque }
+    WrenchStamped { header, wrench }
+    Image { header }
+    PointCloud2 { header }
+    LaserScan { header }
+    Float32 {}
+    Float64 {}
+    Int32 {}
+    Int64 {}
+    String {}
+}
+
+impl Filled for PointField {
+    fn filled(self) -> Self {
+        self
+    }
+}
+
+/// An empty covariance is written as zeros.
+impl Filled for data_types::TwistWithCovariance {
+    fn filled(mut self) -> Self {
+        self.twist = Some(self.twist.unwrap_or_default().filled());
+        if self.covariance.is_empty() {
+            self.covariance = vec![0.0; COVARIANCE_LENGTH];
+        }
+        self
+    }
+}
+
+/// Generates, for each message, the tests of its CDR.
+macro_rules! roundtrip {
+    ($($name:ident: $strategy:expr, $message:ty;)*) => {
+        $(
+            mod $name {
+                use super::*;
+
+                proptest! {
+                    #[test]
+                    fn decodes_what_was_encoded(message in $strategy) {
+                        match cdr::serialize(&message) {
+                            Ok(buf) => {
+                                let decoded = cdr::deserialize::<$message>(&buf)?;
+                                prop_assert_eq!(decoded, message.filled());
+                            }
+                            // Protobuf does not enforce the length of the covariance, nor the
+                            // datatype of the fields of a point cloud, but CDR does.
+                            Err(error) => prop_assert!(
+                                matches!(
+                                    error,
+                                    CdrError::ArrayLength { expected: COVARIANCE_LENGTH, .. }
+                                        | CdrError::InvalidDatatype(_)
+                                ),
+                                "{}",
+                                error
+                            ),
+                        }
+                    }
+
+                    #[test]
+                    fn rejects_truncated_buffers(message in $strategy, end in any::<Index>()) {
+                        if let Ok(buf) = cdr::serialize(&message) {
+                            // Every field has a fixed position: any prefix misses one.
+                            let end = end.index(buf.len());
+                            prop_assert!(
+                                matches!(
+                                    cdr::deserialize::<$message>(&buf[..end]),
+                                    Err(CdrError::UnexpectedEnd { .. })
+                                ),
+                                "{} of {} bytes were accepted",
+                                end,
+                                buf.len()
+                            );
+                        }
+                    }
+
+                    #[test]
+                    fn does_not_panic_on_corrupted_buffers(
+                        message in $strategy,
+                        corruptions in vec((any::<Index>(), any::<u8>()), 1..8),
+                    ) {
+                        if let Ok(mut buf) = cdr::serialize(&message) {
+                            for (index, byte) in corruptions {
+                                let index = index.index(buf.len());
+                                buf[index] = byte;
+                            }
+                            let _ = cdr::deserialize::<$message>(&buf);
+                        }
+                    }
+
+                    #[test]
+                    fn does_not_panic_on_random_buffers(buf in vec(any::<u8>(), 0..256)) {
+                        let _ = cdr::deserialize::<$message>(&buf);
+                    }
+                }
+            }
+        )*
+    };
+}
+
+roundtrip! {
+    timestamp: timestamp(), data_types::Timestamp;
+    header: header(), data_types::Header;
+    point: point(), data_types::Point;
+    quaternion: quaternion(), data_types::Quaternion;
+    vector3: vector3(), data_types::Vector3;
+    vector3_stamped: vector3_stamped(), data_types::Vector3Stamped;
+    pose: pose(), data_types::Pose;
+    twist: twist(), data_types::Twist;
+    twist_with_covariance: twist_with_covariance(), data_types::TwistWithCovariance;
+    twist_with_covariance_stamped:
+        twist_with_covariance_stamped(),
+        data_types::TwistWithCovarianceStamped;
+    wrench: wrench(), data_types::Wrench;
+    wrench_stamped: wrench_stamped(), data_types::WrenchStamped;
+    image: image(), data_types::Image;
+    point_field: point_field(), PointField;
+    pointcloud2: pointcloud2(), data_types::PointCloud2;
+    laserscan: laserscan(), data_types::LaserScan;
+    float32: float().prop_map(|value| data_types::Float32 { value }), data_types::Float32;
+    float64: double().prop_map(|value| data_types::Float64 { value }), data_types::Float64;
+    int32: any::<i32>().prop_map(|value| data_types::Int32 { value }), data_types::Int32;
+    int64: any::<i64>().prop_map(|value| data_types::Int64 { value }), data_types::Int64;
+    string: ".{0,64}".prop_map(|value| data_types::String { value }), data_types::String;
+}
+
+/// A point cloud of two points with `x` and `y` `float32` fields, of the given endianness.
+fn two_points(is_bigendian: bool, data: &[u8]) -> data_types::PointCloud2 {
+    let field = |name: &str, offset| PointField {
+        name: name.into(),
+        offset,
+        datatype: DataType::Float32 as i32,
+        count: 1,
+    };
+    data_types::PointCloud2 {
+        header: Some(data_types::Header {
+            sec: 1,
+            nanosec: 2,
+            frame_id: "map".into(),
+        }),
+        height: 1,
+        width: 2,
+        fields: vec![field("x", 0), field("y", 4)],
+        is_bigendian,
+        point_step: 8,
+        row_step: 16,
+        data: data.to_vec(),
+        is_dense: true,
+    }
+}
+
+/// The points (1, 2) and (3, 4), in little-endian.
+const POINTS_LE: [u8; 16] = [
+    0x00, 0x00, 0x80, 0x3f, 0x00, 0x00, 0x00, 0x40, //
+    0x00, 0x00, 0x40, 0x40, 0x00, 0x00, 0x80, 0x40,
+];
+
+/// The points (1, 2) and (3, 4), in big-endian.
+const POINTS_BE: [u8; 16] = [
+    0x3f, 0x80, 0x00, 0x00, 0x40, 0x00, 0x00, 0x00, //
+    0x40, 0x40, 0x00, 0x00, 0x40, 0x80, 0x00, 0x00,
+];
+
+/// `two_points(false, &POINTS_LE)` as a ROS 2 node publishes it: with the padding of the message
+/// to 4 bytes announced in the options of the encapsulation header.
+#[rustfmt::skip]
+const POINTCLOUD2_LE: [u8; 108] = [
+    0x00, 0x01, 0x00, 0x03,                         // CDR_LE, 3 bytes of padding
+    0x01, 0x00, 0x00, 0x00,                         // header.stamp.sec
+    0x02, 0x00, 0x00, 0x00,                         // header.stamp.nanosec
+    0x04, 0x00, 0x00, 0x00, b'm', b'a', b'p', 0x00, // header.frame_id
+    0x01, 0x00, 0x00, 0x00,                         // height
+    0x02, 0x00, 0x00, 0x00,                         // width
+    0x02, 0x00, 0x00, 0x00,                         // fields
+    0x02, 0x00, 0x00, 0x00, b'x', 0x00, 0x00, 0x00, //   name
+    0x00, 0x00, 0x00, 0x00,                         //   offset
+    0x07, 0x00, 0x00, 0x00,                         //   datatype: FLOAT32
+    0x01, 0x00, 0x00, 0x00,                         //   count
+    0x02, 0x00, 0x00, 0x00, b'y', 0x00, 0x00, 0x00, //   name
+    0x04, 0x00, 0x00, 0x00,                         //   offset
+    0x07, 0x00, 0x00, 0x00,                         //   datatype: FLOAT32
+    0x01, 0x00, 0x00, 0x00,                         //   count
+    0x00, 0x00, 0x00, 0x00,                         // is_bigendian
+    0x08, 0x00, 0x00, 0x00,                         // point_step
+    0x10, 0x00, 0x00, 0x00,                         // row_step
+    0x10, 0x00, 0x00, 0x00,                         // data
+    0x00, 0x00, 0x80, 0x3f, 0x00, 0x00, 0x00, 0x40,
+    0x00, 0x00, 0x40, 0x40, 0x00, 0x00, 0x80, 0x40,
+    0x01, 0x00, 0x00, 0x00,                         // is_dense, padding
+];
+
+/// `two_points(true, &POINTS_BE)` as a ROS 2 node on a big-endian machine publishes it.
+#[rustfmt::skip]
+const POINTCLOUD2_BE: [u8; 108] = [
+    0x00, 0x00, 0x00, 0x03,                         // CDR_BE, 3 bytes of padding
+    0x00, 0x00, 0x00, 0x01,                         // header.stamp.sec
+    0x00, 0x00, 0x00, 0x02,                         // header.stamp.nanosec
+    0x00, 0x00, 0x00, 0x04, b'm', b'a', b'p', 0x00, // header.frame_id
+    0x00, 0x00, 0x00, 0x01,                         // height
+    0x00, 0x00, 0x00, 0x02,                         // width
+    0x00, 0x00, 0x00, 0x02,                         // fields
+    0x00, 0x00, 0x00, 0x02, b'x', 0x00, 0x00, 0x00, //   name
+    0x00, 0x00, 0x00, 0x00,                         //   offset
+    0x07, 0x00, 0x00, 0x00,                         //   datatype: FLOAT32
+    0x00, 0x00, 0x00, 0x01,                         //   count
+    0x00, 0x00, 0x00, 0x02, b'y', 0x00, 0x00, 0x00, //   name
+    0x00, 0x00, 0x00, 0x04,                         //   offset
+    0x07, 0x00, 0x00, 0x00,                         //   datatype: FLOAT32
+    0x00, 0x00, 0x00, 0x01,                         //   count
+    0x01, 0x00, 0x00, 0x00,                         // is_bigendian
+    0x00, 0x00, 0x00, 0x08,                         // point_step
+    0x00, 0x00, 0x00, 0x10,                         // row_step
+    0x00, 0x00, 0x00, 0x10,                         // data
+    0x3f, 0x80, 0x00, 0x00, 0x40, 0x00, 0x00, 0x00,
+    0x40, 0x40, 0x00, 0x00, 0x40, 0x80, 0x00, 0x00,
+    0x01, 0x00, 0x00, 0x00,                         // is_dense, padding
+];
+
+#[test]
+fn writes_a_pointcloud2_as_ros2() {
+    let buf = cdr::serialize(&two_points(false, &POINTS_LE)).unwrap();
+    // Without the padding, which is not needed to read the message.
+    assert_eq!(buf[..4], cdr::ENCAPSULATION_LE);
+    assert_eq!(buf[4..], POINTCLOUD2_LE[4..105]);
+}
+
+#[test]
+fn reads_a_little_endian_pointcloud2_of_ros2() {
+    assert_eq!(
+        cdr::deserialize::<data_types::PointCloud2>(&POINTCLOUD2_LE).unwrap(),
+        two_points(false, &POINTS_LE)
+    );
+}
+
+#[test]
+fn reads_a_big_endian_pointcloud2_of_ros2() {
+    assert_eq!(
+        cdr::deserialize::<data_types::PointCloud2>(&POINTCLOUD2_BE).unwrap(),
+        two_points(true, &POINTS_BE)
+    );
+}
+
+#[test]
+fn maps_the_datatypes_of_ros2() {
+    let datatypes = [
+        (DataType::Int8, 1),
+        (DataType::Uint8, 2),
+        (DataType::Int16, 3),
+        (DataType::Uint16, 4),
+        (DataType::Int32, 5),
+        (DataType::Uint32, 6),
+        (DataType::Float32, 7),
+        (DataType::Float64, 8),
+    ];
+    for (datatype, ros2) in datatypes.iter().copied() {
+        let field = PointField {
+            datatype: datatype as i32,
+            ..Default::default()
+        };
+        let buf = cdr::serialize(&field).unwrap();
+        // The empty name, its NUL, padding to 4 bytes, then the offset.
+        assert_eq!(buf[16], ros2, "{:?}", datatype);
+        assert_eq!(cdr::deserialize::<PointField>(&buf).unwrap(), field);
+    }
+}
+
+#[test]
+fn rejects_the_datatypes_without_equivalent() {
+    // The name, the offset, the datatype and the count of a `sensor_msgs/PointField`.
+    let mut buf = cdr::ENCAPSULATION_LE.to_vec();
+    buf.extend_from_slice(&[1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1, 0, 0, 0]);
+    for (datatype, error) in [(0, -1), (9, 8)].iter().copied() {
+        buf[16] = datatype;
+        assert_eq!(
+            cdr::deserialize::<PointField>(&buf),
+            Err(CdrError::InvalidDatatype(error))
+        );
+    }
+
+    let field = PointField {
+        datatype: 8,
+        ..Default::default()
+    };
+    assert_eq!(cdr::serialize(&field), Err(CdrError::InvalidDatatype(8)));
+}
+
+#[test]
+fn terminates_the_strings_with_nul() {
+    let header = data_types::Header {
+        sec: 0,
+        nanosec: 0,
+        frame_id: String::new(),
+    };
+    let buf = cdr::serialize(&header).unwrap();
+    assert_eq!(buf[12..], [1, 0, 0, 0, 0]);
+    assert_eq!(
+        cdr::deserialize::<data_types::Header>(&buf).unwrap(),
+        header
+    );
+
+    // Some writers send an empty string as a zero length, without its NUL.
+    let empty = [0, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0];
+    assert_eq!(
+        cdr::deserialize::<data_types::Header>(&empty).unwrap(),
+        header
+    );
+}
+
+#[test]
+fn rejects_the_strings_without_nul() {
+    let string = |bytes: &[u8]| {
+        let mut buf = vec![0, 1, 0, 0, bytes.len() as u8, 0, 0, 0];
+        buf.extend_from_slice(bytes);
+        cdr::deserialize::<data_types::String>(&buf)
+    };
+    assert_eq!(
+        string(b"map\0").unwrap(),
+        data_types::String {
+            value: "map".into()
+        }
+    );
+    assert_eq!(string(b"map"), Err(CdrError::InvalidString));
+    assert_eq!(string(b"\xff\0"), Err(CdrError::InvalidString));
+}
+
+#[test]
+fn aligns_the_primitives_from_the_start_of_the_message() {
+    // The `float64` of the vector follows the 8 bytes of the stamp and the 9 of "base": it is
+    // aligned on 24, not on 20 as it would be from the start of the encapsulation header.
+    let vector3_stamped = data_types::Vector3Stamped {
+        header: Some(data_types::Header {
+            sec: 0,
+            nanosec: 0,
+            frame_id: "base".into(),
+        }),
+        vector: Some(data_types::Vector3 {
+            x: 1.0,
+            y: 0.0,
+            z: 0.0,
+        }),
+    };
+    let buf = cdr::serialize(&vector3_stamped).unwrap();
+    assert_eq!(buf.len(), 4 + 24 + 3 * 8);
+    assert_eq!(buf[4 + 24..4 + 32], 1.0f64.to_le_bytes());
+}
+
+#[test]
+fn writes_covariances_of_36_elements() {
+    let twist = |covariance: Vec<f64>| data_types::TwistWithCovariance {
+        twist: None,
+        covariance,
+    };
+    assert_eq!(
+        cdr::serialize(&twist(vec![0.0; 6])),
+        Err(CdrError::ArrayLength {
+            expected: COVARIANCE_LENGTH,
+            actual: 6
+        })
+    );
+    assert_eq!(
+        cdr::serialize(&twist(vec![])).unwrap(),
+        cdr::serialize(&twist(vec![0.0; COVARIANCE_LENGTH])).unwrap()
+    );
+}
+
+#[test]
+fn rejects_the_encapsulations_other_than_plain_cdr() {
+    // PL_CDR_BE, with parameter lists.
+    let mut buf = POINTCLOUD2_LE;
+    buf[..2].copy_from_slice(&[0x00, 0x02]);
+    assert_eq!(
+        cdr::deserialize::<data_types::PointCloud2>(&buf),
+        Err(CdrError::UnsupportedEncapsulation([0x00, 0x02]))
+    );
+}
@@ -0,0 +1,61 @@
+//
+// Copyright (c) 2022 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+//! The messages generated from a given seed, which must not change with the versions of the
+//! dependencies.
+
+use datatypes::data_types::{Float32, Header, Int64, Vector3, Vector3Stamped};
+use datatypes::datagen::DataGen;
+
+#[test]
+fn generates_the_same_messages_from_a_seed() {
+    let mut datagen = DataGen::from_seed(0);
+
+    assert_eq!(
+        datagen.gen::<Header>(),
+        Header {
+            sec: 0,
+            nanosec: 0,
+            frame_id: "ptudgsjDM4lja107".to_string(),
+        }
+    );
+    assert_eq!(
+        datagen.gen::<Vector3Stamped>(),
+        Vector3Stamped {
+            header: None,
+            vector: Some(Vector3 {
+                x: 0.1542912742358563,
+                y: 0.8807117723618908,
+                z: 0.7705537646353312,
+            }),
+        }
+    );
+    assert_eq!(
+        datagen.gen::<Int64>(),
+        Int64 {
+            value: -8779635384032477021
+        }
+    );
+    assert_eq!(datagen.gen::<Float32>(), Float32 { value: 0.6076455 });
+    assert_eq!(datagen.string(12), "WDwkpDMbpmk4");
+}
+
+#[test]
+fn forks_generators_independent_of_their_parent() {
+    let mut datagen = DataGen::from_seed(0);
+    let mut fork = datagen.fork();
+
+    assert_eq!(fork.string(12), DataGen::from_seed(0).fork().string(12));
+    assert_ne!(fork.string(12), datagen.string(12));
+}
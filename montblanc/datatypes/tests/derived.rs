@@ -0,0 +1,63 @@
+//
+// Copyright (c) 2022 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+//! The scans computed from images are valid, whatever the size of the image.
+
+use datatypes::data_types;
+use datatypes::derived;
+use datatypes::encodings;
+use datatypes::image::ImageError;
+use datatypes::validation::Validate;
+
+fn mono8(width: u32, height: u32) -> data_types::Image {
+    data_types::Image {
+        header: Some(data_types::Header {
+            sec: 1,
+            nanosec: 2,
+            frame_id: "camera".to_string(),
+        }),
+        height,
+        width,
+        encoding: encodings::MONO8.to_string(),
+        is_bigendian: false,
+        step: width,
+        data: (0..width * height).map(|i| i as u8).collect(),
+    }
+}
+
+#[test]
+fn scans_the_middle_row_of_images() {
+    for (width, height) in [(1, 1), (2, 1), (3, 3), (640, 480)].iter().copied() {
+        let image = mono8(width, height);
+        let scan = derived::scan(&image).unwrap();
+        assert_eq!(scan.validate(), Ok(()), "{}x{}", width, height);
+        assert_eq!(scan.header, image.header);
+        assert_eq!(scan.ranges.len(), width as usize);
+        assert_eq!(scan.intensities[0], (height / 2 * width) as u8 as f32);
+        assert_eq!(derived::scan_cloud(&scan).validate(), Ok(()));
+    }
+}
+
+#[test]
+fn rejects_images_without_pixels() {
+    for (width, height) in [(0, 0), (0, 3), (3, 0)].iter().copied() {
+        assert_eq!(
+            derived::scan(&mono8(width, height)),
+            Err(ImageError::Empty),
+            "{}x{}",
+            width,
+            height
+        );
+    }
+}
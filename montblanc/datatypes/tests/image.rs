@@ -0,0 +1,180 @@
+//
+// Copyright (c) 2022 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+//! The pixels read, converted, cropped and resized by `image`, whatever the channel order, the
+//! endianness and the padding of the rows.
+
+use datatypes::data_types;
+use datatypes::encodings;
+use datatypes::image::{self, Bgr8, ImageError, ImageView, Mono16, Rgb8};
+use datatypes::validation::Validate;
+
+fn image(encoding: &str, width: u32, height: u32, step: u32, data: &[u8]) -> data_types::Image {
+    data_types::Image {
+        header: None,
+        height,
+        width,
+        encoding: encoding.to_string(),
+        is_bigendian: false,
+        step,
+        data: data.to_vec(),
+    }
+}
+
+/// A 2x2 `rgb8` image whose rows are padded to 8 bytes with `0xee`.
+fn padded() -> data_types::Image {
+    #[rustfmt::skip]
+    let data = [
+        1, 2, 3, 4, 5, 6, 0xee, 0xee,
+        7, 8, 9, 10, 11, 12, 0xee, 0xee,
+    ];
+    image(encodings::RGB8, 2, 2, 8, &data)
+}
+
+#[test]
+fn reads_bgr8_in_reverse_order() {
+    let bgr = image(encodings::BGR8, 1, 1, 3, &[1, 2, 3]);
+    assert_eq!(
+        ImageView::<Bgr8>::new(&bgr).unwrap().get(0, 0),
+        Bgr8([1, 2, 3])
+    );
+
+    let rgb = image::convert(&bgr, encodings::RGB8).unwrap();
+    assert_eq!(rgb.data, [3, 2, 1]);
+    assert_eq!(image::convert(&rgb, encodings::BGR8).unwrap(), bgr);
+
+    // Blue is darker than red.
+    let blue = image::convert(
+        &image(encodings::BGR8, 1, 1, 3, &[255, 0, 0]),
+        encodings::MONO8,
+    );
+    let red = image::convert(
+        &image(encodings::BGR8, 1, 1, 3, &[0, 0, 255]),
+        encodings::MONO8,
+    );
+    assert_eq!(blue.unwrap().data, [29]);
+    assert_eq!(red.unwrap().data, [76]);
+}
+
+#[test]
+fn reads_mono16_in_its_endianness() {
+    fn pixel(image: &data_types::Image) -> Mono16 {
+        ImageView::<Mono16>::new(image).unwrap().get(0, 0)
+    }
+
+    let mut mono16 = image(encodings::MONO16, 1, 1, 2, &[0x12, 0x34]);
+    assert_eq!(pixel(&mono16), Mono16(0x3412));
+    mono16.is_bigendian = true;
+    assert_eq!(pixel(&mono16), Mono16(0x1234));
+
+    // The converted images are little-endian.
+    let converted = image::convert(&mono16, encodings::MONO16).unwrap();
+    assert!(!converted.is_bigendian);
+    assert_eq!(converted.data, [0x34, 0x12]);
+    let mono8 = image::convert(&mono16, encodings::MONO8).unwrap();
+    assert_eq!(mono8.data, [0x12]);
+
+    // Cropping and resizing keep the bytes, and so the endianness.
+    let cropped = image::crop(&mono16, 0, 0, 1, 1).unwrap();
+    assert!(cropped.is_bigendian);
+    assert_eq!(cropped.data, [0x12, 0x34]);
+    let resized = image::resize(&mono16, 2, 1).unwrap();
+    assert!(resized.is_bigendian);
+    assert_eq!(resized.data, [0x12, 0x34, 0x12, 0x34]);
+}
+
+#[test]
+fn skips_the_padding_of_the_rows() {
+    let padded = padded();
+    let view = ImageView::<Rgb8>::new(&padded).unwrap();
+    assert_eq!(view.row(1), [7, 8, 9, 10, 11, 12]);
+    assert_eq!(
+        view.pixels().collect::<Vec<_>>(),
+        [
+            Rgb8([1, 2, 3]),
+            Rgb8([4, 5, 6]),
+            Rgb8([7, 8, 9]),
+            Rgb8([10, 11, 12])
+        ]
+    );
+
+    let converted = image::convert(&padded, encodings::BGR8).unwrap();
+    assert_eq!(converted.step, 6);
+    assert_eq!(converted.data, [3, 2, 1, 6, 5, 4, 9, 8, 7, 12, 11, 10]);
+
+    let cropped = image::crop(&padded, 1, 0, 1, 2).unwrap();
+    assert_eq!(cropped.step, 3);
+    assert_eq!(cropped.data, [4, 5, 6, 10, 11, 12]);
+
+    let resized = image::resize(&padded, 4, 1).unwrap();
+    assert_eq!(resized.step, 12);
+    assert_eq!(resized.data, [1, 2, 3, 1, 2, 3, 4, 5, 6, 4, 5, 6]);
+}
+
+#[test]
+fn resizes_to_and_from_zero_pixels() {
+    for (width, height) in [(0, 0), (0, 2), (2, 0)].iter().copied() {
+        let resized = image::resize(&padded(), width, height).unwrap();
+        assert_eq!((resized.width, resized.height), (width, height));
+        assert_eq!(resized.step, width * 3);
+        assert!(resized.data.is_empty());
+        assert_eq!(resized.validate(), Ok(()));
+    }
+
+    // Nothing to sample from: the image is black.
+    let empty = image(encodings::RGB8, 0, 0, 0, &[]);
+    let resized = image::resize(&empty, 2, 1).unwrap();
+    assert_eq!(resized.data, [0; 6]);
+    assert_eq!(resized.validate(), Ok(()));
+}
+
+#[test]
+fn rejects_regions_out_of_the_image() {
+    assert_eq!(
+        image::crop(&padded(), 1, 1, 2, 1),
+        Err(ImageError::OutOfBounds {
+            x: 1,
+            y: 1,
+            width: 2,
+            height: 1
+        })
+    );
+    assert_eq!(
+        image::crop(&padded(), u32::MAX, 0, 2, 1),
+        Err(ImageError::OutOfBounds {
+            x: u32::MAX,
+            y: 0,
+            width: 2,
+            height: 1
+        })
+    );
+}
+
+#[test]
+fn rejects_the_encodings_it_cannot_convert() {
+    assert_eq!(
+        image::convert(&padded(), "yuv422"),
+        Err(ImageError::UnsupportedEncoding("yuv422".to_string()))
+    );
+    let rgb16 = image(encodings::RGB16, 1, 1, 6, &[0; 6]);
+    assert_eq!(
+        image::convert(&rgb16, encodings::RGB8),
+        Err(ImageError::UnsupportedEncoding(
+            encodings::RGB16.to_string()
+        ))
+    );
+    // Cropping and resizing only move pixels: any known encoding is supported.
+    assert!(image::crop(&rgb16, 0, 0, 1, 1).is_ok());
+    assert!(image::resize(&rgb16, 2, 2).is_ok());
+}
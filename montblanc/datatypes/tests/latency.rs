@@ -0,0 +1,117 @@
+//
+// Copyright (c) 2022 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+//! The quantiles of the latency histograms, and their report.
+
+use datatypes::latency::{self, Histogram, LatencyRecorder};
+use std::time::Duration;
+
+fn us(micros: u64) -> Duration {
+    Duration::from_micros(micros)
+}
+
+fn histogram(values: impl IntoIterator<Item = u64>) -> Histogram {
+    let mut histogram = Histogram::default();
+    values
+        .into_iter()
+        .for_each(|value| histogram.record(us(value)));
+    histogram
+}
+
+#[test]
+fn records_the_values_in_buckets_of_bounded_width() {
+    // Below 16µs, each value has its own bucket.
+    for value in 0..16 {
+        assert_eq!(histogram(vec![value, 100]).quantile(0.5), us(value));
+    }
+    // From 16µs to 31µs as well.
+    assert_eq!(histogram(vec![31, 100]).quantile(0.5), us(31));
+    // Then the width of the buckets doubles with each power of two: [32, 33], [34, 35]...
+    assert_eq!(histogram(vec![32, 100]).quantile(0.5), us(33));
+    assert_eq!(histogram(vec![33, 100]).quantile(0.5), us(33));
+    assert_eq!(histogram(vec![34, 100]).quantile(0.5), us(35));
+    // [992, 1023], which is within 1/16 of its values.
+    assert_eq!(histogram(vec![992, 2000]).quantile(0.5), us(1023));
+    assert_eq!(histogram(vec![1023, 2000]).quantile(0.5), us(1023));
+    assert_eq!(histogram(vec![1024, 2000]).quantile(0.5), us(1087));
+
+    // A quantile is never above the maximum.
+    assert_eq!(histogram(vec![32]).quantile(0.5), us(32));
+    assert_eq!(histogram(vec![1000]).quantile(1.0), us(1000));
+}
+
+#[test]
+fn computes_the_quantiles_of_known_samples() {
+    let histogram = histogram(1..=100);
+    assert_eq!(histogram.count(), 100);
+    assert_eq!(histogram.max(), us(100));
+    // The 50th value, 50µs, is in the bucket [50, 51].
+    assert_eq!(histogram.quantile(0.5), us(51));
+    // The 99th value, 99µs, is in the bucket [96, 99].
+    assert_eq!(histogram.quantile(0.99), us(99));
+    assert_eq!(histogram.quantile(1.0), us(100));
+    // The quantiles are clamped between 0 and 1.
+    assert_eq!(histogram.quantile(0.0), us(1));
+    assert_eq!(histogram.quantile(-1.0), us(1));
+    assert_eq!(histogram.quantile(2.0), us(100));
+
+    // Latencies are recorded in microseconds, rounded down.
+    let mut histogram = Histogram::default();
+    histogram.record(Duration::from_nanos(1999));
+    assert_eq!(histogram.max(), us(1));
+    histogram.record(Duration::from_secs(10));
+    assert_eq!(histogram.max(), Duration::from_secs(10));
+    assert_eq!(histogram.quantile(0.5), us(1));
+}
+
+#[test]
+fn reports_zero_for_an_empty_histogram() {
+    let histogram = Histogram::default();
+    assert_eq!(histogram.count(), 0);
+    assert_eq!(histogram.max(), Duration::ZERO);
+    assert_eq!(histogram.quantile(0.5), Duration::ZERO);
+    assert_eq!(histogram.quantile(0.99), Duration::ZERO);
+}
+
+#[test]
+fn reports_a_line_per_path_sorted_by_name() {
+    let mut recorder = LatencyRecorder::default();
+    assert_eq!(recorder.report(), "path\tcount\tp50_us\tp99_us\tmax_us\n");
+
+    for value in 1..=100 {
+        recorder.record("b:a", us(value));
+    }
+    recorder.record("a", us(7));
+    assert_eq!(recorder.histogram("a").map(Histogram::count), Some(1));
+    assert!(recorder.histogram("c").is_none());
+    assert_eq!(
+        recorder.report(),
+        "path\tcount\tp50_us\tp99_us\tmax_us\n\
+         a\t1\t7\t7\t7\n\
+         b:a\t100\t51\t99\t100\n"
+    );
+}
+
+#[test]
+fn measures_the_time_elapsed_since_a_timestamp() {
+    let timestamp = latency::now();
+    assert!(latency::elapsed_since(timestamp) < Duration::from_secs(1));
+    // The seconds are in the 32 high bits.
+    assert!(latency::elapsed_since(timestamp - (5 << 32)) >= Duration::from_secs(5));
+    // A timestamp in the future.
+    assert_eq!(
+        latency::elapsed_since(timestamp + (5 << 32)),
+        Duration::ZERO
+    );
+}
@@ -0,0 +1,53 @@
+//
+// Copyright (c) 2022 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+//! The payloads generated are intact, and no longer once altered.
+
+use datatypes::datagen::DataGen;
+use datatypes::payload::{self, CHECKSUM_LEN};
+
+#[test]
+fn generates_intact_payloads_of_the_length_asked() {
+    let mut datagen = DataGen::from_seed(0);
+    for length in [0, CHECKSUM_LEN - 1, CHECKSUM_LEN, 64].iter().copied() {
+        let generated = payload::generate(&mut datagen, length);
+        assert_eq!(generated.len(), length.max(CHECKSUM_LEN));
+        assert!(payload::is_intact(&generated), "{:?}", generated);
+    }
+}
+
+#[test]
+fn rejects_altered_payloads() {
+    let mut datagen = DataGen::from_seed(0);
+    let generated = payload::generate(&mut datagen, 64);
+    let other = payload::generate(&mut datagen, 64);
+
+    let mut altered = generated.clone().into_bytes();
+    altered[0] = if altered[0] == b'a' { b'b' } else { b'a' };
+    let altered = String::from_utf8(altered).unwrap();
+
+    let cases = [
+        ("altered", altered),
+        ("truncated", generated[1..].to_string()),
+        ("extended", format!("{}a", generated)),
+        ("mixed", format!("{}{}", &generated[..32], &other[32..])),
+        ("checksum only", generated[56..].to_string()),
+        ("too short", generated[..CHECKSUM_LEN - 1].to_string()),
+        ("not ascii", format!("é{}", &generated[2..])),
+        ("empty", String::new()),
+    ];
+    for (case, payload) in cases.iter() {
+        assert!(!payload::is_intact(payload), "{}: {:?}", case, payload);
+    }
+}
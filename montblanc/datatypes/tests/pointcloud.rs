@@ -0,0 +1,325 @@
+//
+// Copyright (c) 2022 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+//! The points written by `CloudBuilder` and read by `CloudView`, whatever the datatype, the byte
+//! order and the number of values of their fields.
+
+use datatypes::data_types::point_cloud2::point_field::DataType;
+use datatypes::data_types::point_cloud2::PointField;
+use datatypes::data_types::{Header, PointCloud2};
+use datatypes::pointcloud::{
+    CloudBuilder, CloudView, Point, PointReader, PointWriter, PointXYZ, PointXYZI,
+};
+use datatypes::validation::Validate;
+
+fn points(len: usize) -> Vec<PointXYZI> {
+    (0..len)
+        .map(|i| PointXYZI {
+            x: i as f32,
+            y: -(i as f32),
+            z: 0.5,
+            intensity: 100.0 + i as f32,
+        })
+        .collect()
+}
+
+/// A point with a field of every `DataType`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct AllTypes {
+    int8: i8,
+    uint8: u8,
+    int16: i16,
+    uint16: u16,
+    int32: i32,
+    uint32: u32,
+    float32: f32,
+    float64: f64,
+}
+
+impl Point for AllTypes {
+    const FIELDS: &'static [(&'static str, DataType)] = &[
+        ("int8", DataType::Int8),
+        ("uint8", DataType::Uint8),
+        ("int16", DataType::Int16),
+        ("uint16", DataType::Uint16),
+        ("int32", DataType::Int32),
+        ("uint32", DataType::Uint32),
+        ("float32", DataType::Float32),
+        ("float64", DataType::Float64),
+    ];
+
+    fn read(reader: &mut PointReader<'_>) -> Self {
+        Self {
+            int8: reader.read(),
+            uint8: reader.read(),
+            int16: reader.read(),
+            uint16: reader.read(),
+            int32: reader.read(),
+            uint32: reader.read(),
+            float32: reader.read(),
+            float64: reader.read(),
+        }
+    }
+
+    fn write(&self, writer: &mut PointWriter<'_>) {
+        writer.put(self.int8);
+        writer.put(self.uint8);
+        writer.put(self.int16);
+        writer.put(self.uint16);
+        writer.put(self.int32);
+        writer.put(self.uint32);
+        writer.put(self.float32);
+        writer.put(self.float64);
+    }
+}
+
+static ALL_TYPES: AllTypes = AllTypes {
+    int8: -2,
+    uint8: 0xfe,
+    int16: -0x1234,
+    uint16: 0xfedc,
+    int32: -0x1234_5678,
+    uint32: 0xfedc_ba98,
+    float32: 1.5,
+    float64: -0.25,
+};
+
+/// The bytes of `ALL_TYPES`, little-endian.
+#[rustfmt::skip]
+static ALL_TYPES_LE: [u8; 26] = [
+    0xfe,
+    0xfe,
+    0xcc, 0xed,
+    0xdc, 0xfe,
+    0x88, 0xa9, 0xcb, 0xed,
+    0x98, 0xba, 0xdc, 0xfe,
+    0x00, 0x00, 0xc0, 0x3f,
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0xd0, 0xbf,
+];
+
+#[test]
+fn reads_the_points_it_built() {
+    let header = Header {
+        sec: 1,
+        nanosec: 2,
+        frame_id: "lidar".to_string(),
+    };
+    let cloud = CloudBuilder::<PointXYZI>::new()
+        .header(header.clone())
+        .push(points(1)[0])
+        .extend(points(3).into_iter().skip(1))
+        .build();
+
+    assert_eq!(cloud.validate(), Ok(()));
+    assert_eq!(cloud.header, Some(header));
+    assert_eq!((cloud.width, cloud.height), (3, 1));
+    assert_eq!((cloud.point_step, cloud.row_step), (16, 48));
+    assert!(!cloud.is_bigendian);
+
+    let view = CloudView::new(&cloud).unwrap();
+    assert_eq!(view.len(), 3);
+    assert_eq!(
+        view.read::<PointXYZI>().unwrap().collect::<Vec<_>>(),
+        points(3)
+    );
+    // A point with fewer fields can be read from the same cloud.
+    assert_eq!(
+        view.read::<PointXYZ>().unwrap().nth(2),
+        Some(PointXYZ {
+            x: 2.0,
+            y: -2.0,
+            z: 0.5
+        })
+    );
+    assert!(view.point(3).is_none());
+
+    let empty = CloudBuilder::<PointXYZI>::new().build();
+    assert_eq!(empty.validate(), Ok(()));
+    assert_eq!((empty.width, empty.height), (0, 0));
+    assert!(CloudView::new(&empty).unwrap().is_empty());
+}
+
+#[test]
+fn reads_and_writes_every_datatype() {
+    let cloud = CloudBuilder::<AllTypes>::new().push(ALL_TYPES).build();
+    assert_eq!(cloud.validate(), Ok(()));
+    assert_eq!(cloud.data, ALL_TYPES_LE);
+
+    let offsets = cloud
+        .fields
+        .iter()
+        .map(|field| (field.name.as_str(), field.offset, field.count))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        offsets,
+        [
+            ("int8", 0, 1),
+            ("uint8", 1, 1),
+            ("int16", 2, 1),
+            ("uint16", 4, 1),
+            ("int32", 6, 1),
+            ("uint32", 10, 1),
+            ("float32", 14, 1),
+            ("float64", 18, 1),
+        ]
+    );
+
+    let view = CloudView::new(&cloud).unwrap();
+    assert_eq!(view.read::<AllTypes>().unwrap().next(), Some(ALL_TYPES));
+    for (name, datatype) in AllTypes::FIELDS.iter() {
+        assert_eq!(view.field(name).unwrap().datatype(), *datatype);
+    }
+
+    // Values are converted as with `as`.
+    assert_eq!(view.values::<f64>("uint16").unwrap().next(), Some(65244.0));
+    assert_eq!(view.values::<i8>("float32").unwrap().next(), Some(1));
+    assert_eq!(view.values::<u8>("int32").unwrap().next(), Some(0));
+}
+
+#[test]
+fn reads_and_writes_big_endian_clouds() {
+    let cloud = CloudBuilder::<AllTypes>::new()
+        .big_endian(true)
+        .push(ALL_TYPES)
+        .build();
+    assert!(cloud.is_bigendian);
+
+    let mut expected = Vec::<u8>::new();
+    let mut offset = 0;
+    for (_, datatype) in AllTypes::FIELDS.iter() {
+        let size = datatypes::pointcloud::datatype_size(*datatype);
+        expected.extend(ALL_TYPES_LE[offset..offset + size].iter().rev());
+        offset += size;
+    }
+    assert_eq!(cloud.data, expected);
+
+    let view = CloudView::new(&cloud).unwrap();
+    assert_eq!(view.read::<AllTypes>().unwrap().next(), Some(ALL_TYPES));
+
+    // The same bytes, read as little-endian, are other values.
+    let swapped = PointCloud2 {
+        is_bigendian: false,
+        ..cloud.clone()
+    };
+    let view = CloudView::new(&swapped).unwrap();
+    assert_eq!(view.values::<i16>("int16").unwrap().next(), Some(-0x3313));
+}
+
+#[test]
+fn reads_every_value_of_the_fields_of_several_values() {
+    // `rgb` holds 3 values per point, followed by `x`.
+    let cloud = PointCloud2 {
+        header: None,
+        height: 1,
+        width: 2,
+        fields: vec![
+            PointField {
+                name: "rgb".to_string(),
+                offset: 0,
+                datatype: DataType::Uint8 as i32,
+                count: 3,
+            },
+            PointField {
+                name: "x".to_string(),
+                offset: 4,
+                datatype: DataType::Float32 as i32,
+                count: 1,
+            },
+        ],
+        is_bigendian: false,
+        point_step: 8,
+        row_step: 16,
+        #[rustfmt::skip]
+        data: vec![
+            1, 2, 3, 0xee, 0, 0, 0x80, 0x3f,
+            4, 5, 6, 0xee, 0, 0, 0x00, 0x40,
+        ],
+        is_dense: true,
+    };
+
+    let view = CloudView::new(&cloud).unwrap();
+    let rgb = view.field("rgb").unwrap();
+    assert_eq!(rgb.count(), 3);
+    let colors = view
+        .points()
+        .map(|point| {
+            (0..4)
+                .map(|index| point.element::<u8>(&rgb, index))
+                .collect::<Vec<_>>()
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(
+        colors,
+        [
+            [Some(1), Some(2), Some(3), None],
+            [Some(4), Some(5), Some(6), None]
+        ]
+    );
+    // The values of a field are its first ones.
+    assert_eq!(
+        view.values::<u8>("rgb").unwrap().collect::<Vec<_>>(),
+        [1, 4]
+    );
+    assert_eq!(
+        view.values::<f32>("x").unwrap().collect::<Vec<_>>(),
+        [1.0, 2.0]
+    );
+}
+
+#[test]
+fn finds_no_unknown_fields() {
+    let cloud = CloudBuilder::<PointXYZ>::new()
+        .extend(vec![PointXYZ::default(); 2])
+        .build();
+    let view = CloudView::new(&cloud).unwrap();
+
+    assert!(view.field("intensity").is_none());
+    assert!(view.values::<f32>("intensity").is_none());
+    assert!(view.read::<PointXYZI>().is_none());
+    // Field names are case sensitive.
+    assert!(view.field("X").is_none());
+}
+
+#[test]
+fn organizes_the_points_in_rows_of_equal_length() {
+    let organized = CloudBuilder::<PointXYZI>::new()
+        .extend(points(6))
+        .build_organized(2)
+        .unwrap();
+    assert_eq!(organized.validate(), Ok(()));
+    assert_eq!((organized.width, organized.height), (3, 2));
+    assert_eq!(organized.row_step, 3 * organized.point_step);
+    let view = CloudView::new(&organized).unwrap();
+    assert_eq!(
+        view.read::<PointXYZI>().unwrap().collect::<Vec<_>>(),
+        points(6)
+    );
+
+    let builder = CloudBuilder::<PointXYZI>::new().extend(points(5));
+    for height in [0, 2, 3, 4, 6].iter().copied() {
+        assert!(
+            builder.clone().build_organized(height).is_none(),
+            "5 points in {} rows",
+            height
+        );
+    }
+    for height in [1, 5].iter().copied() {
+        let cloud = builder.clone().build_organized(height).unwrap();
+        assert_eq!(cloud.validate(), Ok(()), "5 points in {} rows", height);
+    }
+
+    let empty = CloudBuilder::<PointXYZI>::new().build_organized(0).unwrap();
+    assert_eq!((empty.width, empty.height), (0, 0));
+    assert_eq!(empty.validate(), Ok(()));
+}
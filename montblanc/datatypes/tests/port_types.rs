@@ -0,0 +1,45 @@
+//
+// Copyright (c) 2022 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+use datatypes::data_types::{Float32, Pose, String};
+use datatypes::{check_port_type, Named, AMAZON_PORT, MESSAGES, PORT_TYPES, TAGUS_PORT};
+
+#[test]
+fn accepts_the_declared_message() {
+    assert!(check_port_type::<Float32>(AMAZON_PORT).is_ok());
+    assert!(check_port_type::<Pose>(TAGUS_PORT).is_ok());
+}
+
+#[test]
+fn rejects_another_message() {
+    let error = check_port_type::<Pose>(AMAZON_PORT).unwrap_err();
+    assert!(
+        error.to_string().contains("`Float32` messages, not `Pose`"),
+        "{}",
+        error
+    );
+}
+
+#[test]
+fn accepts_any_message_on_unknown_ports() {
+    assert!(check_port_type::<String>("Input").is_ok());
+}
+
+#[test]
+fn names_the_messages_as_the_registry() {
+    assert_eq!(<Pose as Named>::NAME, "Pose");
+    for (port, message) in PORT_TYPES {
+        assert!(MESSAGES.contains(message), "{} exchanges {}", port, message);
+    }
+}
@@ -0,0 +1,97 @@
+//
+// Copyright (c) 2022 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+//! The publications of the sources, as set by their configuration.
+
+use datatypes::publication::Publication;
+use datatypes::Configurable;
+use serde_json::{json, Value};
+use zenoh_flow::prelude::{Configuration, Result};
+
+fn configure(publication: Publication, configuration: Value) -> Result<Publication> {
+    publication.configure(Some(&Configuration::from(configuration)))
+}
+
+/// The payload and burst sizes of `publication`.
+fn sizes(publication: Result<Publication>) -> Option<(usize, usize)> {
+    publication
+        .ok()
+        .map(|publication| (publication.payload_bytes, publication.burst_size))
+}
+
+#[test]
+fn publishes_a_single_message_without_payload_by_default() {
+    for publication in [Publication::default(), Publication::without_payload()].iter() {
+        assert_eq!((publication.payload_bytes, publication.burst_size), (0, 1));
+        assert_eq!(publication.configure(None).ok(), Some(*publication));
+        assert_eq!(configure(*publication, json!({})).ok(), Some(*publication));
+        assert_eq!(
+            configure(*publication, json!({ "rate_hz": 5 })).ok(),
+            Some(*publication)
+        );
+    }
+}
+
+#[test]
+fn reads_the_payload_and_burst_sizes() {
+    let cases = [
+        (json!({ "payload_bytes": 1024 }), (1024, 1)),
+        (json!({ "payload_bytes": 0 }), (0, 1)),
+        (json!({ "burst_size": 8 }), (0, 8)),
+        (
+            json!({ "payload_bytes": 6220800, "burst_size": 2 }),
+            (6220800, 2),
+        ),
+    ];
+    for (configuration, expected) in cases.iter() {
+        assert_eq!(
+            sizes(configure(Publication::default(), configuration.clone())),
+            Some(*expected),
+            "{}",
+            configuration
+        );
+    }
+}
+
+#[test]
+fn rejects_invalid_sizes() {
+    let cases = [
+        json!({ "burst_size": 0 }),
+        json!({ "burst_size": -1 }),
+        json!({ "burst_size": 1.5 }),
+        json!({ "burst_size": "8" }),
+        json!({ "payload_bytes": -1 }),
+        json!({ "payload_bytes": "1KiB" }),
+        json!({ "payload_bytes": null }),
+    ];
+    for configuration in cases.iter() {
+        assert!(
+            configure(Publication::default(), configuration.clone()).is_err(),
+            "{}",
+            configuration
+        );
+    }
+}
+
+#[test]
+fn rejects_a_payload_for_the_sources_without_one() {
+    let publication = Publication::without_payload();
+    assert!(configure(publication, json!({ "payload_bytes": 1024 })).is_err());
+    // Even an empty one: the source would silently ignore it.
+    assert!(configure(publication, json!({ "payload_bytes": 0 })).is_err());
+    assert_eq!(
+        sizes(configure(publication, json!({ "burst_size": 4 }))),
+        Some((0, 4))
+    );
+}
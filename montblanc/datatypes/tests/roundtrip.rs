@@ -0,0 +1,126 @@
+//
+// Copyright (c) 2022 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+//! Every message of `data_types` is decoded as it was encoded, and decoding a truncated or
+//! corrupted buffer returns an error rather than panicking.
+
+mod strategies;
+
+use datatypes::data_types;
+use proptest::collection::vec;
+use proptest::prelude::*;
+use proptest::sample::Index;
+use prost::Message;
+use strategies::*;
+
+/// Generates, for each message, the tests of its `serialize` and `deserialize` functions.
+macro_rules! roundtrip {
+    ($($name:ident: $strategy:expr, $serialize:expr, $deserialize:expr;)*) => {
+        $(
+            mod $name {
+                use super::*;
+
+                proptest! {
+                    #[test]
+                    fn decodes_what_was_encoded(message in $strategy) {
+                        let buf = $serialize(&message);
+                        prop_assert_eq!(buf.len(), message.encoded_len());
+                        prop_assert_eq!($deserialize(&buf).unwrap(), message);
+                    }
+
+                    #[test]
+                    fn rejects_truncated_buffers(message in $strategy, end in any::<Index>()) {
+                        let buf = $serialize(&message);
+                        // The last field of a message is incomplete without its last byte...
+                        if !buf.is_empty() {
+                            prop_assert!($deserialize(&buf[..buf.len() - 1]).is_err());
+                        }
+                        // ... but the buffer may end on a field of a shorter message.
+                        let _ = $deserialize(&buf[..end.index(buf.len() + 1)]);
+                    }
+
+                    #[test]
+                    fn does_not_panic_on_corrupted_buffers(
+                        message in $strategy,
+                        corruptions in vec((any::<Index>(), any::<u8>()), 1..8),
+                    ) {
+                        let mut buf = $serialize(&message);
+                        if !buf.is_empty() {
+                            for (index, byte) in corruptions {
+                                let index = index.index(buf.len());
+                                buf[index] = byte;
+                            }
+                        }
+                        let _ = $deserialize(&buf);
+                    }
+
+                    #[test]
+                    fn does_not_panic_on_random_buffers(buf in vec(any::<u8>(), 0..256)) {
+                        let _ = $deserialize(&buf);
+                    }
+                }
+            }
+        )*
+    };
+}
+
+/// For the messages without their own `serialize_*` and `deserialize_*` functions.
+fn encode<T: Message>(message: &T) -> Vec<u8> {
+    message.encode_to_vec()
+}
+
+fn decode<T: Message + Default>(buf: &[u8]) -> Result<T, prost::DecodeError> {
+    T::decode(buf)
+}
+
+roundtrip! {
+    timestamp: timestamp(), encode, decode::<data_types::Timestamp>;
+    header: header(), encode, decode::<data_types::Header>;
+    point: point(), datatypes::serialize_point, datatypes::deserialize_point;
+    quaternion: quaternion(), datatypes::serialize_quaternion, datatypes::deserialize_quaternion;
+    vector3: vector3(), datatypes::serialize_vector3, datatypes::deserialize_vector3;
+    vector3_stamped:
+        vector3_stamped(),
+        datatypes::serialize_vector3_stamped,
+        datatypes::deserialize_vector3_stamped;
+    pose: pose(), datatypes::serialize_pose, datatypes::deserialize_pose;
+    twist: twist(), datatypes::serialize_twist, datatypes::deserialize_twist;
+    twist_with_covariance:
+        twist_with_covariance(),
+        datatypes::serialize_twist_with_covariance,
+        datatypes::deserialize_twist_with_covariance;
+    twist_with_covariance_stamped:
+        twist_with_covariance_stamped(),
+        datatypes::serialize_twist_with_covariance_stamped,
+        datatypes::deserialize_twist_with_covariance_stamped;
+    wrench: wrench(), datatypes::serialize_wrench, datatypes::deserialize_wrench;
+    wrench_stamped:
+        wrench_stamped(),
+        datatypes::serialize_wrench_stamped,
+        datatypes::deserialize_wrench_stamped;
+    image: image(), datatypes::serialize_image, datatypes::deserialize_image;
+    point_field: point_field(), encode, decode::<data_types::point_cloud2::PointField>;
+    pointcloud2: pointcloud2(), datatypes::serialize_pointcloud2, datatypes::deserialize_pointcloud2;
+    laserscan: laserscan(), datatypes::serialize_laserscan, datatypes::deserialize_laserscan;
+    float32: float().prop_map(|value| data_types::Float32 { value }), encode,
+        decode::<data_types::Float32>;
+    float64: double().prop_map(|value| data_types::Float64 { value }), encode,
+        decode::<data_types::Float64>;
+    int32: any::<i32>().prop_map(|value| data_types::Int32 { value }), encode,
+        decode::<data_types::Int32>;
+    int64: any::<i64>().prop_map(|value| data_types::Int64 { value }), encode,
+        decode::<data_types::Int64>;
+    string: ".{0,64}".prop_map(|value| data_types::String { value }), encode,
+        decode::<data_types::String>;
+}
@@ -0,0 +1,156 @@
+//
+// Copyright (c) 2022 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+//! The messages of the sensor models are valid, whatever their settings and the room measured.
+
+use datatypes::data_types::Header;
+use datatypes::encodings;
+use datatypes::pointcloud::{CloudView, PointXYZI};
+use datatypes::sensors::{Camera, Encoding, Lidar2d, Lidar3d};
+use datatypes::validation::Validate;
+use rand::SeedableRng;
+use rand_chacha::ChaCha8Rng;
+
+/// The number of rooms measured by every sensor.
+const ROOMS: u64 = 20;
+
+fn header() -> Option<Header> {
+    Some(Header {
+        sec: 1,
+        nanosec: 2,
+        frame_id: "sensor".to_string(),
+    })
+}
+
+#[test]
+fn scans_valid_laser_scans() {
+    let lidars = [
+        Lidar2d::default(),
+        Lidar2d {
+            beams: 360,
+            range_min: 0.5,
+            range_max: 5.0,
+            scan_time: 0.05,
+        },
+        Lidar2d {
+            beams: 1,
+            ..Lidar2d::default()
+        },
+    ];
+    for lidar in lidars.iter() {
+        for seed in 0..ROOMS {
+            let scan = lidar.scan(header(), &mut ChaCha8Rng::seed_from_u64(seed));
+            assert_eq!(scan.validate(), Ok(()), "{:?}, room {}", lidar, seed);
+            assert!(scan.angle_min <= scan.angle_max);
+            assert_eq!(scan.ranges.len(), lidar.beams as usize);
+            assert_eq!(scan.intensities.len(), lidar.beams as usize);
+            assert!(scan
+                .ranges
+                .iter()
+                .all(|range| (lidar.range_min..=lidar.range_max).contains(range)));
+            assert_eq!(scan.header, header());
+        }
+    }
+}
+
+#[test]
+fn scans_valid_point_clouds() {
+    let lidars = [
+        Lidar3d::default(),
+        Lidar3d {
+            lasers: 1,
+            columns: 8,
+            ..Lidar3d::default()
+        },
+        Lidar3d {
+            lasers: 4,
+            columns: 3,
+            range_max: 2.0,
+            ..Lidar3d::default()
+        },
+    ];
+    for lidar in lidars.iter() {
+        for seed in 0..ROOMS {
+            let cloud = lidar.scan(header(), &mut ChaCha8Rng::seed_from_u64(seed));
+            assert_eq!(cloud.validate(), Ok(()), "{:?}, room {}", lidar, seed);
+            assert_eq!((cloud.width, cloud.height), (lidar.columns, lidar.lasers));
+
+            let view = CloudView::new(&cloud).unwrap();
+            for point in view.read::<PointXYZI>().unwrap() {
+                let range = (point.x * point.x + point.y * point.y + point.z * point.z).sqrt();
+                assert!(range <= lidar.range_max * (1.0 + 1e-5), "{:?}", point);
+                assert!(range >= lidar.range_min * (1.0 - 1e-5), "{:?}", point);
+            }
+        }
+    }
+}
+
+#[test]
+fn captures_valid_images() {
+    let cameras = [
+        Camera::default(),
+        Camera {
+            width: 7,
+            height: 3,
+            encoding: Encoding::Mono8,
+        },
+        Camera::default().with_payload(1000),
+        Camera {
+            encoding: Encoding::Mono8,
+            ..Camera::default()
+        }
+        .with_payload(1000),
+        Camera::default().with_payload(1),
+        Camera::default().with_payload(0),
+    ];
+    for camera in cameras.iter() {
+        let image = camera.capture(header(), &mut ChaCha8Rng::seed_from_u64(0));
+        assert_eq!(image.validate(), Ok(()), "{:?}", camera);
+        assert_eq!(
+            image.data.len(),
+            image.height as usize * image.step as usize
+        );
+        assert_eq!(image.step, image.width * camera.encoding.channels());
+        assert_eq!(image.encoding, camera.encoding.name());
+    }
+    assert_eq!(Encoding::Mono8.name(), encodings::MONO8);
+}
+
+#[test]
+fn sizes_the_images_after_their_payload() {
+    for encoding in [Encoding::Rgb8, Encoding::Mono8].iter().copied() {
+        let camera = Camera {
+            encoding,
+            ..Camera::default()
+        };
+        for bytes in [1, 100, 1000, 65536, 1 << 20].iter().copied() {
+            let resized = camera.with_payload(bytes);
+            let image = resized.capture(None, &mut ChaCha8Rng::seed_from_u64(0));
+            assert_eq!(image.validate(), Ok(()), "{:?}", resized);
+
+            // At least `bytes`, rounded up to a whole row.
+            assert!(image.data.len() >= bytes, "{:?}", resized);
+            assert!(
+                image.data.len() < bytes + image.step as usize + encoding.channels() as usize,
+                "{:?}",
+                resized
+            );
+        }
+    }
+
+    // The aspect ratio is kept.
+    let resized = Camera::default().with_payload(3 * 40 * 30);
+    assert_eq!((resized.width, resized.height), (40, 30));
+    assert_eq!(Camera::default().with_payload(0), Camera::default());
+}
@@ -0,0 +1,146 @@
+//
+// Copyright (c) 2022 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+//! The reports of `Statistics`, driven by a manual clock.
+
+use datatypes::clock::{ManualClock, CLOCK_KEY};
+use datatypes::stats::Statistics;
+use serde_json::json;
+use std::path::PathBuf;
+use std::time::Duration;
+use zenoh_flow::prelude::Configuration;
+
+const INTERVAL: Duration = Duration::from_millis(500);
+
+static HEADER: &str = "time_ms,direction,port,messages,messages_per_sec,bytes_per_sec,gaps";
+
+/// The statistics of the node `test` and the file they are reported to, every `INTERVAL` of
+/// `clock`, whose time starts at 1000s since the epoch.
+fn statistics(test: &str) -> (Statistics, ManualClock, PathBuf) {
+    let clock = ManualClock::starting_at(Duration::from_secs(1000));
+    let dir = std::env::temp_dir().join(format!("montblanc-stats-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+
+    let configuration = Configuration::from(json!({
+        "stats_dir": dir,
+        "stats_interval_ms": INTERVAL.as_millis() as u64,
+        CLOCK_KEY: clock.name(),
+    }));
+    let statistics = Statistics::new(test, &["A", "B"], &["Out"], Some(&configuration)).unwrap();
+    (statistics, clock, dir.join(format!("{}.csv", test)))
+}
+
+/// Waits for the statistics to be reported after `elapsed` more time, and returns the lines of the
+/// report.
+async fn report(clock: &ManualClock, path: &PathBuf, elapsed: Duration) -> Vec<String> {
+    clock.asleep().await;
+    clock.advance(elapsed);
+    // The report sleeps again once written.
+    clock.asleep().await;
+    std::fs::read_to_string(path)
+        .unwrap()
+        .lines()
+        .map(str::to_string)
+        .collect()
+}
+
+#[async_std::test]
+async fn reports_the_counters_of_each_port_at_each_interval() {
+    let (statistics, clock, path) = statistics("counters");
+    clock.asleep().await;
+
+    for _ in 0..3 {
+        statistics.received("A");
+    }
+    statistics.decoded("A", 30);
+    statistics.decoded("A", 20);
+    statistics.sent("Out");
+    statistics.encoded("Out", 100);
+    // Unknown ports, and the inputs counted as outputs, are ignored.
+    statistics.received("Unknown");
+    statistics.sent("A");
+    statistics.encoded("B", 1000);
+
+    assert_eq!(
+        report(&clock, &path, INTERVAL).await,
+        [
+            HEADER,
+            "1000500,input,A,3,6.00,100.00,0",
+            "1000500,input,B,0,0.00,0.00,0",
+            "1000500,output,Out,1,2.00,200.00,0",
+        ]
+    );
+
+    // The counters were reset by the report.
+    statistics.received("B");
+    let lines = report(&clock, &path, INTERVAL).await;
+    assert_eq!(
+        lines[4..],
+        [
+            "1001000,input,A,0,0.00,0.00,0",
+            "1001000,input,B,1,2.00,0.00,0",
+            "1001000,output,Out,0,0.00,0.00,0",
+        ]
+    );
+}
+
+#[async_std::test]
+async fn counts_the_gaps_after_the_warmup() {
+    let (statistics, clock, path) = statistics("gaps");
+    clock.asleep().await;
+
+    // A late message before the average interval is known is not a gap.
+    statistics.received("A");
+    clock.advance(Duration::from_millis(10));
+    statistics.received("A");
+    clock.advance(Duration::from_millis(50));
+    statistics.received("A");
+    // 10 intervals of 10ms, then one of 40ms: the average is close enough to 10ms to detect it.
+    for _ in 0..10 {
+        clock.advance(Duration::from_millis(10));
+        statistics.received("A");
+    }
+    clock.advance(Duration::from_millis(40));
+    statistics.received("A");
+    // Back to 10ms.
+    clock.advance(Duration::from_millis(10));
+    statistics.received("A");
+
+    let elapsed = Duration::from_millis(10 + 50 + 10 * 10 + 40 + 10);
+    let lines = report(&clock, &path, INTERVAL - elapsed).await;
+    assert_eq!(lines[1], "1000500,input,A,15,30.00,0.00,1");
+}
+
+#[test]
+fn rejects_invalid_settings() {
+    let missing = std::env::temp_dir()
+        .join(format!("montblanc-stats-{}", std::process::id()))
+        .join("missing");
+    let cases = [
+        json!({ "stats_dir": missing }),
+        json!({ "stats_dir": 42 }),
+        json!({ "stats_dir": std::env::temp_dir(), "stats_interval_ms": 0 }),
+        json!({ "stats_dir": std::env::temp_dir(), "stats_interval_ms": "1s" }),
+        json!({ "clock": "manual:nowhere" }),
+    ];
+    for case in cases.iter() {
+        let configuration = Configuration::from(case.clone());
+        assert!(
+            Statistics::new("invalid", &["A"], &[], Some(&configuration)).is_err(),
+            "{}",
+            case
+        );
+    }
+    assert!(Statistics::new("none", &["A"], &[], None).is_ok());
+}
@@ -0,0 +1,202 @@
+//
+// Copyright (c) 2022 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+//! The strategies generating the messages of `data_types`, shared by the tests of their encodings.
+
+use datatypes::data_types;
+use datatypes::data_types::point_cloud2::point_field::DataType;
+use proptest::collection::vec;
+use proptest::option;
+use proptest::prelude::*;
+
+/// Any float but NaN, which is not equal to itself.
+pub fn float() -> impl Strategy<Value = f32> {
+    use proptest::num::f32::*;
+    POSITIVE | NEGATIVE | NORMAL | SUBNORMAL | ZERO | INFINITE
+}
+
+pub fn double() -> impl Strategy<Value = f64> {
+    use proptest::num::f64::*;
+    POSITIVE | NEGATIVE | NORMAL | SUBNORMAL | ZERO | INFINITE
+}
+
+prop_compose! {
+    pub fn timestamp()(sec in any::<u64>(), nanosec in any::<u32>()) -> data_types::Timestamp {
+        data_types::Timestamp { sec, nanosec }
+    }
+}
+
+prop_compose! {
+    pub fn header()(sec in any::<i32>(), nanosec in any::<u32>(), frame_id in ".{0,32}")
+        -> data_types::Header {
+        data_types::Header { sec, nanosec, frame_id }
+    }
+}
+
+prop_compose! {
+    pub fn point()(x in double(), y in double(), z in double()) -> data_types::Point {
+        data_types::Point { x, y, z }
+    }
+}
+
+prop_compose! {
+    pub fn quaternion()(x in double(), y in double(), z in double(), w in double())
+        -> data_types::Quaternion {
+        data_types::Quaternion { x, y, z, w }
+    }
+}
+
+prop_compose! {
+    pub fn vector3()(x in double(), y in double(), z in double()) -> data_types::Vector3 {
+        data_types::Vector3 { x, y, z }
+    }
+}
+
+prop_compose! {
+    pub fn vector3_stamped()(header in option::of(header()), vector in option::of(vector3()))
+        -> data_types::Vector3Stamped {
+        data_types::Vector3Stamped { header, vector }
+    }
+}
+
+prop_compose! {
+    pub fn pose()(position in option::of(point()), orientation in option::of(quaternion()))
+        -> data_types::Pose {
+        data_types::Pose { position, orientation }
+    }
+}
+
+prop_compose! {
+    pub fn twist()(linear in option::of(vector3()), angular in option::of(vector3()))
+        -> data_types::Twist {
+        data_types::Twist { linear, angular }
+    }
+}
+
+prop_compose! {
+    // The covariance is a 6x6 matrix, but protobuf does not enforce its length.
+    pub fn twist_with_covariance()(
+        twist in option::of(twist()),
+        covariance in prop_oneof![vec(double(), 36), vec(double(), 0..64)],
+    ) -> data_types::TwistWithCovariance {
+        data_types::TwistWithCovariance { twist, covariance }
+    }
+}
+
+prop_compose! {
+    pub fn twist_with_covariance_stamped()(
+        header in option::of(header()),
+        twist in option::of(twist_with_covariance()),
+    ) -> data_types::TwistWithCovarianceStamped {
+        data_types::TwistWithCovarianceStamped { header, twist }
+    }
+}
+
+prop_compose! {
+    pub fn wrench()(force in option::of(vector3()), torque in option::of(vector3()))
+        -> data_types::Wrench {
+        data_types::Wrench { force, torque }
+    }
+}
+
+prop_compose! {
+    pub fn wrench_stamped()(header in option::of(header()), wrench in option::of(wrench()))
+        -> data_types::WrenchStamped {
+        data_types::WrenchStamped { header, wrench }
+    }
+}
+
+prop_compose! {
+    pub fn image()(
+        header in option::of(header()),
+        height in any::<u32>(),
+        width in any::<u32>(),
+        encoding in prop_oneof![Just("rgb8".to_string()), Just("mono16".to_string()), ".{0,16}"],
+        is_bigendian in any::<bool>(),
+        step in any::<u32>(),
+        data in vec(any::<u8>(), 0..4096),
+    ) -> data_types::Image {
+        data_types::Image { header, height, width, encoding, is_bigendian, step, data }
+    }
+}
+
+/// A known datatype most of the time, but protobuf keeps unknown values as they are.
+pub fn datatype() -> impl Strategy<Value = i32> {
+    prop_oneof![
+        4 => prop_oneof![
+            Just(DataType::Int8),
+            Just(DataType::Uint8),
+            Just(DataType::Int16),
+            Just(DataType::Uint16),
+            Just(DataType::Int32),
+            Just(DataType::Uint32),
+            Just(DataType::Float32),
+            Just(DataType::Float64),
+        ]
+        .prop_map(|datatype| datatype as i32),
+        1 => any::<i32>(),
+    ]
+}
+
+prop_compose! {
+    pub fn point_field()(name in ".{0,16}", offset in any::<u32>(), datatype in datatype(),
+                     count in any::<u32>()) -> data_types::point_cloud2::PointField {
+        data_types::point_cloud2::PointField { name, offset, datatype, count }
+    }
+}
+
+prop_compose! {
+    pub fn pointcloud2()(
+        header in option::of(header()),
+        height in any::<u32>(),
+        width in any::<u32>(),
+        fields in vec(point_field(), 0..8),
+        is_bigendian in any::<bool>(),
+        point_step in any::<u32>(),
+        row_step in any::<u32>(),
+        data in vec(any::<u8>(), 0..4096),
+        is_dense in any::<bool>(),
+    ) -> data_types::PointCloud2 {
+        data_types::PointCloud2 {
+            header, height, width, fields, is_bigendian, point_step, row_step, data, is_dense,
+        }
+    }
+}
+
+prop_compose! {
+    pub fn laserscan()(
+        header in option::of(header()),
+        angles in [float(), float(), float()],
+        time_increment in float(),
+        scan_time in float(),
+        range_min in float(),
+        range_max in float(),
+        ranges in vec(float(), 0..512),
+        intensities in vec(float(), 0..512),
+    ) -> data_types::LaserScan {
+        let [angle_min, angle_max, angle_increment] = angles;
+        data_types::LaserScan {
+            header,
+            angle_min,
+            angle_max,
+            angle_increment,
+            time_increment,
+            scan_time,
+            range_min,
+            range_max,
+            ranges,
+            intensities,
+        }
+    }
+}
@@ -0,0 +1,159 @@
+//
+// Copyright (c) 2022 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+//! The deadlines of a `Ticker` driven by a manual clock, on time and after missed ticks.
+
+use datatypes::clock::ManualClock;
+use datatypes::ticker::{MissedTicks, Ticker};
+use datatypes::Configurable;
+use futures::executor::block_on;
+use futures::FutureExt;
+use serde_json::json;
+use std::time::{Duration, Instant};
+use zenoh_flow::prelude::Configuration;
+
+const PERIOD: Duration = Duration::from_millis(100);
+
+fn ms(millis: u64) -> Duration {
+    Duration::from_millis(millis)
+}
+
+/// A ticker on a manual clock, which ticked once on time, and the time it was created at.
+fn ticker(missed_ticks: MissedTicks) -> (Ticker, ManualClock, Instant) {
+    let clock = ManualClock::new();
+    let start = clock.now();
+    let ticker = Ticker::every(PERIOD)
+        .with_missed_ticks(missed_ticks)
+        .with_clock(clock.clock());
+
+    assert_eq!(ticker.tick().now_or_never(), None);
+    clock.advance(PERIOD);
+    assert_eq!(ticker.tick().now_or_never(), Some(start + PERIOD));
+    (ticker, clock, start)
+}
+
+#[test]
+fn ticks_at_each_period() {
+    let (ticker, clock, start) = ticker(MissedTicks::Skip);
+
+    for tick in 2..5 {
+        clock.advance(ms(60));
+        assert_eq!(ticker.tick().now_or_never(), None);
+        clock.advance(ms(40));
+        assert_eq!(ticker.tick().now_or_never(), Some(start + PERIOD * tick));
+    }
+}
+
+#[test]
+fn wakes_up_when_the_clock_reaches_the_deadline() {
+    let (ticker, clock, start) = ticker(MissedTicks::Skip);
+
+    let advance = async {
+        clock.asleep().await;
+        clock.advance(PERIOD);
+    };
+    let (deadline, _) = block_on(async { futures::join!(ticker.tick(), advance) });
+    assert_eq!(deadline, start + PERIOD * 2);
+}
+
+#[test]
+fn skip_drops_the_missed_ticks() {
+    let (ticker, clock, start) = ticker(MissedTicks::Skip);
+
+    // Late by 2.5 periods: the late tick happens, the ticks at 300ms and 400ms are dropped.
+    clock.advance(ms(350));
+    assert_eq!(ticker.tick().now_or_never(), Some(start + ms(200)));
+    assert_eq!(ticker.tick().now_or_never(), None);
+    clock.advance(ms(50));
+    assert_eq!(ticker.tick().now_or_never(), Some(start + ms(500)));
+}
+
+#[test]
+fn burst_catches_up_with_the_missed_ticks() {
+    let (ticker, clock, start) = ticker(MissedTicks::Burst);
+
+    clock.advance(ms(350));
+    for deadline in [200, 300, 400].iter().copied() {
+        assert_eq!(ticker.tick().now_or_never(), Some(start + ms(deadline)));
+    }
+    assert_eq!(ticker.tick().now_or_never(), None);
+    clock.advance(ms(50));
+    assert_eq!(ticker.tick().now_or_never(), Some(start + ms(500)));
+}
+
+#[test]
+fn delay_shifts_the_deadlines() {
+    let (ticker, clock, start) = ticker(MissedTicks::Delay);
+
+    clock.advance(ms(350));
+    assert_eq!(ticker.tick().now_or_never(), Some(start + ms(200)));
+    clock.advance(ms(99));
+    assert_eq!(ticker.tick().now_or_never(), None);
+    clock.advance(ms(1));
+    assert_eq!(ticker.tick().now_or_never(), Some(start + ms(550)));
+}
+
+#[test]
+fn a_late_tick_within_a_period_does_not_shift_the_deadlines() {
+    for missed_ticks in MissedTicks::ALL.iter().copied() {
+        let (ticker, clock, start) = ticker(missed_ticks);
+
+        clock.advance(ms(150));
+        assert_eq!(ticker.tick().now_or_never(), Some(start + ms(200)));
+        clock.advance(ms(50));
+        assert_eq!(ticker.tick().now_or_never(), Some(start + ms(300)));
+    }
+}
+
+#[test]
+fn reads_its_rate_policy_and_clock_from_the_configuration() {
+    let clock = ManualClock::new();
+    let start = clock.now();
+    let configuration = Configuration::from(json!({
+        "rate_hz": 20,
+        "missed_ticks": "delay",
+        "clock": clock.name(),
+    }));
+    let ticker = Ticker::every(PERIOD)
+        .configure(Some(&configuration))
+        .unwrap();
+
+    assert_eq!(ticker.period(), ms(50));
+    assert_eq!(ticker.missed_ticks(), MissedTicks::Delay);
+    assert_eq!(ticker.tick().now_or_never(), None);
+    clock.advance(ms(50));
+    assert_eq!(ticker.tick().now_or_never(), Some(start + ms(50)));
+}
+
+#[test]
+fn rejects_rates_without_a_period() {
+    for rate in [
+        json!(0),
+        json!(-1.0),
+        json!(2e9),
+        json!(1e-300),
+        json!("fast"),
+    ]
+    .iter()
+    {
+        let configuration = Configuration::from(json!({ "rate_hz": rate }));
+        assert!(
+            Ticker::every(PERIOD)
+                .configure(Some(&configuration))
+                .is_err(),
+            "rate_hz: {} was accepted",
+            rate
+        );
+    }
+}
@@ -0,0 +1,319 @@
+//
+// Copyright (c) 2022 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+//! The messages accepted by `Validate`, and a rejected one for every `ValidationError`.
+
+use datatypes::data_types::point_cloud2::point_field::DataType;
+use datatypes::data_types::point_cloud2::PointField;
+use datatypes::data_types::{Image, LaserScan, PointCloud2, Quaternion};
+use datatypes::encodings;
+use datatypes::validation::{Validate, ValidationError, QUATERNION_TOLERANCE};
+
+/// A 2x2 `rgb8` image whose rows are padded to 8 bytes.
+fn image() -> Image {
+    Image {
+        header: None,
+        height: 2,
+        width: 2,
+        encoding: encodings::RGB8.to_string(),
+        is_bigendian: false,
+        step: 8,
+        data: vec![0; 16],
+    }
+}
+
+/// 2 points of 8 bytes: `x` followed by `y`.
+fn cloud() -> PointCloud2 {
+    let field = |name: &str, offset| PointField {
+        name: name.to_string(),
+        offset,
+        datatype: DataType::Float32 as i32,
+        count: 1,
+    };
+    PointCloud2 {
+        header: None,
+        height: 1,
+        width: 2,
+        fields: vec![field("x", 0), field("y", 4)],
+        is_bigendian: false,
+        point_step: 8,
+        row_step: 16,
+        data: vec![0; 16],
+        is_dense: true,
+    }
+}
+
+/// 5 ranges, from -1 to 1 radian.
+fn scan() -> LaserScan {
+    LaserScan {
+        header: None,
+        angle_min: -1.0,
+        angle_max: 1.0,
+        angle_increment: 0.5,
+        time_increment: 0.0,
+        scan_time: 0.1,
+        range_min: 0.1,
+        range_max: 30.0,
+        ranges: vec![1.0; 5],
+        intensities: vec![],
+    }
+}
+
+fn quaternion(x: f64, y: f64, z: f64, w: f64) -> Quaternion {
+    Quaternion { x, y, z, w }
+}
+
+#[test]
+fn accepts_consistent_messages() {
+    assert_eq!(image().validate(), Ok(()));
+    let unpadded = Image {
+        step: 6,
+        data: vec![0; 12],
+        ..image()
+    };
+    assert_eq!(unpadded.validate(), Ok(()));
+    let empty = Image {
+        height: 0,
+        data: vec![],
+        ..image()
+    };
+    assert_eq!(empty.validate(), Ok(()));
+
+    assert_eq!(cloud().validate(), Ok(()));
+
+    assert_eq!(scan().validate(), Ok(()));
+    let with_intensities = LaserScan {
+        intensities: vec![0.0; 5],
+        ..scan()
+    };
+    assert_eq!(with_intensities.validate(), Ok(()));
+    let single = LaserScan {
+        angle_max: -1.0,
+        ranges: vec![1.0],
+        ..scan()
+    };
+    assert_eq!(single.validate(), Ok(()));
+
+    assert_eq!(quaternion(0.0, 0.0, 0.0, 1.0).validate(), Ok(()));
+    let half = 0.5_f64;
+    assert_eq!(quaternion(half, half, half, half).validate(), Ok(()));
+    assert_eq!(
+        quaternion(0.0, 0.0, 0.0, 1.0 + QUATERNION_TOLERANCE / 2.0).validate(),
+        Ok(())
+    );
+}
+
+#[test]
+fn rejects_inconsistent_images() {
+    let unknown = Image {
+        encoding: "rgb9".to_string(),
+        ..image()
+    };
+    assert_eq!(
+        unknown.validate(),
+        Err(ValidationError::UnknownEncoding("rgb9".to_string()))
+    );
+
+    let narrow = Image {
+        step: 5,
+        data: vec![0; 10],
+        ..image()
+    };
+    assert_eq!(
+        narrow.validate(),
+        Err(ValidationError::StepTooSmall {
+            step: 5,
+            row_bytes: 6
+        })
+    );
+
+    let truncated = Image {
+        data: vec![0; 15],
+        ..image()
+    };
+    assert_eq!(
+        truncated.validate(),
+        Err(ValidationError::DataLength {
+            expected: 16,
+            actual: 15
+        })
+    );
+}
+
+#[test]
+fn rejects_inconsistent_point_clouds() {
+    let mut unknown = cloud();
+    unknown.fields[1].datatype = 9;
+    assert_eq!(
+        unknown.validate(),
+        Err(ValidationError::UnknownDatatype {
+            field: "y".to_string(),
+            datatype: 9
+        })
+    );
+
+    let mut outside = cloud();
+    outside.fields[1].offset = 6;
+    assert_eq!(
+        outside.validate(),
+        Err(ValidationError::FieldOutOfPoint {
+            field: "y".to_string(),
+            end: 10,
+            point_step: 8
+        })
+    );
+    let mut counted = cloud();
+    counted.fields[1].count = 2;
+    assert_eq!(
+        counted.validate(),
+        Err(ValidationError::FieldOutOfPoint {
+            field: "y".to_string(),
+            end: 12,
+            point_step: 8
+        })
+    );
+
+    let padded_rows = PointCloud2 {
+        row_step: 20,
+        data: vec![0; 20],
+        ..cloud()
+    };
+    assert_eq!(
+        padded_rows.validate(),
+        Err(ValidationError::RowStep {
+            row_step: 20,
+            expected: 16
+        })
+    );
+
+    let truncated = PointCloud2 {
+        data: vec![0; 8],
+        ..cloud()
+    };
+    assert_eq!(
+        truncated.validate(),
+        Err(ValidationError::DataLength {
+            expected: 16,
+            actual: 8
+        })
+    );
+}
+
+#[test]
+fn rejects_inconsistent_scans() {
+    let uneven = LaserScan {
+        angle_increment: 0.3,
+        ..scan()
+    };
+    assert_eq!(
+        uneven.validate(),
+        Err(ValidationError::AngleIncrement {
+            angle_min: -1.0,
+            angle_max: 1.0,
+            angle_increment: 0.3
+        })
+    );
+    let backwards = LaserScan {
+        angle_increment: -0.5,
+        ..scan()
+    };
+    assert!(matches!(
+        backwards.validate(),
+        Err(ValidationError::AngleIncrement { .. })
+    ));
+    let zero = LaserScan {
+        angle_increment: 0.0,
+        ..scan()
+    };
+    assert!(matches!(
+        zero.validate(),
+        Err(ValidationError::AngleIncrement { .. })
+    ));
+
+    for (range_min, range_max) in [(-0.1, 30.0), (31.0, 30.0), (f32::NAN, 30.0)]
+        .iter()
+        .copied()
+    {
+        let bounds = LaserScan {
+            range_min,
+            range_max,
+            ..scan()
+        };
+        assert!(
+            matches!(bounds.validate(), Err(ValidationError::RangeBounds { .. })),
+            "[{}, {}]",
+            range_min,
+            range_max
+        );
+    }
+
+    let missing = LaserScan {
+        ranges: vec![1.0; 4],
+        ..scan()
+    };
+    assert_eq!(
+        missing.validate(),
+        Err(ValidationError::RangeCount {
+            expected: 5,
+            actual: 4
+        })
+    );
+
+    let intensities = LaserScan {
+        intensities: vec![0.0; 6],
+        ..scan()
+    };
+    assert_eq!(
+        intensities.validate(),
+        Err(ValidationError::IntensityCount {
+            expected: 5,
+            actual: 6
+        })
+    );
+}
+
+#[test]
+fn rejects_quaternions_that_are_not_normalised() {
+    assert_eq!(
+        quaternion(0.0, 0.0, 0.0, 2.0).validate(),
+        Err(ValidationError::NotNormalised { norm: 2.0 })
+    );
+    assert_eq!(
+        quaternion(0.0, 0.0, 0.0, 0.0).validate(),
+        Err(ValidationError::NotNormalised { norm: 0.0 })
+    );
+    assert!(matches!(
+        quaternion(f64::NAN, 0.0, 0.0, 1.0).validate(),
+        Err(ValidationError::NotNormalised { .. })
+    ));
+    assert!(quaternion(0.0, 0.0, 0.0, 1.0 + 2.0 * QUATERNION_TOLERANCE)
+        .validate()
+        .is_err());
+}
+
+#[test]
+fn describes_the_errors() {
+    assert_eq!(
+        ValidationError::DataLength {
+            expected: 16,
+            actual: 15
+        }
+        .to_string(),
+        "data is 15 bytes, expected 16"
+    );
+    assert_eq!(
+        ValidationError::UnknownEncoding("rgb9".to_string()).to_string(),
+        "unknown encoding `rgb9`"
+    );
+}
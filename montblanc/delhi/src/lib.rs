@@ -12,41 +12,47 @@
 //   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
 //
 
+use async_std::sync::Mutex;
+use datatypes::datagen::DataGen;
+use datatypes::publication::Publication;
+use datatypes::sensors::Camera;
+use datatypes::stats::Statistics;
+use datatypes::ticker::Ticker;
+use datatypes::Ports;
 use datatypes::COLUMBIA_PORT;
-use prost::Message;
-use rand::random;
 use std::time::Duration;
 use zenoh_flow::prelude::*;
 
 #[export_source]
+#[derive(Ports)]
 pub struct Delhi {
+    #[output(COLUMBIA_PORT)]
     output: Output<datatypes::data_types::Image>,
+    #[statistics]
+    statistics: Statistics,
+    #[configuration(Ticker::every(Duration::from_millis(1000)))]
+    ticker: Ticker,
+    #[configuration(Publication::default())]
+    publication: Publication,
+    #[generate]
+    datagen: Mutex<DataGen>,
 }
 
 #[async_trait::async_trait]
 impl Node for Delhi {
     async fn iteration(&self) -> Result<()> {
-        async_std::task::sleep(Duration::from_millis(1000)).await;
-        let value: datatypes::data_types::Image = random();
-        self.output.send(value, None).await
-    }
-}
-
-#[async_trait::async_trait]
-impl Source for Delhi {
-    async fn new(
-        _context: Context,
-        _configuration: Option<Configuration>,
-        mut outputs: Outputs,
-    ) -> Result<Self> {
-        Ok(Self {
-            output: outputs
-                .take(COLUMBIA_PORT)
-                .unwrap_or_else(|| panic!("No Output called '{}' found", COLUMBIA_PORT))
-                .typed(|buf, v: &datatypes::data_types::Image| {
-                    buf.resize(v.encoded_len(), 0);
-                    Ok(v.encode(buf)?)
-                }),
-        })
+        self.ticker.tick().await;
+        let camera = Camera::default().with_payload(self.publication.payload_bytes);
+        for _ in 0..self.publication.burst_size {
+            let timestamp = datatypes::latency::now();
+            let value = {
+                let mut datagen = self.datagen.lock().await;
+                let header = datagen.gen();
+                camera.capture(Some(header), datagen.rng())
+            };
+            self.statistics.sent(COLUMBIA_PORT);
+            self.output.send(value, Some(timestamp)).await?;
+        }
+        Ok(())
     }
 }
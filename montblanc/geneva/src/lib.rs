@@ -14,11 +14,12 @@
 
 use async_std::sync::Mutex;
 use datatypes::data_types;
+use datatypes::datagen::{DataGen, Generate};
+use datatypes::stats::Statistics;
+use datatypes::Ports;
 use datatypes::{ARKANSAS_PORT, CONGO_PORT, DANUBE_PORT, PARANA_PORT, TAGUS_PORT};
 use futures::prelude::*;
 use futures::select;
-use prost::Message as _;
-use rand::random;
 use std::sync::Arc;
 use zenoh_flow::prelude::*;
 #[derive(Debug, Clone)]
@@ -28,85 +29,68 @@ struct GenevaState {
     congo_last_val: data_types::Twist,
 }
 
+impl Generate for GenevaState {
+    fn generate(datagen: &mut DataGen) -> Self {
+        GenevaState {
+            danube_last_val: data_types::String {
+                value: datagen.string(1),
+            },
+            tagus_last_val: datagen.gen(),
+            congo_last_val: datagen.gen(),
+        }
+    }
+}
+
 #[export_operator]
+#[derive(Ports)]
 pub struct Geneva {
+    #[input(PARANA_PORT)]
     input_parana: Input<data_types::String>,
+    #[input(DANUBE_PORT)]
     input_danube: Input<data_types::String>,
+    #[input(TAGUS_PORT)]
     input_tagus: Input<data_types::Pose>,
+    #[input(CONGO_PORT)]
     input_congo: Input<data_types::Twist>,
+    #[output(ARKANSAS_PORT)]
     output_arkansas: Output<data_types::String>,
+    #[statistics]
+    statistics: Statistics,
+    #[generate]
     state: Arc<Mutex<GenevaState>>,
 }
 
-#[async_trait::async_trait]
-impl Operator for Geneva {
-    async fn new(
-        _context: Context,
-        _configuration: Option<Configuration>,
-        mut inputs: Inputs,
-        mut outputs: Outputs,
-    ) -> Result<Self> {
-        Ok(Self {
-            input_parana: inputs
-                .take(PARANA_PORT)
-                .unwrap_or_else(|| panic!("No Input called '{}' found", PARANA_PORT))
-                .typed(|buf| Ok(data_types::String::decode(buf)?)),
-            input_danube: inputs
-                .take(DANUBE_PORT)
-                .unwrap_or_else(|| panic!("No Input called '{}' found", DANUBE_PORT))
-                .typed(|buf| Ok(data_types::String::decode(buf)?)),
-            input_tagus: inputs
-                .take(TAGUS_PORT)
-                .unwrap_or_else(|| panic!("No Input called '{}' found", TAGUS_PORT))
-                .typed(|buf| Ok(data_types::Pose::decode(buf)?)),
-            input_congo: inputs
-                .take(CONGO_PORT)
-                .unwrap_or_else(|| panic!("No Input called '{}' found", CONGO_PORT))
-                .typed(|buf| Ok(data_types::Twist::decode(buf)?)),
-            output_arkansas: outputs
-                .take(ARKANSAS_PORT)
-                .unwrap_or_else(|| panic!("No Output called '{}' found", ARKANSAS_PORT))
-                .typed(|buf, v: &data_types::String| {
-                    buf.resize(v.encoded_len(), 0);
-                    Ok(v.encode(buf)?)
-                }),
-            state: Arc::new(Mutex::new(GenevaState {
-                danube_last_val: data_types::String {
-                    value: datatypes::random_string(1),
-                },
-                tagus_last_val: random(),
-                congo_last_val: random(),
-            })),
-        })
-    }
-}
-
 #[async_trait::async_trait]
 impl Node for Geneva {
     async fn iteration(&self) -> Result<()> {
         select! {
             msg = self.input_danube.recv().fuse() => {
                 if let Ok((Message::Data(inner_data), _ts)) = msg {
+                    self.statistics.received(DANUBE_PORT);
                     self.state.lock().await.danube_last_val = (*inner_data).clone();
                 }
             },
             msg  = self.input_tagus.recv().fuse() => {
                 if let Ok((Message::Data(inner_data), _ts)) = msg {
+                    self.statistics.received(TAGUS_PORT);
                     self.state.lock().await.tagus_last_val = (*inner_data).clone();
                 }
             },
             msg  = self.input_congo.recv().fuse() => {
                 if let Ok((Message::Data(inner_data), _ts)) = msg {
+                    self.statistics.received(CONGO_PORT);
                     self.state.lock().await.congo_last_val = (*inner_data).clone();
                 }
             },
             msg  = self.input_parana.recv().fuse() => {
-                if let Ok((Message::Data(inner_data), _ts)) = msg {
+                if let Ok((Message::Data(inner_data), ts)) = msg {
+                    self.statistics.received(PARANA_PORT);
                     let value = data_types::String {
                         value: format!("geneva/arkansas:{}", inner_data.value),
                     };
 
-                    self.output_arkansas.send(value, None).await?;
+                    self.statistics.sent(ARKANSAS_PORT);
+                    self.output_arkansas.send(value, Some(ts.get_time().as_u64())).await?;
                 }
             }
         }
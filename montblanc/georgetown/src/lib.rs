@@ -14,11 +14,15 @@
 
 use async_std::sync::Mutex;
 use datatypes::data_types;
+use datatypes::datagen::{DataGen, Generate};
+use datatypes::derived::{self, Outputs};
+use datatypes::stats::Statistics;
+use datatypes::ticker::Ticker;
+use datatypes::Ports;
 use datatypes::{LENA_PORT, MURRAY_PORT, VOLGA_PORT};
 use futures::prelude::*;
 use futures::select;
-use prost::Message as pMessage;
-use rand::random;
+use rand::Rng;
 use std::sync::Arc;
 use std::time::Duration;
 use zenoh_flow::prelude::*;
@@ -29,67 +33,94 @@ struct GeorgetownState {
     lena_last_val: data_types::WrenchStamped,
 
     f64_data: data_types::Float64,
+    /// The timestamp of the newest message received, if any.
+    newest_timestamp: Option<u64>,
+}
+
+impl GeorgetownState {
+    fn received(&mut self, timestamp: u64) {
+        self.newest_timestamp = self.newest_timestamp.max(Some(timestamp));
+    }
+}
+
+impl Generate for GeorgetownState {
+    fn generate(datagen: &mut DataGen) -> Self {
+        GeorgetownState {
+            murray_last_val: datagen.gen(),
+            lena_last_val: datagen.gen(),
+            f64_data: data_types::Float64 {
+                value: datagen.rng().gen(),
+            },
+            newest_timestamp: None,
+        }
+    }
 }
 
 #[export_operator]
+#[derive(Ports)]
 pub struct Georgetown {
+    #[input(MURRAY_PORT)]
     input_murray: Input<data_types::Vector3Stamped>,
+    #[input(LENA_PORT)]
     input_lena: Input<data_types::WrenchStamped>,
+    #[output(VOLGA_PORT)]
     output_volga: Output<data_types::Float64>,
+    #[statistics]
+    statistics: Statistics,
+    #[configuration(Outputs::default())]
+    outputs: Outputs,
+    #[configuration(Ticker::every(Duration::from_millis(50)))]
+    ticker: Ticker,
+    #[generate]
     state: Arc<Mutex<GeorgetownState>>,
 }
 
-#[async_trait::async_trait]
-impl Operator for Georgetown {
-    async fn new(
-        _context: Context,
-        _configuration: Option<Configuration>,
-        mut inputs: Inputs,
-        mut outputs: Outputs,
-    ) -> Result<Self> {
-        Ok(Self {
-            input_murray: inputs
-                .take(MURRAY_PORT)
-                .unwrap_or_else(|| panic!("No Input called '{}' found", MURRAY_PORT))
-                .typed(|buf| Ok(data_types::Vector3Stamped::decode(buf)?)),
-            input_lena: inputs
-                .take(LENA_PORT)
-                .unwrap_or_else(|| panic!("No Input called '{}' found", LENA_PORT))
-                .typed(|buf| Ok(data_types::WrenchStamped::decode(buf)?)),
-            output_volga: outputs
-                .take(VOLGA_PORT)
-                .unwrap_or_else(|| panic!("No Output called '{}' found", VOLGA_PORT))
-                .typed(|buf, v: &data_types::Float64| {
-                    buf.resize(v.encoded_len(), 0);
-                    Ok(v.encode(buf)?)
-                }),
-            state: Arc::new(Mutex::new(GeorgetownState {
-                murray_last_val: random(),
-                lena_last_val: random(),
-                f64_data: data_types::Float64 { value: random() },
-            })),
-        })
-    }
-}
-
 #[async_trait::async_trait]
 impl Node for Georgetown {
     async fn iteration(&self) -> Result<()> {
         select! {
             msg = self.input_murray.recv().fuse() => {
-                if let Ok((Message::Data(inner_data), _ts)) = msg {
-                    self.state.lock().await.murray_last_val = (*inner_data).clone();
+                if let Ok((Message::Data(inner_data), ts)) = msg {
+                    self.statistics.received(MURRAY_PORT);
+                    let mut state = self.state.lock().await;
+                    state.murray_last_val = (*inner_data).clone();
+                    state.received(ts.get_time().as_u64());
                 }
             },
             msg  = self.input_lena.recv().fuse() => {
-                if let Ok((Message::Data(inner_data), _ts)) = msg {
-                    self.state.lock().await.lena_last_val = (*inner_data).clone();
+                if let Ok((Message::Data(inner_data), ts)) = msg {
+                    self.statistics.received(LENA_PORT);
+                    let mut state = self.state.lock().await;
+                    state.lena_last_val = (*inner_data).clone();
+                    state.received(ts.get_time().as_u64());
                 }
             },
-            // Output every 50ms
-            _ = async_std::task::sleep(Duration::from_millis(50)).fuse() => {
+            // Output at each tick, every 50ms by default: a random value is stamped when sent, a
+            // work with the newest of the timestamps of the force and of the displacement.
+            _ = self.ticker.tick().fuse() => {
                 let guard_state = self.state.lock().await;
-                self.output_volga.send(guard_state.f64_data.clone(), None).await?;
+                let (value, timestamp) = match self.outputs {
+                    Outputs::Random => (guard_state.f64_data.clone(), None),
+                    // The work of the force of Barcelona along the displacement of Rotterdam.
+                    Outputs::Derived => {
+                        let work = derived::work(
+                            guard_state
+                                .lena_last_val
+                                .wrench
+                                .as_ref()
+                                .and_then(|wrench| wrench.force.as_ref())
+                                .unwrap_or(&Default::default()),
+                            guard_state
+                                .murray_last_val
+                                .vector
+                                .as_ref()
+                                .unwrap_or(&Default::default()),
+                        );
+                        (work, guard_state.newest_timestamp)
+                    }
+                };
+                self.statistics.sent(VOLGA_PORT);
+                self.output_volga.send(value, timestamp).await?;
             }
         }
         Ok(())
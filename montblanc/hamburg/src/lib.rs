@@ -14,14 +14,15 @@
 
 use async_std::sync::Mutex;
 use datatypes::data_types;
+use datatypes::stats::Statistics;
+use datatypes::Ports;
 use datatypes::{DANUBE_PORT, GANGES_PORT, NILE_PORT, PARANA_PORT, TIGRIS_PORT};
 use futures::prelude::*;
 use futures::select;
-use prost::Message as pMessage;
 use std::sync::Arc;
 use zenoh_flow::prelude::*;
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default)]
 struct HamburgState {
     ganges_last_val: i64,
     nile_last_val: i32,
@@ -29,81 +30,53 @@ struct HamburgState {
 }
 
 #[export_operator]
+#[derive(Ports)]
 pub struct Hamburg {
+    #[input(TIGRIS_PORT)]
     input_tigris: Input<data_types::Float32>,
+    #[input(GANGES_PORT)]
     input_ganges: Input<data_types::Int64>,
+    #[input(NILE_PORT)]
     input_nile: Input<data_types::Int32>,
+    #[input(DANUBE_PORT)]
     input_danube: Input<data_types::String>,
+    #[output(PARANA_PORT)]
     output_parana: Output<data_types::String>,
+    #[statistics]
+    statistics: Statistics,
     state: Arc<Mutex<HamburgState>>,
 }
 
-#[async_trait::async_trait]
-impl Operator for Hamburg {
-    async fn new(
-        _context: Context,
-        _configuration: Option<Configuration>,
-        mut inputs: Inputs,
-        mut outputs: Outputs,
-    ) -> Result<Self> {
-        Ok(Self {
-            input_tigris: inputs
-                .take(TIGRIS_PORT)
-                .unwrap_or_else(|| panic!("No Input called '{}' found", TIGRIS_PORT))
-                .typed(|buf| Ok(data_types::Float32::decode(buf)?)),
-            input_ganges: inputs
-                .take(GANGES_PORT)
-                .unwrap_or_else(|| panic!("No Input called '{}' found", GANGES_PORT))
-                .typed(|buf| Ok(data_types::Int64::decode(buf)?)),
-            input_nile: inputs
-                .take(NILE_PORT)
-                .unwrap_or_else(|| panic!("No Input called '{}' found", NILE_PORT))
-                .typed(|buf| Ok(data_types::Int32::decode(buf)?)),
-            input_danube: inputs
-                .take(DANUBE_PORT)
-                .unwrap_or_else(|| panic!("No Input called '{}' found", DANUBE_PORT))
-                .typed(|buf| Ok(data_types::String::decode(buf)?)),
-            output_parana: outputs
-                .take(PARANA_PORT)
-                .unwrap_or_else(|| panic!("No Output called '{}' found", PARANA_PORT))
-                .typed(|buf, v: &data_types::String| {
-                    buf.resize(v.encoded_len(), 0);
-                    Ok(v.encode(buf)?)
-                }),
-            state: Arc::new(Mutex::new(HamburgState {
-                ganges_last_val: 0i64,
-                nile_last_val: 0i32,
-                tigris_last_val: 0.0f32,
-            })),
-        })
-    }
-}
-
 #[async_trait::async_trait]
 impl Node for Hamburg {
     async fn iteration(&self) -> Result<()> {
         select! {
             msg = self.input_tigris.recv().fuse() => {
                 if let Ok((Message::Data(inner_data), _ts)) = msg {
+                    self.statistics.received(TIGRIS_PORT);
                     self.state.lock().await.tigris_last_val = inner_data.value;
                 }
             },
             msg  = self.input_ganges.recv().fuse() => {
                 if let Ok((Message::Data(inner_data), _ts)) = msg {
+                    self.statistics.received(GANGES_PORT);
                     self.state.lock().await.ganges_last_val = inner_data.value;
                 }
             },
             msg  = self.input_nile.recv().fuse() => {
                 if let Ok((Message::Data(inner_data), _ts)) = msg {
+                    self.statistics.received(NILE_PORT);
                     self.state.lock().await.nile_last_val = inner_data.value;
                 }
             },
             msg  = self.input_danube.recv().fuse() => {
-                if let Ok((Message::Data(inner_data), _ts)) = msg {
+                if let Ok((Message::Data(inner_data), ts)) = msg {
+                    self.statistics.received(DANUBE_PORT);
                     let new_value = data_types::String {
                         value: format!("hamburg/parana:{}", inner_data.value)
                     };
-                    self.output_parana.send(new_value, None).await?;
+                    self.statistics.sent(PARANA_PORT);
+                    self.output_parana.send(new_value, Some(ts.get_time().as_u64())).await?;
                 }
             }
         }
@@ -12,40 +12,40 @@
 //   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
 //
 
+use async_std::sync::Mutex;
+use datatypes::datagen::DataGen;
+use datatypes::publication::Publication;
+use datatypes::stats::Statistics;
+use datatypes::ticker::Ticker;
+use datatypes::Ports;
 use datatypes::CHENAB_PORT;
-use prost::Message;
-use rand::random;
 use std::time::Duration;
 use zenoh_flow::prelude::*;
 #[export_source]
+#[derive(Ports)]
 pub struct Hebron {
+    #[output(CHENAB_PORT)]
     output: Output<datatypes::data_types::Quaternion>,
+    #[statistics]
+    statistics: Statistics,
+    #[configuration(Ticker::every(Duration::from_millis(100)))]
+    ticker: Ticker,
+    #[configuration(Publication::without_payload())]
+    publication: Publication,
+    #[generate]
+    datagen: Mutex<DataGen>,
 }
 
 #[async_trait::async_trait]
 impl Node for Hebron {
     async fn iteration(&self) -> Result<()> {
-        async_std::task::sleep(Duration::from_millis(100)).await;
-        let value: datatypes::data_types::Quaternion = random();
-        self.output.send(value, None).await
-    }
-}
-
-#[async_trait::async_trait]
-impl Source for Hebron {
-    async fn new(
-        _context: Context,
-        _configuration: Option<Configuration>,
-        mut outputs: Outputs,
-    ) -> Result<Self> {
-        Ok(Self {
-            output: outputs
-                .take(CHENAB_PORT)
-                .unwrap_or_else(|| panic!("No Output called '{}' found", CHENAB_PORT))
-                .typed(|buf, v: &datatypes::data_types::Quaternion| {
-                    buf.resize(v.encoded_len(), 0);
-                    Ok(v.encode(buf)?)
-                }),
-        })
+        self.ticker.tick().await;
+        for _ in 0..self.publication.burst_size {
+            let timestamp = datatypes::latency::now();
+            let value: datatypes::data_types::Quaternion = self.datagen.lock().await.gen();
+            self.statistics.sent(CHENAB_PORT);
+            self.output.send(value, Some(timestamp)).await?;
+        }
+        Ok(())
     }
 }
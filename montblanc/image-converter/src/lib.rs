@@ -0,0 +1,159 @@
+//
+// Copyright (c) 2022 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+use datatypes::data_types;
+use datatypes::image;
+use datatypes::stats::Statistics;
+use datatypes::{Configurable, Ports};
+use std::convert::TryFrom;
+use zenoh_flow::prelude::*;
+
+static INPUT_PORT: &str = "Input";
+static OUTPUT_PORT: &str = "Output";
+
+static CROP_KEY: &str = "crop";
+static RESIZE_KEY: &str = "resize";
+static ENCODING_KEY: &str = "encoding";
+
+/// The region of an image to keep.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Region {
+    x: u32,
+    y: u32,
+    width: u32,
+    height: u32,
+}
+
+/// The transformations applied to every image, in this order.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+struct Conversion {
+    crop: Option<Region>,
+    resize: Option<(u32, u32)>,
+    encoding: Option<String>,
+}
+
+impl Conversion {
+    fn apply(
+        &self,
+        image: &data_types::Image,
+    ) -> std::result::Result<data_types::Image, image::ImageError> {
+        let mut image = match self.crop {
+            Some(region) => image::crop(image, region.x, region.y, region.width, region.height)?,
+            None => image.clone(),
+        };
+        if let Some((width, height)) = self.resize {
+            image = image::resize(&image, width, height)?;
+        }
+        if let Some(encoding) = &self.encoding {
+            if image.encoding != *encoding {
+                image = image::convert(&image, encoding)?;
+            }
+        }
+        Ok(image)
+    }
+}
+
+/// Reads the unsigned integer `key` of the object `section`.
+fn dimension(section: &str, value: &serde_json::Value, key: &str) -> Result<u32> {
+    value
+        .get(key)
+        .and_then(|dimension| dimension.as_u64())
+        .and_then(|dimension| u32::try_from(dimension).ok())
+        .ok_or_else(|| {
+            zferror!(
+                ErrorKind::ConfigurationError,
+                "`{}.{}` must be a number of pixels",
+                section,
+                key
+            )
+            .into()
+        })
+}
+
+impl Configurable for Conversion {
+    /// Reads the `crop` (`x`, `y`, `width` and `height`), `resize` (`width` and `height`) and
+    /// `encoding` of the configuration.
+    fn configure(mut self, configuration: Option<&Configuration>) -> Result<Self> {
+        let configuration = match configuration {
+            Some(configuration) => configuration,
+            None => return Ok(self),
+        };
+
+        if let Some(crop) = configuration.get(CROP_KEY) {
+            self.crop = Some(Region {
+                x: dimension(CROP_KEY, crop, "x")?,
+                y: dimension(CROP_KEY, crop, "y")?,
+                width: dimension(CROP_KEY, crop, "width")?,
+                height: dimension(CROP_KEY, crop, "height")?,
+            });
+        }
+
+        if let Some(resize) = configuration.get(RESIZE_KEY) {
+            self.resize = Some((
+                dimension(RESIZE_KEY, resize, "width")?,
+                dimension(RESIZE_KEY, resize, "height")?,
+            ));
+        }
+
+        if let Some(encoding) = configuration.get(ENCODING_KEY) {
+            self.encoding = Some(
+                encoding
+                    .as_str()
+                    .ok_or_else(|| {
+                        zferror!(
+                            ErrorKind::ConfigurationError,
+                            "`{}` must be a string, found: {}",
+                            ENCODING_KEY,
+                            encoding
+                        )
+                    })?
+                    .to_string(),
+            );
+        }
+
+        Ok(self)
+    }
+}
+
+/// Crops, resizes and converts the images it receives, as set in its configuration.
+#[export_operator]
+#[derive(Ports)]
+pub struct ImageConverter {
+    #[input(INPUT_PORT)]
+    input: Input<data_types::Image>,
+    #[output(OUTPUT_PORT)]
+    output: Output<data_types::Image>,
+    #[statistics]
+    statistics: Statistics,
+    #[configuration(Conversion::default())]
+    conversion: Conversion,
+}
+
+#[async_trait::async_trait]
+impl Node for ImageConverter {
+    async fn iteration(&self) -> Result<()> {
+        if let Ok((Message::Data(data), ts)) = self.input.recv().await {
+            self.statistics.received(INPUT_PORT);
+            let value = self
+                .conversion
+                .apply(&data)
+                .map_err(|e| zferror!(ErrorKind::InvalidData, "{}", e))?;
+            self.statistics.sent(OUTPUT_PORT);
+            self.output
+                .send(value, Some(ts.get_time().as_u64()))
+                .await?;
+        }
+        Ok(())
+    }
+}
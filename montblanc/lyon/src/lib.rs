@@ -12,46 +12,30 @@
 //   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
 //
 
+use datatypes::stats::Statistics;
+use datatypes::Ports;
 use datatypes::{AMAZON_PORT, TIGRIS_PORT};
-use prost::Message;
 use zenoh_flow::prelude::*;
 
 #[export_operator]
+#[derive(Ports)]
 pub struct Lyon {
+    #[input(AMAZON_PORT)]
     input: Input<datatypes::data_types::Float32>,
+    #[output(TIGRIS_PORT)]
     output: Output<datatypes::data_types::Float32>,
-}
-
-#[async_trait::async_trait]
-impl Operator for Lyon {
-    async fn new(
-        _context: Context,
-        _configuration: Option<Configuration>,
-        mut inputs: Inputs,
-        mut outputs: Outputs,
-    ) -> Result<Self> {
-        Ok(Self {
-            input: inputs
-                .take(AMAZON_PORT)
-                .unwrap_or_else(|| panic!("No Input called '{}' found", AMAZON_PORT))
-                .typed(|buf| Ok(datatypes::data_types::Float32::decode(buf)?)),
-            output: outputs
-                .take(TIGRIS_PORT)
-                .unwrap_or_else(|| panic!("No Output called '{}' found", TIGRIS_PORT))
-                .typed(|buf, v: &datatypes::data_types::Float32| {
-                    buf.resize(v.encoded_len(), 0);
-                    Ok(v.encode(buf)?)
-                }),
-        })
-    }
+    #[statistics]
+    statistics: Statistics,
 }
 
 #[async_trait::async_trait]
 impl Node for Lyon {
     async fn iteration(&self) -> Result<()> {
-        let (msg, _ts) = self.input.recv().await?;
+        let (msg, ts) = self.input.recv().await?;
         if let zenoh_flow::prelude::Message::Data(data) = msg {
-            self.output.send(data, None).await?;
+            self.statistics.received(AMAZON_PORT);
+            self.statistics.sent(TIGRIS_PORT);
+            self.output.send(data, Some(ts.get_time().as_u64())).await?;
         }
         Ok(())
     }
@@ -12,42 +12,43 @@
 //   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
 //
 
+use async_std::sync::Mutex;
+use datatypes::datagen::DataGen;
+use datatypes::publication::Publication;
+use datatypes::stats::Statistics;
+use datatypes::ticker::Ticker;
+use datatypes::Ports;
 use datatypes::NILE_PORT;
-use prost::Message;
-use rand::random;
+use rand::Rng;
 use std::time::Duration;
 use zenoh_flow::prelude::*;
 
 #[export_source]
+#[derive(Ports)]
 pub struct Madelin {
+    #[output(NILE_PORT)]
     output: Output<datatypes::data_types::Int32>,
+    #[statistics]
+    statistics: Statistics,
+    #[configuration(Ticker::every(Duration::from_millis(10)))]
+    ticker: Ticker,
+    #[configuration(Publication::without_payload())]
+    publication: Publication,
+    #[generate]
+    datagen: Mutex<DataGen>,
 }
 
 #[async_trait::async_trait]
 impl Node for Madelin {
     async fn iteration(&self) -> Result<()> {
-        async_std::task::sleep(Duration::from_millis(10)).await;
-        let data: i32 = random::<i32>();
-        let value = datatypes::data_types::Int32 { value: data };
-        self.output.send(value, None).await
-    }
-}
-
-#[async_trait::async_trait]
-impl Source for Madelin {
-    async fn new(
-        _context: Context,
-        _configuration: Option<Configuration>,
-        mut outputs: Outputs,
-    ) -> Result<Self> {
-        Ok(Self {
-            output: outputs
-                .take(NILE_PORT)
-                .unwrap_or_else(|| panic!("No Output called '{}' found", NILE_PORT))
-                .typed(|buf, v: &datatypes::data_types::Int32| {
-                    buf.resize(v.encoded_len(), 0);
-                    Ok(v.encode(buf)?)
-                }),
-        })
+        self.ticker.tick().await;
+        for _ in 0..self.publication.burst_size {
+            let timestamp = datatypes::latency::now();
+            let data: i32 = self.datagen.lock().await.rng().gen();
+            let value = datatypes::data_types::Int32 { value: data };
+            self.statistics.sent(NILE_PORT);
+            self.output.send(value, Some(timestamp)).await?;
+        }
+        Ok(())
     }
 }
@@ -14,14 +14,17 @@
 
 use async_std::sync::Mutex;
 use datatypes::data_types;
+use datatypes::datagen::{DataGen, Generate};
+use datatypes::derived::{self, Outputs};
+use datatypes::stats::Statistics;
+use datatypes::ticker::Ticker;
+use datatypes::Ports;
 use datatypes::{
     BRAZOS_PORT, CHENAB_PORT, DANUBE_PORT, GODAVARI_PORT, LOIRE_PORT, MISSOURI_PORT, SALWEEN_PORT,
     TAGUS_PORT, YAMUNA_PORT,
 };
 use futures::prelude::*;
 use futures::select;
-use prost::Message as _;
-use rand::random;
 use std::sync::Arc;
 use std::time::Duration;
 use zenoh_flow::prelude::*;
@@ -37,137 +40,150 @@ struct MandalayState {
     pointcloud2_data: data_types::PointCloud2,
     pose_data: data_types::Pose,
     img_data: data_types::Image,
+    /// The timestamp of the newest message received, if any.
+    newest_timestamp: Option<u64>,
+}
+
+impl MandalayState {
+    fn received(&mut self, timestamp: u64) {
+        self.newest_timestamp = self.newest_timestamp.max(Some(timestamp));
+    }
+}
+
+impl Generate for MandalayState {
+    fn generate(datagen: &mut DataGen) -> Self {
+        MandalayState {
+            danube_last_val: data_types::String {
+                value: datagen.string(1),
+            },
+            chenab_last_val: datagen.gen(),
+            salween_last_val: datagen.gen(),
+            godavari_last_val: datagen.gen(),
+            loire_last_val: datagen.gen(),
+            yamuna_last_val: datagen.gen(),
+            pointcloud2_data: datagen.gen(),
+            pose_data: datagen.gen(),
+            img_data: datagen.gen(),
+            newest_timestamp: None,
+        }
+    }
 }
 
 #[export_operator]
+#[derive(Ports)]
 pub struct Mandalay {
+    #[input(DANUBE_PORT)]
     input_danube: Input<data_types::String>,
+    #[input(CHENAB_PORT)]
     input_chenab: Input<data_types::Quaternion>,
+    #[input(SALWEEN_PORT)]
     input_salween: Input<data_types::PointCloud2>,
+    #[input(GODAVARI_PORT)]
     input_godavari: Input<data_types::LaserScan>,
+    #[input(LOIRE_PORT)]
     input_loire: Input<data_types::PointCloud2>,
+    #[input(YAMUNA_PORT)]
     input_yamuna: Input<data_types::Vector3>,
+    #[output(BRAZOS_PORT)]
     output_brazos: Output<data_types::PointCloud2>,
+    #[output(TAGUS_PORT)]
     output_tagus: Output<data_types::Pose>,
+    #[output(MISSOURI_PORT)]
     output_missouri: Output<data_types::Image>,
+    #[statistics]
+    statistics: Statistics,
+    #[configuration(Outputs::default())]
+    outputs: Outputs,
+    #[configuration(Ticker::every(Duration::from_millis(100)))]
+    ticker: Ticker,
+    #[generate]
     state: Arc<Mutex<MandalayState>>,
 }
 
-#[async_trait::async_trait]
-impl Operator for Mandalay {
-    async fn new(
-        _context: Context,
-        _configuration: Option<Configuration>,
-        mut inputs: Inputs,
-        mut outputs: Outputs,
-    ) -> Result<Self> {
-        Ok(Self {
-            input_danube: inputs
-                .take(DANUBE_PORT)
-                .unwrap_or_else(|| panic!("No Input called '{}' found", DANUBE_PORT))
-                .typed(|buf| Ok(data_types::String::decode(buf)?)),
-            input_chenab: inputs
-                .take(CHENAB_PORT)
-                .unwrap_or_else(|| panic!("No Input called '{}' found", CHENAB_PORT))
-                .typed(|buf| Ok(data_types::Quaternion::decode(buf)?)),
-            input_salween: inputs
-                .take(SALWEEN_PORT)
-                .unwrap_or_else(|| panic!("No Input called '{}' found", SALWEEN_PORT))
-                .typed(|buf| Ok(data_types::PointCloud2::decode(buf)?)),
-            input_godavari: inputs
-                .take(GODAVARI_PORT)
-                .unwrap_or_else(|| panic!("No Input called '{}' found", GODAVARI_PORT))
-                .typed(|buf| Ok(data_types::LaserScan::decode(buf)?)),
-            input_loire: inputs
-                .take(LOIRE_PORT)
-                .unwrap_or_else(|| panic!("No Input called '{}' found", LOIRE_PORT))
-                .typed(|buf| Ok(data_types::PointCloud2::decode(buf)?)),
-            input_yamuna: inputs
-                .take(YAMUNA_PORT)
-                .unwrap_or_else(|| panic!("No Input called '{}' found", YAMUNA_PORT))
-                .typed(|buf| Ok(data_types::Vector3::decode(buf)?)),
-            output_brazos: outputs
-                .take(BRAZOS_PORT)
-                .unwrap_or_else(|| panic!("No Output called '{}' found", BRAZOS_PORT))
-                .typed(|buf, v: &data_types::PointCloud2| {
-                    buf.resize(v.encoded_len(), 0);
-                    Ok(v.encode(buf)?)
-                }),
-            output_tagus: outputs
-                .take(TAGUS_PORT)
-                .unwrap_or_else(|| panic!("No Output called '{}' found", TAGUS_PORT))
-                .typed(|buf, v: &data_types::Pose| {
-                    buf.resize(v.encoded_len(), 0);
-                    Ok(v.encode(buf)?)
-                }),
-            output_missouri: outputs
-                .take(MISSOURI_PORT)
-                .unwrap_or_else(|| panic!("No Output called '{}' found", MISSOURI_PORT))
-                .typed(|buf, v: &data_types::Image| {
-                    buf.resize(v.encoded_len(), 0);
-                    Ok(v.encode(buf)?)
-                }),
-            state: Arc::new(Mutex::new(MandalayState {
-                danube_last_val: data_types::String {
-                    value: datatypes::random_string(1),
-                },
-                chenab_last_val: random(),
-                salween_last_val: random(),
-                godavari_last_val: random(),
-                loire_last_val: random(),
-                yamuna_last_val: random(),
-                pointcloud2_data: random(),
-                pose_data: random(),
-                img_data: random(),
-            })),
-        })
-    }
-}
-
 #[async_trait::async_trait]
 impl Node for Mandalay {
     async fn iteration(&self) -> Result<()> {
         select! {
             msg = self.input_danube.recv().fuse() => {
-                if let Ok((Message::Data(inner_data), _ts)) = msg {
-                    self.state.lock().await.danube_last_val = (*inner_data).clone();
+                if let Ok((Message::Data(inner_data), ts)) = msg {
+                    self.statistics.received(DANUBE_PORT);
+                    let mut state = self.state.lock().await;
+                    state.danube_last_val = (*inner_data).clone();
+                    state.received(ts.get_time().as_u64());
                 }
             },
             msg = self.input_chenab.recv().fuse() => {
-                if let Ok((Message::Data(inner_data), _ts)) = msg {
-                    self.state.lock().await.chenab_last_val = (*inner_data).clone();
+                if let Ok((Message::Data(inner_data), ts)) = msg {
+                    self.statistics.received(CHENAB_PORT);
+                    let mut state = self.state.lock().await;
+                    state.chenab_last_val = (*inner_data).clone();
+                    state.received(ts.get_time().as_u64());
                 }
             },
             msg = self.input_salween.recv().fuse() => {
-                if let Ok((Message::Data(inner_data), _ts)) = msg {
-                    self.state.lock().await.salween_last_val = (*inner_data).clone();
+                if let Ok((Message::Data(inner_data), ts)) = msg {
+                    self.statistics.received(SALWEEN_PORT);
+                    let mut state = self.state.lock().await;
+                    state.salween_last_val = (*inner_data).clone();
+                    state.received(ts.get_time().as_u64());
                 }
             },
             msg = self.input_godavari.recv().fuse() => {
-                if let Ok((Message::Data(inner_data), _ts)) = msg {
-                    self.state.lock().await.godavari_last_val = (*inner_data).clone();
+                if let Ok((Message::Data(inner_data), ts)) = msg {
+                    self.statistics.received(GODAVARI_PORT);
+                    let mut state = self.state.lock().await;
+                    state.godavari_last_val = (*inner_data).clone();
+                    state.received(ts.get_time().as_u64());
                 }
             },
             msg = self.input_loire.recv().fuse() => {
-                if let Ok((Message::Data(inner_data), _ts)) = msg {
-                    self.state.lock().await.loire_last_val = (*inner_data).clone();
+                if let Ok((Message::Data(inner_data), ts)) = msg {
+                    self.statistics.received(LOIRE_PORT);
+                    let mut state = self.state.lock().await;
+                    state.loire_last_val = (*inner_data).clone();
+                    state.received(ts.get_time().as_u64());
                 }
             },
             msg = self.input_yamuna.recv().fuse() => {
-                if let Ok((Message::Data(inner_data), _ts)) = msg {
-                    self.state.lock().await.yamuna_last_val = (*inner_data).clone();
+                if let Ok((Message::Data(inner_data), ts)) = msg {
+                    self.statistics.received(YAMUNA_PORT);
+                    let mut state = self.state.lock().await;
+                    state.yamuna_last_val = (*inner_data).clone();
+                    state.received(ts.get_time().as_u64());
                 }
             },
-            // Output every 100ms
-            _ = async_std::task::sleep(Duration::from_millis(100)).fuse() => {
-
+            // Output at each tick, every 100ms by default. Random outputs start new paths, stamped
+            // when sent. Derived ones carry the timestamp of the newest message received, so that
+            // the latency of the paths going through Mandalay is measured from the freshest data.
+            _ = self.ticker.tick().fuse() => {
                 let guard_state = self.state.lock().await;
+                let (pointcloud2, pose, img, timestamp) = match self.outputs {
+                    Outputs::Random => (
+                        guard_state.pointcloud2_data.clone(),
+                        guard_state.pose_data.clone(),
+                        guard_state.img_data.clone(),
+                        None,
+                    ),
+                    // The points of Osaka and Tripoli, the pose given by Hebron and Kingston, and
+                    // the ranges of the latest scan of Osaka.
+                    Outputs::Derived => (
+                        derived::merge(&[
+                            &guard_state.salween_last_val,
+                            &guard_state.loire_last_val,
+                        ]),
+                        derived::pose(&guard_state.chenab_last_val, &guard_state.yamuna_last_val),
+                        derived::range_image(&guard_state.godavari_last_val),
+                        guard_state.newest_timestamp,
+                    ),
+                };
 
-                self.output_brazos.send(guard_state.pointcloud2_data.clone(), None).await?;
-                self.output_tagus.send(guard_state.pose_data.clone(), None).await?;
-                self.output_missouri.send(guard_state.img_data.clone(), None).await?;
+                self.statistics.sent(BRAZOS_PORT);
+                self.output_brazos.send(pointcloud2, timestamp).await?;
+                self.statistics.sent(TAGUS_PORT);
+                self.output_tagus.send(pose, timestamp).await?;
+                self.statistics.sent(MISSOURI_PORT);
+                self.output_missouri.send(img, timestamp).await?;
             }
-
         }
         Ok(())
     }
@@ -12,42 +12,31 @@
 //   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
 //
 
+use async_std::sync::Mutex;
 use datatypes::data_types;
+use datatypes::datagen::DataGen;
+use datatypes::derived::{self, Outputs};
+use datatypes::stats::Statistics;
+use datatypes::Ports;
 use datatypes::{CONGO_PORT, OHIO_PORT};
 use futures::prelude::*;
 use futures::select;
-use prost::Message as pMessage;
-use rand::random;
+use rand::Rng;
 use zenoh_flow::prelude::*;
 
 #[export_operator]
+#[derive(Ports)]
 pub struct Monaco {
+    #[input(CONGO_PORT)]
     input_congo: Input<data_types::Twist>,
+    #[output(OHIO_PORT)]
     output_ohio: Output<data_types::Float32>,
-}
-
-#[async_trait::async_trait]
-impl Operator for Monaco {
-    async fn new(
-        _context: Context,
-        _configuration: Option<Configuration>,
-        mut inputs: Inputs,
-        mut outputs: Outputs,
-    ) -> Result<Self> {
-        Ok(Self {
-            input_congo: inputs
-                .take(CONGO_PORT)
-                .unwrap_or_else(|| panic!("No Input called '{}' found", CONGO_PORT))
-                .typed(|buf| Ok(data_types::Twist::decode(buf)?)),
-            output_ohio: outputs
-                .take(OHIO_PORT)
-                .unwrap_or_else(|| panic!("No Output called '{}' found", OHIO_PORT))
-                .typed(|buf, v: &data_types::Float32| {
-                    buf.resize(v.encoded_len(), 0);
-                    Ok(v.encode(buf)?)
-                }),
-        })
-    }
+    #[statistics]
+    statistics: Statistics,
+    #[configuration(Outputs::default())]
+    outputs: Outputs,
+    #[generate]
+    datagen: Mutex<DataGen>,
 }
 
 #[async_trait::async_trait]
@@ -55,9 +44,16 @@ impl Node for Monaco {
     async fn iteration(&self) -> Result<()> {
         select! {
             msg  = self.input_congo.recv().fuse() => {
-                if let Ok((Message::Data(_inner_data), _ts)) = msg {
-                    let value = data_types::Float32 { value: random() };
-                    self.output_ohio.send(value, None).await?;
+                if let Ok((Message::Data(inner_data), ts)) = msg {
+                    self.statistics.received(CONGO_PORT);
+                    let value = match self.outputs {
+                        Outputs::Random => data_types::Float32 {
+                            value: self.datagen.lock().await.rng().gen(),
+                        },
+                        Outputs::Derived => derived::speed(&inner_data),
+                    };
+                    self.statistics.sent(OHIO_PORT);
+                    self.output_ohio.send(value, Some(ts.get_time().as_u64())).await?;
             }}
         }
         Ok(())
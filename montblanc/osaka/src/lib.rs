@@ -14,11 +14,13 @@
 
 use async_std::sync::Mutex;
 use datatypes::data_types;
+use datatypes::datagen::{DataGen, Generate};
+use datatypes::derived::{self, Outputs};
+use datatypes::stats::Statistics;
+use datatypes::Ports;
 use datatypes::{COLORADO_PORT, COLUMBIA_PORT, GODAVARI_PORT, PARANA_PORT, SALWEEN_PORT};
 use futures::prelude::*;
 use futures::select;
-use prost::Message as _;
-use rand::random;
 use std::sync::Arc;
 use zenoh_flow::prelude::*;
 
@@ -31,84 +33,81 @@ struct OsakaState {
     laserscan_data: data_types::LaserScan,
 }
 
+impl Generate for OsakaState {
+    fn generate(datagen: &mut DataGen) -> Self {
+        OsakaState {
+            parana_last_val: data_types::String {
+                value: datagen.string(1),
+            },
+            columbia_last_val: datagen.gen(),
+            _colorado_last_val: datagen.gen(),
+            pointcloud2_data: datagen.gen(),
+            laserscan_data: datagen.gen(),
+        }
+    }
+}
+
 #[export_operator]
+#[derive(Ports)]
 pub struct Osaka {
+    #[input(PARANA_PORT)]
     input_parana: Input<data_types::String>,
+    #[input(COLUMBIA_PORT)]
     input_columbia: Input<data_types::Image>,
+    #[input(COLORADO_PORT)]
     input_colorado: Input<data_types::Image>,
+    #[output(SALWEEN_PORT)]
     output_salween: Output<data_types::PointCloud2>,
+    #[output(GODAVARI_PORT)]
     output_godavari: Output<data_types::LaserScan>,
+    #[statistics]
+    statistics: Statistics,
+    #[configuration(Outputs::default())]
+    outputs: Outputs,
+    #[generate]
     state: Arc<Mutex<OsakaState>>,
 }
 
-#[async_trait::async_trait]
-impl Operator for Osaka {
-    async fn new(
-        _context: Context,
-        _configuration: Option<Configuration>,
-        mut inputs: Inputs,
-        mut outputs: Outputs,
-    ) -> Result<Self> {
-        Ok(Self {
-            input_parana: inputs
-                .take(PARANA_PORT)
-                .unwrap_or_else(|| panic!("No Input called '{}' found", PARANA_PORT))
-                .typed(|buf| Ok(data_types::String::decode(buf)?)),
-            input_columbia: inputs
-                .take(COLUMBIA_PORT)
-                .unwrap_or_else(|| panic!("No Input called '{}' found", COLUMBIA_PORT))
-                .typed(|buf| Ok(data_types::Image::decode(buf)?)),
-            input_colorado: inputs
-                .take(COLORADO_PORT)
-                .unwrap_or_else(|| panic!("No Input called '{}' found", COLORADO_PORT))
-                .typed(|buf| Ok(data_types::Image::decode(buf)?)),
-            output_salween: outputs
-                .take(SALWEEN_PORT)
-                .unwrap_or_else(|| panic!("No Output called '{}' found", SALWEEN_PORT))
-                .typed(|buf, v: &data_types::PointCloud2| {
-                    buf.resize(v.encoded_len(), 0);
-                    Ok(v.encode(buf)?)
-                }),
-            output_godavari: outputs
-                .take(GODAVARI_PORT)
-                .unwrap_or_else(|| panic!("No Output called '{}' found", GODAVARI_PORT))
-                .typed(|buf, v: &data_types::LaserScan| {
-                    buf.resize(v.encoded_len(), 0);
-                    Ok(v.encode(buf)?)
-                }),
-            state: Arc::new(Mutex::new(OsakaState {
-                parana_last_val: data_types::String {
-                    value: datatypes::random_string(1),
-                },
-                columbia_last_val: random(),
-                _colorado_last_val: random(),
-                pointcloud2_data: random(),
-                laserscan_data: random(),
-            })),
-        })
-    }
-}
-
 #[async_trait::async_trait]
 impl Node for Osaka {
     async fn iteration(&self) -> Result<()> {
         select! {
             msg = self.input_parana.recv().fuse() => {
                 if let Ok((Message::Data(inner_data), _ts)) = msg {
+                    self.statistics.received(PARANA_PORT);
                     self.state.lock().await.parana_last_val = (*inner_data).clone();
                 }
             },
             msg = self.input_columbia.recv().fuse() => {
                 if let Ok((Message::Data(inner_data), _ts)) = msg {
+                    self.statistics.received(COLUMBIA_PORT);
                     self.state.lock().await.columbia_last_val = (*inner_data).clone();
                 }
             },
             msg = self.input_colorado.recv().fuse() => {
-                if let Ok((Message::Data(_inner_data), _ts)) = msg {
-                    let guard_state = self.state.lock().await;
+                if let Ok((Message::Data(inner_data), ts)) = msg {
+                    self.statistics.received(COLORADO_PORT);
+                    let (pointcloud2, laserscan) = match self.outputs {
+                        Outputs::Random => {
+                            let guard_state = self.state.lock().await;
+                            (
+                                guard_state.pointcloud2_data.clone(),
+                                guard_state.laserscan_data.clone(),
+                            )
+                        }
+                        // A scan of the image, and the points it hit.
+                        Outputs::Derived => {
+                            let laserscan = derived::scan(&inner_data)
+                                .map_err(|e| zferror!(ErrorKind::InvalidData, "{}", e))?;
+                            (derived::scan_cloud(&laserscan), laserscan)
+                        }
+                    };
 
-                    self.output_salween.send(guard_state.pointcloud2_data.clone(), None).await?;
-                    self.output_godavari.send(guard_state.laserscan_data.clone(), None).await?;
+                    let timestamp = Some(ts.get_time().as_u64());
+                    self.statistics.sent(SALWEEN_PORT);
+                    self.output_salween.send(pointcloud2, timestamp).await?;
+                    self.statistics.sent(GODAVARI_PORT);
+                    self.output_godavari.send(laserscan, timestamp).await?;
                 }
             }
         }
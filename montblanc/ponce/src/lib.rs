@@ -14,14 +14,17 @@
 
 use async_std::sync::Mutex;
 use datatypes::data_types;
+use datatypes::datagen::{DataGen, Generate};
+use datatypes::derived::{self, Outputs};
+use datatypes::stats::Statistics;
+use datatypes::Ports;
 use datatypes::{
-    BRAZOS_PORT, CONGO_PORT, DANUBE_PORT, LOIRE_PORT, MEKONG_PORT, MISSOURI_PORT, OHIO_PORT,
-    TAGUS_PORT, VOLGA_PORT, YAMUNA_PORT,
+    BRAZOS_PORT, CONGO_PORT, DANUBE_PORT, GODAVARI_PORT, LOIRE_PORT, MEKONG_PORT, MISSOURI_PORT,
+    OHIO_PORT, TAGUS_PORT, VOLGA_PORT, YAMUNA_PORT,
 };
 use futures::prelude::*;
 use futures::select;
-use prost::Message as pMessage;
-use rand::random;
+use rand::Rng;
 use std::sync::Arc;
 use zenoh_flow::prelude::*;
 
@@ -32,7 +35,7 @@ struct PonceState {
     missouri_last_val: data_types::Image,
     loire_last_val: data_types::PointCloud2,
     yamuna_last_val: data_types::Vector3,
-
+    godavari_last_val: data_types::LaserScan,
     ohio_last_val: data_types::Float32,
     volga_last_val: data_types::Float64,
 
@@ -40,141 +43,146 @@ struct PonceState {
     twist_w_cov_data: data_types::TwistWithCovarianceStamped,
 }
 
+impl Generate for PonceState {
+    fn generate(datagen: &mut DataGen) -> Self {
+        PonceState {
+            danube_last_val: data_types::String {
+                value: datagen.string(1),
+            },
+            tagus_last_val: datagen.gen(),
+            missouri_last_val: datagen.gen(),
+            loire_last_val: datagen.gen(),
+            yamuna_last_val: datagen.gen(),
+            godavari_last_val: datagen.gen(),
+            ohio_last_val: data_types::Float32 {
+                value: datagen.rng().gen(),
+            },
+            volga_last_val: data_types::Float64 {
+                value: datagen.rng().gen(),
+            },
+
+            twist_data: datagen.gen(),
+            twist_w_cov_data: datagen.gen(),
+        }
+    }
+}
+
 #[export_operator]
+#[derive(Ports)]
 pub struct Ponce {
+    #[input(DANUBE_PORT)]
     input_danube: Input<data_types::String>,
+    #[input(TAGUS_PORT)]
     input_tagus: Input<data_types::Pose>,
+    #[input(MISSOURI_PORT)]
     input_missouri: Input<data_types::Image>,
+    #[input(LOIRE_PORT)]
     input_loire: Input<data_types::PointCloud2>,
+    #[input(YAMUNA_PORT)]
     input_yamuna: Input<data_types::Vector3>,
+    #[input(GODAVARI_PORT)]
+    input_godavari: Input<data_types::LaserScan>,
+    #[input(OHIO_PORT)]
     input_ohio: Input<data_types::Float32>,
+    #[input(VOLGA_PORT)]
     input_volga: Input<data_types::Float64>,
+    #[input(BRAZOS_PORT)]
     input_brazos: Input<data_types::PointCloud2>,
+    #[output(CONGO_PORT)]
     output_congo: Output<data_types::Twist>,
+    #[output(MEKONG_PORT)]
     output_mekong: Output<data_types::TwistWithCovarianceStamped>,
+    #[statistics]
+    statistics: Statistics,
+    #[configuration(Outputs::default())]
+    outputs: Outputs,
+    #[generate]
     state: Arc<Mutex<PonceState>>,
 }
 
-#[async_trait::async_trait]
-impl Operator for Ponce {
-    async fn new(
-        _context: Context,
-        _configuration: Option<Configuration>,
-        mut inputs: Inputs,
-        mut outputs: Outputs,
-    ) -> Result<Self> {
-        Ok(Self {
-            input_danube: inputs
-                .take(DANUBE_PORT)
-                .unwrap_or_else(|| panic!("No Input called '{}' found", DANUBE_PORT))
-                .typed(|buf| Ok(data_types::String::decode(buf)?)),
-            input_tagus: inputs
-                .take(TAGUS_PORT)
-                .unwrap_or_else(|| panic!("No Input called '{}' found", TAGUS_PORT))
-                .typed(|buf| Ok(data_types::Pose::decode(buf)?)),
-            input_missouri: inputs
-                .take(MISSOURI_PORT)
-                .unwrap_or_else(|| panic!("No Input called '{}' found", MISSOURI_PORT))
-                .typed(|buf| Ok(data_types::Image::decode(buf)?)),
-            input_loire: inputs
-                .take(LOIRE_PORT)
-                .unwrap_or_else(|| panic!("No Input called '{}' found", LOIRE_PORT))
-                .typed(|buf| Ok(data_types::PointCloud2::decode(buf)?)),
-            input_yamuna: inputs
-                .take(YAMUNA_PORT)
-                .unwrap_or_else(|| panic!("No Input called '{}' found", YAMUNA_PORT))
-                .typed(|buf| Ok(data_types::Vector3::decode(buf)?)),
-            input_ohio: inputs
-                .take(OHIO_PORT)
-                .unwrap_or_else(|| panic!("No Input called '{}' found", OHIO_PORT))
-                .typed(|buf| Ok(data_types::Float32::decode(buf)?)),
-            input_volga: inputs
-                .take(VOLGA_PORT)
-                .unwrap_or_else(|| panic!("No Input called '{}' found", VOLGA_PORT))
-                .typed(|buf| Ok(data_types::Float64::decode(buf)?)),
-            input_brazos: inputs
-                .take(BRAZOS_PORT)
-                .unwrap_or_else(|| panic!("No Input called '{}' found", BRAZOS_PORT))
-                .typed(|buf| Ok(data_types::PointCloud2::decode(buf)?)),
-
-            output_congo: outputs
-                .take(CONGO_PORT)
-                .unwrap_or_else(|| panic!("No Output called '{}' found", CONGO_PORT))
-                .typed(|buf, v: &data_types::Twist| {
-                    buf.resize(v.encoded_len(), 0);
-                    Ok(v.encode(buf)?)
-                }),
-            output_mekong: outputs
-                .take(MEKONG_PORT)
-                .unwrap_or_else(|| panic!("No Output called '{}' found", MEKONG_PORT))
-                .typed(|buf, v: &data_types::TwistWithCovarianceStamped| {
-                    buf.resize(v.encoded_len(), 0);
-                    Ok(v.encode(buf)?)
-                }),
-            state: Arc::new(Mutex::new(PonceState {
-                danube_last_val: data_types::String {
-                    value: datatypes::random_string(1),
-                },
-                tagus_last_val: random(),
-                missouri_last_val: random(),
-                loire_last_val: random(),
-                yamuna_last_val: random(),
-
-                ohio_last_val: data_types::Float32 { value: random() },
-                volga_last_val: data_types::Float64 { value: random() },
-
-                twist_data: random(),
-                twist_w_cov_data: random(),
-            })),
-        })
-    }
-}
-
 #[async_trait::async_trait]
 impl Node for Ponce {
     async fn iteration(&self) -> Result<()> {
         select! {
             msg = self.input_danube.recv().fuse() => {
                 if let Ok((Message::Data(inner_data), _ts)) = msg {
+                    self.statistics.received(DANUBE_PORT);
                     self.state.lock().await.danube_last_val = (*inner_data).clone();
                 }
             },
             msg  = self.input_tagus.recv().fuse() => {
                 if let Ok((Message::Data(inner_data), _ts)) = msg {
+                    self.statistics.received(TAGUS_PORT);
                     self.state.lock().await.tagus_last_val = (*inner_data).clone();
                 }
             },
             msg = self.input_missouri.recv().fuse() => {
                 if let Ok((Message::Data(inner_data), _ts)) = msg {
+                    self.statistics.received(MISSOURI_PORT);
                     self.state.lock().await.missouri_last_val = (*inner_data).clone();
                 }
             },
             msg  = self.input_loire.recv().fuse() => {
                 if let Ok((Message::Data(inner_data), _ts)) = msg {
+                    self.statistics.received(LOIRE_PORT);
                     self.state.lock().await.loire_last_val = (*inner_data).clone();
                 }
             },
             msg = self.input_yamuna.recv().fuse() => {
                 if let Ok((Message::Data(inner_data), _ts)) = msg {
+                    self.statistics.received(YAMUNA_PORT);
                     self.state.lock().await.yamuna_last_val = (*inner_data).clone();
                 }
             },
+            msg = self.input_godavari.recv().fuse() => {
+                if let Ok((Message::Data(inner_data), _ts)) = msg {
+                    self.statistics.received(GODAVARI_PORT);
+                    self.state.lock().await.godavari_last_val = (*inner_data).clone();
+                }
+            },
             msg  = self.input_ohio.recv().fuse() => {
                 if let Ok((Message::Data(inner_data), _ts)) = msg {
+                    self.statistics.received(OHIO_PORT);
                     self.state.lock().await.ohio_last_val = (*inner_data).clone();
                 }
             },
             msg  = self.input_volga.recv().fuse() => {
                 if let Ok((Message::Data(inner_data), _ts)) = msg {
+                    self.statistics.received(VOLGA_PORT);
                     self.state.lock().await.volga_last_val = (*inner_data).clone();
                 }
             },
             msg  = self.input_brazos.recv().fuse() => {
-                if let Ok((Message::Data(_inner_data), _ts)) = msg {
+                if let Ok((Message::Data(inner_data), ts)) = msg {
+                    self.statistics.received(BRAZOS_PORT);
                     let guard_state = self.state.lock().await;
+                    let (twist, twist_w_cov) = match self.outputs {
+                        Outputs::Random => {
+                            (guard_state.twist_data.clone(), guard_state.twist_w_cov_data.clone())
+                        }
+                        // Moving as measured by Kingston towards the latest pose of Mandalay, with
+                        // the uncertainty of the speed and the work fed back by Monaco and
+                        // Georgetown. The point cloud that triggered the output stamps it.
+                        Outputs::Derived => {
+                            let twist = derived::twist(
+                                &guard_state.tagus_last_val,
+                                &guard_state.yamuna_last_val,
+                            );
+                            let twist_w_cov = derived::twist_with_covariance(
+                                inner_data.header.clone(),
+                                twist.clone(),
+                                guard_state.ohio_last_val.value as f64,
+                                guard_state.volga_last_val.value,
+                            );
+                            (twist, twist_w_cov)
+                        }
+                    };
 
-                    self.output_congo.send(guard_state.twist_data.clone(), None).await?;
-                    self.output_mekong.send(guard_state.twist_w_cov_data.clone(), None).await?;
+                    let timestamp = Some(ts.get_time().as_u64());
+                    self.statistics.sent(CONGO_PORT);
+                    self.output_congo.send(twist, timestamp).await?;
+                    self.statistics.sent(MEKONG_PORT);
+                    self.output_mekong.send(twist_w_cov, timestamp).await?;
                 }
             }
         }
@@ -0,0 +1,288 @@
+//
+// Copyright (c) 2022 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+extern crate proc_macro;
+
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+use syn::{parse_macro_input, Data, DeriveInput, Expr, Fields, Ident};
+
+/// Derives the constructor of a zenoh-flow node from the ports declared on its fields.
+///
+/// Fields annotated with `#[input(<port>)]` (resp. `#[output(<port>)]`) are filled with
+/// `datatypes::take_input` (resp. `datatypes::take_output`), `<port>` being either a string literal
+/// or one of the port constants of `datatypes`, and their messages encoded with the
+/// `datatypes::codec::Codec` set by the configuration of the node. A field annotated with
+/// `#[statistics]` is filled with the `datatypes::stats::Statistics` of these ports, configured
+/// from the configuration of the node, which also count the bytes their codec encodes and decodes.
+/// A field annotated with `#[configuration(<default>)]` is filled with `<default>`, overridden by
+/// the configuration of the node through `datatypes::Configurable`. Fields annotated with
+/// `#[generate]` are filled through `datatypes::datagen::Generate`, in declaration order, from a
+/// `DataGen` seeded with the `seed` of the configuration, if any.
+/// All other fields are initialised with `Default::default()`.
+///
+/// Depending on the ports declared, a `Source` (only outputs), a `Sink` (only inputs) or an
+/// `Operator` (both) is implemented. The ids of the ports and the YAML descriptor of the node are
+/// also made available through `inputs()`, `outputs()` and `descriptor(uri)`.
+///
+/// ```ignore
+/// #[export_operator]
+/// #[derive(Ports)]
+/// pub struct Lyon {
+///     #[input(AMAZON_PORT)]
+///     input: Input<data_types::Float32>,
+///     #[output(TIGRIS_PORT)]
+///     output: Output<data_types::Float32>,
+///     #[statistics]
+///     statistics: Statistics,
+/// }
+/// ```
+#[proc_macro_derive(Ports, attributes(input, output, statistics, configuration, generate))]
+pub fn derive_ports(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    match expand(input) {
+        Ok(tokens) => tokens.into(),
+        Err(error) => error.to_compile_error().into(),
+    }
+}
+
+/// What a field of the node is bound to.
+enum Binding {
+    Input(Expr),
+    Output(Expr),
+    Statistics,
+    Configuration(Expr),
+    Generate,
+}
+
+fn binding_of(field: &syn::Field) -> syn::Result<Option<Binding>> {
+    let mut binding = None;
+    for attr in &field.attrs {
+        let declared = if attr.path().is_ident("input") {
+            Binding::Input(attr.parse_args()?)
+        } else if attr.path().is_ident("output") {
+            Binding::Output(attr.parse_args()?)
+        } else if attr.path().is_ident("statistics") {
+            attr.meta.require_path_only()?;
+            Binding::Statistics
+        } else if attr.path().is_ident("configuration") {
+            Binding::Configuration(attr.parse_args()?)
+        } else if attr.path().is_ident("generate") {
+            attr.meta.require_path_only()?;
+            Binding::Generate
+        } else {
+            continue;
+        };
+
+        if binding.is_some() {
+            return Err(syn::Error::new_spanned(
+                attr,
+                "A field can only be bound to a single port, the statistics, the configuration or \
+                 the data generator",
+            ));
+        }
+        binding = Some(declared);
+    }
+
+    Ok(binding)
+}
+
+fn expand(input: DeriveInput) -> syn::Result<TokenStream2> {
+    let name = &input.ident;
+    let fields = match &input.data {
+        Data::Struct(data) => match &data.fields {
+            Fields::Named(fields) => &fields.named,
+            _ => {
+                return Err(syn::Error::new_spanned(
+                    name,
+                    "`Ports` can only be derived on structs with named fields",
+                ))
+            }
+        },
+        _ => {
+            return Err(syn::Error::new_spanned(
+                name,
+                "`Ports` can only be derived on structs",
+            ))
+        }
+    };
+
+    let bindings = fields
+        .iter()
+        .map(binding_of)
+        .collect::<syn::Result<Vec<_>>>()?;
+    let inputs = bindings
+        .iter()
+        .filter_map(|binding| match binding {
+            Some(Binding::Input(port)) => Some(port.clone()),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+    let outputs = bindings
+        .iter()
+        .filter_map(|binding| match binding {
+            Some(Binding::Output(port)) => Some(port.clone()),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+
+    let initialisers = fields
+        .iter()
+        .zip(&bindings)
+        .map(|(field, binding)| {
+            let ident = field.ident.as_ref().expect("named fields have an ident");
+            match binding {
+                Some(Binding::Input(port)) => quote! {
+                    #ident: ::datatypes::take_input(&mut inputs, #port, codec, &statistics)?
+                },
+                Some(Binding::Output(port)) => quote! {
+                    #ident: ::datatypes::take_output(&mut outputs, #port, codec, &statistics)?
+                },
+                Some(Binding::Statistics) => quote! { #ident: statistics.clone() },
+                Some(Binding::Configuration(default)) => quote! {
+                    #ident: ::datatypes::Configurable::configure(#default, _configuration.as_ref())?
+                },
+                Some(Binding::Generate) => quote! {
+                    #ident: ::datatypes::datagen::Generate::generate(&mut datagen)
+                },
+                None => quote! { #ident: ::std::default::Default::default() },
+            }
+        })
+        .collect::<Vec<_>>();
+
+    // The `DataGen` of the node is only created if a field is generated from it.
+    let datagen = bindings
+        .iter()
+        .any(|binding| matches!(binding, Some(Binding::Generate)))
+        .then(|| {
+            quote! {
+                let mut datagen = ::datatypes::Configurable::configure(
+                    ::datatypes::datagen::DataGen::default(),
+                    _configuration.as_ref(),
+                )?;
+            }
+        });
+
+    // Without a statistics field, the bytes encoded and decoded by the ports are not counted.
+    let statistics = if bindings
+        .iter()
+        .any(|binding| matches!(binding, Some(Binding::Statistics)))
+    {
+        quote! {
+            let statistics = ::datatypes::stats::Statistics::new(
+                stringify!(#name),
+                &[#(#inputs),*],
+                &[#(#outputs),*],
+                _configuration.as_ref(),
+            )?;
+        }
+    } else {
+        quote! {
+            let statistics = ::datatypes::stats::Statistics::default();
+        }
+    };
+
+    let prelude = quote! {
+        let codec = ::datatypes::Configurable::configure(
+            ::datatypes::codec::Codec::default(),
+            _configuration.as_ref(),
+        )?;
+        #statistics
+        #datagen
+    };
+
+    let constructor = constructor(name, &inputs, &outputs, &prelude, &initialisers)?;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+
+    Ok(quote! {
+        impl #impl_generics #name #ty_generics #where_clause {
+            /// The ids of the inputs of this node, in declaration order.
+            pub fn inputs() -> ::std::vec::Vec<&'static str> {
+                ::std::vec![#(#inputs),*]
+            }
+
+            /// The ids of the outputs of this node, in declaration order.
+            pub fn outputs() -> ::std::vec::Vec<&'static str> {
+                ::std::vec![#(#outputs),*]
+            }
+
+            /// The YAML descriptor of this node, its library being located at `uri`.
+            pub fn descriptor(uri: &str) -> ::std::string::String {
+                ::datatypes::node_descriptor(stringify!(#name), uri, &Self::inputs(), &Self::outputs())
+            }
+        }
+
+        #constructor
+    })
+}
+
+fn constructor(
+    name: &Ident,
+    inputs: &[Expr],
+    outputs: &[Expr],
+    prelude: &TokenStream2,
+    initialisers: &[TokenStream2],
+) -> syn::Result<TokenStream2> {
+    let constructor = match (inputs.is_empty(), outputs.is_empty()) {
+        (true, true) => {
+            return Err(syn::Error::new_spanned(
+                name,
+                "At least one field must be annotated with `#[input(..)]` or `#[output(..)]`",
+            ))
+        }
+        (true, false) => quote! {
+            #[::async_trait::async_trait]
+            impl ::zenoh_flow::prelude::Source for #name {
+                async fn new(
+                    _context: ::zenoh_flow::prelude::Context,
+                    _configuration: ::std::option::Option<::zenoh_flow::prelude::Configuration>,
+                    mut outputs: ::zenoh_flow::prelude::Outputs,
+                ) -> ::zenoh_flow::prelude::Result<Self> {
+                    #prelude
+                    ::std::result::Result::Ok(Self { #(#initialisers),* })
+                }
+            }
+        },
+        (false, true) => quote! {
+            #[::async_trait::async_trait]
+            impl ::zenoh_flow::prelude::Sink for #name {
+                async fn new(
+                    _context: ::zenoh_flow::prelude::Context,
+                    _configuration: ::std::option::Option<::zenoh_flow::prelude::Configuration>,
+                    mut inputs: ::zenoh_flow::prelude::Inputs,
+                ) -> ::zenoh_flow::prelude::Result<Self> {
+                    #prelude
+                    ::std::result::Result::Ok(Self { #(#initialisers),* })
+                }
+            }
+        },
+        (false, false) => quote! {
+            #[::async_trait::async_trait]
+            impl ::zenoh_flow::prelude::Operator for #name {
+                async fn new(
+                    _context: ::zenoh_flow::prelude::Context,
+                    _configuration: ::std::option::Option<::zenoh_flow::prelude::Configuration>,
+                    mut inputs: ::zenoh_flow::prelude::Inputs,
+                    mut outputs: ::zenoh_flow::prelude::Outputs,
+                ) -> ::zenoh_flow::prelude::Result<Self> {
+                    #prelude
+                    ::std::result::Result::Ok(Self { #(#initialisers),* })
+                }
+            }
+        },
+    };
+
+    Ok(constructor)
+}
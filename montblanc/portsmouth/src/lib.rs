@@ -12,41 +12,51 @@
 //   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
 //
 
+use async_std::sync::Mutex;
+use datatypes::datagen::DataGen;
+use datatypes::payload;
+use datatypes::publication::Publication;
+use datatypes::stats::Statistics;
+use datatypes::ticker::Ticker;
+use datatypes::Ports;
 use datatypes::DANUBE_PORT;
-use prost::Message;
 use std::time::Duration;
 use zenoh_flow::prelude::*;
 #[export_source]
+#[derive(Ports)]
 pub struct Portsmouth {
+    #[output(DANUBE_PORT)]
     output: Output<datatypes::data_types::String>,
+    #[statistics]
+    statistics: Statistics,
+    #[configuration(Ticker::every(Duration::from_millis(200)))]
+    ticker: Ticker,
+    #[configuration(Publication::default())]
+    publication: Publication,
+    #[generate]
+    datagen: Mutex<DataGen>,
 }
 
 #[async_trait::async_trait]
 impl Node for Portsmouth {
     async fn iteration(&self) -> Result<()> {
-        async_std::task::sleep(Duration::from_millis(200)).await;
-        let value = datatypes::data_types::String {
-            value: "portsmouth/danube".into(),
-        };
-        self.output.send(value, None).await
-    }
-}
-
-#[async_trait::async_trait]
-impl Source for Portsmouth {
-    async fn new(
-        _context: Context,
-        _configuration: Option<Configuration>,
-        mut outputs: Outputs,
-    ) -> Result<Self> {
-        Ok(Self {
-            output: outputs
-                .take(DANUBE_PORT)
-                .unwrap_or_else(|| panic!("No Input called '{}' found", DANUBE_PORT))
-                .typed(|buf, v: &datatypes::data_types::String| {
-                    buf.resize(v.encoded_len(), 0);
-                    Ok(v.encode(buf)?)
-                }),
-        })
+        self.ticker.tick().await;
+        for _ in 0..self.publication.burst_size {
+            let timestamp = datatypes::latency::now();
+            let mut value = datatypes::data_types::String {
+                value: "portsmouth/danube".into(),
+            };
+            // The payload follows the path, separated by a space, and ends with its checksum.
+            let padding = self
+                .publication
+                .payload_bytes
+                .saturating_sub(value.value.len() + 1);
+            let payload = payload::generate(&mut *self.datagen.lock().await, padding);
+            value.value.push(' ');
+            value.value.push_str(&payload);
+            self.statistics.sent(DANUBE_PORT);
+            self.output.send(value, Some(timestamp)).await?;
+        }
+        Ok(())
     }
 }
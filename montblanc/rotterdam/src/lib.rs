@@ -12,42 +12,27 @@
 //   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
 //
 
+use async_std::sync::Mutex;
 use datatypes::data_types;
+use datatypes::datagen::DataGen;
+use datatypes::stats::Statistics;
+use datatypes::Ports;
 use datatypes::{MEKONG_PORT, MURRAY_PORT};
 use futures::prelude::*;
 use futures::select;
-use prost::Message as pMessage;
-use rand::random;
 use zenoh_flow::prelude::*;
 
 #[export_operator]
+#[derive(Ports)]
 pub struct Rotterdam {
+    #[input(MEKONG_PORT)]
     input_mekong: Input<data_types::TwistWithCovarianceStamped>,
+    #[output(MURRAY_PORT)]
     output_murray: Output<data_types::Vector3Stamped>,
-}
-
-#[async_trait::async_trait]
-impl Operator for Rotterdam {
-    async fn new(
-        _context: Context,
-        _configuration: Option<Configuration>,
-        mut inputs: Inputs,
-        mut outputs: Outputs,
-    ) -> Result<Self> {
-        Ok(Self {
-            input_mekong: inputs
-                .take(MEKONG_PORT)
-                .unwrap_or_else(|| panic!("No Input called '{}' found", MEKONG_PORT))
-                .typed(|buf| Ok(data_types::TwistWithCovarianceStamped::decode(buf)?)),
-            output_murray: outputs
-                .take(MURRAY_PORT)
-                .unwrap_or_else(|| panic!("No Output called '{}' found", MURRAY_PORT))
-                .typed(|buf, v: &data_types::Vector3Stamped| {
-                    buf.resize(v.encoded_len(), 0);
-                    Ok(v.encode(buf)?)
-                }),
-        })
-    }
+    #[statistics]
+    statistics: Statistics,
+    #[generate]
+    datagen: Mutex<DataGen>,
 }
 
 #[async_trait::async_trait]
@@ -55,9 +40,14 @@ impl Node for Rotterdam {
     async fn iteration(&self) -> Result<()> {
         select! {
             msg  = self.input_mekong.recv().fuse() => {
-                if let Ok((Message::Data(inner_data), _ts)) = msg {
+                if let Ok((Message::Data(inner_data), ts)) = msg {
+                    self.statistics.received(MEKONG_PORT);
+                    let header = match inner_data.header.clone() {
+                        Some(header) => header,
+                        None => self.datagen.lock().await.gen(),
+                    };
                     let value = data_types::Vector3Stamped {
-                        header: Some(inner_data.header.clone().unwrap_or(random())),
+                        header: Some(header),
                         vector: inner_data
                             .twist
                             .as_ref()
@@ -68,7 +58,8 @@ impl Node for Rotterdam {
                             .linear
                             .clone(),
                     };
-                    self.output_murray.send(value, None).await?;
+                    self.statistics.sent(MURRAY_PORT);
+                    self.output_murray.send(value, Some(ts.get_time().as_u64())).await?;
                 }
             }
         }
@@ -0,0 +1,295 @@
+//
+// Copyright (c) 2022 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+//! Runs a data flow of this workspace in the current process, without a Zenoh router, a
+//! zenoh-flow daemon or `zfctl`, e.g. in the tests.
+//!
+//! The nodes cannot be linked in a single binary, as each of them exports its constructor under the
+//! same symbol. They are loaded from the shared libraries of their crates instead, as a daemon
+//! does, and created with their `Source::new`, `Operator::new` or `Sink::new`. All the nodes run
+//! on the same runtime: the links of the data flow are in-memory channels.
+//!
+//! Building a crate that depends on the runner does not build these libraries, so they are only
+//! loaded if they are newer than the sources of their crates, e.g. after a
+//! `cargo build --workspace`.
+
+use anyhow::{anyhow, bail, Context, Result};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::convert::TryFrom;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tools::flow::{self, Severity};
+use tools::nodes::{self, NodeKind};
+use uuid::Uuid;
+use zenoh_flow::model::descriptor::FlattenDataFlowDescriptor;
+use zenoh_flow::model::record::DataFlowRecord;
+use zenoh_flow::runtime::dataflow::instance::DataFlowInstance;
+use zenoh_flow::runtime::dataflow::DataFlow;
+
+/// The name of the runtime all the nodes are mapped to.
+static RUNTIME: &str = "runner";
+
+/// The directory of this workspace, where the crates of the nodes are.
+fn workspace() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR"))
+        .parent()
+        .map(Path::to_path_buf)
+        .unwrap_or_default()
+}
+
+/// The directory of the current executable, or of its parent for the tests and examples built in
+/// `target/<profile>/deps` or `target/<profile>/examples`: where Cargo writes the shared libraries.
+fn profile_dir() -> Result<PathBuf> {
+    let exe = std::env::current_exe()?;
+    let dir = exe
+        .parent()
+        .ok_or_else(|| anyhow!("{} has no parent", exe.display()))?;
+    Ok(match dir.file_name().and_then(|name| name.to_str()) {
+        Some("deps") | Some("examples") => dir.parent().unwrap_or(dir).to_path_buf(),
+        _ => dir.to_path_buf(),
+    })
+}
+
+/// The crates the library of a node in `crate_dir` is built from: its own, and the ones of this
+/// repository it depends on.
+fn library_crates(crate_dir: &Path) -> Vec<PathBuf> {
+    let workspace = workspace();
+    vec![
+        crate_dir.to_path_buf(),
+        workspace.join("datatypes"),
+        workspace.join("ports-derive"),
+        workspace.join("../zf-clock"),
+    ]
+}
+
+/// The time of the last modification of the files a crate is built from: its manifest, its build
+/// script and its `src` directory.
+fn last_modified(crate_dir: &Path) -> Result<SystemTime> {
+    fn visit(path: &Path, last: &mut SystemTime) -> Result<()> {
+        let metadata = match fs::metadata(path) {
+            Ok(metadata) => metadata,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(()),
+            Err(e) => return Err(e).with_context(|| format!("Could not read {}", path.display())),
+        };
+        if metadata.is_dir() {
+            for entry in fs::read_dir(path)? {
+                visit(&entry?.path(), last)?;
+            }
+        } else {
+            *last = (*last).max(metadata.modified()?);
+        }
+        Ok(())
+    }
+
+    let mut last = UNIX_EPOCH;
+    for file in ["Cargo.toml", "build.rs", "src"] {
+        visit(&crate_dir.join(file), &mut last)?;
+    }
+    Ok(last)
+}
+
+/// The `configuration` of a node in the data flow, with the keys of `overrides` replacing its own.
+fn merge(configuration: Option<&Value>, overrides: Option<&Value>) -> Option<Value> {
+    match (configuration, overrides) {
+        (Some(Value::Object(configuration)), Some(Value::Object(overrides))) => {
+            let mut merged = configuration.clone();
+            merged.extend(overrides.clone());
+            Some(merged.into())
+        }
+        (configuration, None) => configuration.cloned(),
+        (_, Some(overrides)) => Some(overrides.clone()),
+    }
+}
+
+/// Runs the data flow described in a file, as `zfctl launch` would.
+#[derive(Debug, Clone)]
+pub struct Runner {
+    flow: PathBuf,
+    vars: HashMap<String, String>,
+    library_dir: Option<PathBuf>,
+    configurations: HashMap<String, Value>,
+}
+
+impl Runner {
+    pub fn new(flow: impl Into<PathBuf>) -> Self {
+        Self {
+            flow: flow.into(),
+            vars: HashMap::new(),
+            library_dir: None,
+            configurations: HashMap::new(),
+        }
+    }
+
+    /// Overrides a variable of the data flow, and of the descriptors of its nodes.
+    pub fn var(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.vars.insert(name.into(), value.into());
+        self
+    }
+
+    /// Where the shared libraries of the nodes are, by default the directory of the profile the
+    /// current executable was built with, e.g. `target/debug`.
+    pub fn library_dir(mut self, library_dir: impl Into<PathBuf>) -> Self {
+        self.library_dir = Some(library_dir.into());
+        self
+    }
+
+    /// Overrides the `configuration` of the node `id` in the data flow: the keys of `configuration`
+    /// replace the ones of the data flow, the others are kept.
+    pub fn configure(mut self, id: impl Into<String>, configuration: Value) -> Self {
+        self.configurations.insert(id.into(), configuration);
+        self
+    }
+
+    /// The data flow, with the descriptors of its nodes inlined, their libraries located in
+    /// `library_dir`, and all its nodes mapped to the runtime of the runner.
+    fn flatten(&self, library_dir: &Path) -> Result<Value> {
+        let mut diagnostics = Vec::new();
+        let loaded = flow::load(&self.flow, &self.vars, &mut diagnostics);
+        if let Some(loaded) = &loaded {
+            diagnostics.extend(flow::validate(loaded));
+        }
+        let errors = diagnostics
+            .iter()
+            .filter(|diagnostic| diagnostic.severity == Severity::Error)
+            .map(ToString::to_string)
+            .collect::<Vec<_>>();
+        let loaded = match loaded {
+            Some(loaded) if errors.is_empty() => loaded,
+            _ => bail!("Invalid data flow:\n{}", errors.join("\n")),
+        };
+
+        let constants_path = workspace().join("datatypes/src/lib.rs");
+        let constants = nodes::port_constants(&nodes::parse_file(&constants_path)?);
+
+        let mut sections: HashMap<&str, Vec<Value>> = HashMap::new();
+        let mut mapping = serde_json::Map::new();
+        for node in &loaded.nodes {
+            if node.builtin {
+                bail!(
+                    "{}: the built-in nodes of Zenoh-Flow cannot be run by the runner, only the \
+                     nodes of this workspace",
+                    node.id
+                );
+            }
+            let crate_dir = node.ports_file.parent().unwrap_or_else(|| Path::new("."));
+            let definition = nodes::scan_crate(crate_dir, &constants)?
+                .ok_or_else(|| anyhow!("{}: no node found in {}", node.id, crate_dir.display()))?;
+            let library = library_dir.join(definition.library_file_name());
+            if !library.exists() {
+                bail!(
+                    "{}: could not find {}, build the workspace first",
+                    node.id,
+                    library.display()
+                );
+            }
+            let built = fs::metadata(&library)?.modified()?;
+            for sources in library_crates(crate_dir) {
+                if last_modified(&sources)? > built {
+                    bail!(
+                        "{}: {} is older than the sources of {}, build the workspace first",
+                        node.id,
+                        library.display(),
+                        sources.display()
+                    );
+                }
+            }
+
+            let ports =
+                |ports: &[flow::Port]| ports.iter().map(|port| port.id.clone()).collect::<Vec<_>>();
+            let section = match node.kind {
+                NodeKind::Source => "sources",
+                NodeKind::Operator => "operators",
+                NodeKind::Sink => "sinks",
+            };
+            let configuration = merge(
+                node.configuration.as_ref(),
+                self.configurations.get(&node.id),
+            );
+            let mut descriptor = json!({
+                "id": node.id,
+                "uri": format!("file://{}", library.display()),
+                "configuration": configuration,
+            });
+            if node.kind != NodeKind::Source {
+                descriptor["inputs"] = ports(&node.inputs).into();
+            }
+            if node.kind != NodeKind::Sink {
+                descriptor["outputs"] = ports(&node.outputs).into();
+            }
+            sections.entry(section).or_default().push(descriptor);
+            mapping.insert(node.id.clone(), RUNTIME.into());
+        }
+
+        let links = loaded
+            .links
+            .iter()
+            .map(|link| {
+                json!({
+                    "from": { "node": link.from.node, "output": link.from.port },
+                    "to": { "node": link.to.node, "input": link.to.port },
+                })
+            })
+            .collect::<Vec<_>>();
+
+        Ok(json!({
+            "flow": self.flow.file_stem().map(|stem| stem.to_string_lossy()),
+            "sources": sections.remove("sources").unwrap_or_default(),
+            "operators": sections.remove("operators").unwrap_or_default(),
+            "sinks": sections.remove("sinks").unwrap_or_default(),
+            "links": links,
+            "mapping": mapping,
+        }))
+    }
+
+    /// Loads and connects the nodes of the data flow, in a runtime of its own.
+    async fn instantiate(&self, flattened: Value) -> Result<DataFlowInstance> {
+        let (context, hlc) = zf_test_harness::runtime_context(RUNTIME).await?;
+        let descriptor: FlattenDataFlowDescriptor = serde_json::from_value(flattened)?;
+        let record = DataFlowRecord::try_from((descriptor, Uuid::new_v4()))?;
+        let dataflow = DataFlow::try_new(record, context)?;
+        Ok(DataFlowInstance::try_instantiate(dataflow, hlc).await?)
+    }
+
+    /// Runs the data flow for `duration`, then stops it, the sources first.
+    ///
+    /// The nodes are dropped, and so write what they report when the flow is stopped, before this
+    /// returns.
+    pub async fn run(&self, duration: Duration) -> Result<()> {
+        let library_dir = match &self.library_dir {
+            Some(library_dir) => library_dir.clone(),
+            None => profile_dir()?,
+        };
+        let flattened = self
+            .flatten(&library_dir)
+            .with_context(|| format!("Could not load {}", self.flow.display()))?;
+        let mut instance = self.instantiate(flattened).await?;
+
+        let sinks = instance.get_sinks();
+        let operators = instance.get_operators();
+        let sources = instance.get_sources();
+        for id in sinks.iter().chain(&operators).chain(&sources) {
+            instance.start_node(id)?;
+        }
+
+        async_std::task::sleep(duration).await;
+
+        for id in sources.iter().chain(&operators).chain(&sinks) {
+            instance.stop_node(id).await?;
+        }
+        drop(instance);
+        Ok(())
+    }
+}
@@ -0,0 +1,105 @@
+//
+// Copyright (c) 2022 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+//! Runs montblanc.yml for a few seconds and checks the strings Arequipa received.
+//!
+//! The shared libraries of the nodes must be built first, which `cargo test --workspace` does: the
+//! runner refuses the libraries older than their sources.
+
+use datatypes::payload;
+use runner::Runner;
+use serde_json::json;
+use std::fs;
+use std::path::Path;
+use std::time::Duration;
+
+const DURATION: Duration = Duration::from_secs(5);
+
+/// The size of the strings sent by Portsmouth, its path and its payload.
+const PAYLOAD_BYTES: usize = 64;
+
+/// The nodes and outputs the strings received by Arequipa went through, from the last to the first.
+static ARKANSAS_PATH: &str = "geneva/arkansas:hamburg/parana:portsmouth/danube";
+
+#[async_std::test]
+async fn arequipa_receives_well_formed_strings() -> anyhow::Result<()> {
+    let workspace = Path::new(env!("CARGO_MANIFEST_DIR"))
+        .parent()
+        .expect("The runner is in the Montblanc workspace");
+    let dir = std::env::temp_dir().join(format!("montblanc-runner-{}", std::process::id()));
+    fs::create_dir_all(&dir)?;
+    let out_file = dir.join("montblanc.out");
+    let latency_file = dir.join("montblanc-latency.out");
+
+    Runner::new(workspace.join("montblanc.yml"))
+        .var("BASE_DIR", workspace.to_string_lossy())
+        .configure("Portsmouth", json!({ "payload_bytes": PAYLOAD_BYTES }))
+        .configure(
+            "Arequipa",
+            json!({
+                "out_file": out_file,
+                "latency_file": latency_file,
+                "check": true,
+            }),
+        )
+        .run(DURATION)
+        .await?;
+
+    let received = fs::read_to_string(&out_file)?;
+    let latencies = fs::read_to_string(&latency_file)?;
+    fs::remove_dir_all(&dir)?;
+
+    let strings = received.lines().collect::<Vec<_>>();
+    assert!(
+        !strings.is_empty(),
+        "Arequipa received nothing in {:?}",
+        DURATION
+    );
+    for string in strings {
+        let (path, payload) = string
+            .split_once(' ')
+            .unwrap_or_else(|| panic!("No payload in {:?}", string));
+        assert_eq!(path, ARKANSAS_PATH);
+        assert_eq!(payload.len(), PAYLOAD_BYTES - "portsmouth/danube ".len());
+        assert!(
+            payload.chars().all(|c| c.is_ascii_alphanumeric()),
+            "Unexpected payload: {:?}",
+            payload
+        );
+        assert!(
+            payload::is_intact(payload),
+            "Altered payload: {:?}",
+            payload
+        );
+    }
+    assert!(
+        latencies.contains(ARKANSAS_PATH),
+        "No latencies for {} in:\n{}",
+        ARKANSAS_PATH,
+        latencies
+    );
+    // The checks are reported after the latencies: all the strings checked must have passed.
+    let checks = latencies.lines().last().unwrap_or_default();
+    let counts = checks
+        .split('\t')
+        .map(str::parse::<usize>)
+        .collect::<Result<Vec<_>, _>>()?;
+    assert!(
+        counts.len() == 3 && counts[0] > 0 && counts[1..] == [0, 0],
+        "Unexpected checks in:\n{}",
+        latencies
+    );
+
+    Ok(())
+}
@@ -13,46 +13,30 @@
 //
 
 use datatypes::data_types;
+use datatypes::stats::Statistics;
+use datatypes::Ports;
 use datatypes::{COLORADO_PORT, COLUMBIA_PORT};
-use prost::Message;
 use zenoh_flow::prelude::*;
 
 #[export_operator]
+#[derive(Ports)]
 pub struct Taipei {
+    #[input(COLUMBIA_PORT)]
     input: Input<data_types::Image>,
+    #[output(COLORADO_PORT)]
     output: Output<data_types::Image>,
-}
-
-#[async_trait::async_trait]
-impl Operator for Taipei {
-    async fn new(
-        _context: Context,
-        _configuration: Option<Configuration>,
-        mut inputs: Inputs,
-        mut outputs: Outputs,
-    ) -> Result<Self> {
-        Ok(Self {
-            input: inputs
-                .take(COLUMBIA_PORT)
-                .unwrap_or_else(|| panic!("No Input called '{}' found", COLUMBIA_PORT))
-                .typed(|buf| Ok(data_types::Image::decode(buf)?)),
-            output: outputs
-                .take(COLORADO_PORT)
-                .unwrap_or_else(|| panic!("No Output called '{}' found", COLORADO_PORT))
-                .typed(|buf, v: &data_types::Image| {
-                    buf.resize(v.encoded_len(), 0);
-                    Ok(v.encode(buf)?)
-                }),
-        })
-    }
+    #[statistics]
+    statistics: Statistics,
 }
 
 #[async_trait::async_trait]
 impl Node for Taipei {
     async fn iteration(&self) -> Result<()> {
-        let (msg, _ts) = self.input.recv().await?;
+        let (msg, ts) = self.input.recv().await?;
         if let zenoh_flow::prelude::Message::Data(data) = msg {
-            self.output.send(data, None).await?;
+            self.statistics.received(COLUMBIA_PORT);
+            self.statistics.sent(COLORADO_PORT);
+            self.output.send(data, Some(ts.get_time().as_u64())).await?;
         }
         Ok(())
     }
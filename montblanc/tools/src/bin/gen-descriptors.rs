@@ -0,0 +1,149 @@
+//
+// Copyright (c) 2022 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+use anyhow::{Context, Result};
+use clap::Parser;
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use tools::nodes;
+
+/// Generates the YAML descriptor of every node of a Cargo workspace, from the ports declared in
+/// its sources.
+///
+/// The descriptor of a node is written next to its `Cargo.toml`, as `<crate>/<crate>.yml` (or
+/// `<crate>/<crate>.yaml` if such a file already exists), unless `--output-dir` is provided. The
+/// `vars` and the `configuration` of an existing descriptor are preserved.
+///
+/// With `--base-dir`, the libraries are located from the `BASE_DIR` variable of the data flow
+/// rather than with an absolute path, so that the descriptors do not depend on the machine they
+/// were generated on.
+#[derive(Parser)]
+struct Args {
+    /// The directory of the Cargo workspace.
+    #[arg(short, long, default_value = ".")]
+    workspace: PathBuf,
+    /// The target directory where the shared libraries are built, defaults to
+    /// `<workspace>/target`.
+    #[arg(short, long)]
+    target_dir: Option<PathBuf>,
+    /// Point to the libraries built in release mode.
+    #[arg(short, long)]
+    release: bool,
+    /// The directory `{{ BASE_DIR }}` stands for in the data flow: the libraries are then located
+    /// relatively to it.
+    #[arg(short, long)]
+    base_dir: Option<PathBuf>,
+    /// Where to write all the descriptors.
+    #[arg(short, long)]
+    output_dir: Option<PathBuf>,
+    /// The file declaring the port constants the nodes refer to.
+    #[arg(long, default_value = "datatypes/src/lib.rs")]
+    constants: PathBuf,
+}
+
+/// Returns the `vars` and `configuration` sections of the descriptor located at `path`, if any.
+fn existing_sections(path: &Path) -> Result<serde_yaml::Mapping> {
+    let mut sections = serde_yaml::Mapping::new();
+    if !path.exists() {
+        return Ok(sections);
+    }
+
+    let descriptor: serde_yaml::Value = serde_yaml::from_str(&fs::read_to_string(path)?)
+        .with_context(|| format!("Could not parse {}", path.display()))?;
+    for key in ["vars", "configuration"] {
+        if let Some(section) = descriptor.get(key) {
+            sections.insert(key.into(), section.clone());
+        }
+    }
+    Ok(sections)
+}
+
+fn main() -> Result<()> {
+    let args = Args::parse();
+
+    let workspace = args
+        .workspace
+        .canonicalize()
+        .with_context(|| format!("Could not find {}", args.workspace.display()))?;
+
+    let constants_path = workspace.join(&args.constants);
+    let constants = if constants_path.exists() {
+        nodes::port_constants(&nodes::parse_file(&constants_path)?)
+    } else {
+        HashMap::new()
+    };
+
+    let target_dir = match args.target_dir {
+        Some(target_dir) => target_dir,
+        None => workspace.join("target"),
+    };
+    let library_dir = target_dir.join(if args.release { "release" } else { "debug" });
+    let library_dir = if library_dir.is_absolute() {
+        library_dir
+    } else {
+        std::env::current_dir()?.join(library_dir)
+    };
+    let library_dir = match args.base_dir {
+        Some(base_dir) => {
+            let base_dir = base_dir
+                .canonicalize()
+                .with_context(|| format!("Could not find {}", base_dir.display()))?;
+            let relative = library_dir.strip_prefix(&base_dir).with_context(|| {
+                format!(
+                    "{} is not within {}",
+                    library_dir.display(),
+                    base_dir.display()
+                )
+            })?;
+            Path::new("{{ BASE_DIR }}").join(relative)
+        }
+        None => library_dir,
+    };
+
+    for crate_dir in nodes::workspace_members(&workspace)? {
+        let node = match nodes::scan_crate(&crate_dir, &constants)? {
+            Some(node) => node,
+            None => continue,
+        };
+
+        let crate_name = crate_dir
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_else(|| node.library.clone());
+        let path = match &args.output_dir {
+            Some(output_dir) => output_dir.join(format!("{}.yml", crate_name)),
+            None => {
+                let yaml = crate_dir.join(format!("{}.yaml", crate_name));
+                if yaml.exists() {
+                    yaml
+                } else {
+                    crate_dir.join(format!("{}.yml", crate_name))
+                }
+            }
+        };
+
+        let mut descriptor = node.descriptor(&library_dir);
+        let sections = existing_sections(&path)?;
+        if !sections.is_empty() {
+            descriptor.push_str(&serde_yaml::to_string(&sections)?);
+        }
+
+        fs::write(&path, descriptor)
+            .with_context(|| format!("Could not write {}", path.display()))?;
+        println!("{} -> {}", node.id, path.display());
+    }
+
+    Ok(())
+}
@@ -0,0 +1,169 @@
+//
+// Copyright (c) 2022 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+use anyhow::{anyhow, bail, Context, Result};
+use clap::Parser;
+use std::fs;
+use std::path::{Path, PathBuf};
+use tools::topology;
+
+/// Generates, from the description of a topology, the crate of each of its nodes, their
+/// descriptors and the data flow.
+///
+/// The crates are members of a new Cargo workspace in `--output-dir`, sharing the dependencies and
+/// the profiles of `--workspace`, where `datatypes` is found. The crate of a node is written in
+/// `<output-dir>/<crate>`, with its descriptor as `<crate>/<crate>.yml`, and the data flow in
+/// `<output-dir>/<name>.yml`.
+#[derive(Parser)]
+struct Args {
+    /// The description of the topology.
+    topology: PathBuf,
+    /// Where to write the workspace.
+    #[arg(short, long)]
+    output_dir: PathBuf,
+    /// The number of copies of the topology in the data flow, written in
+    /// `<output-dir>/<name>-x<scale>.yml` when greater than 1.
+    #[arg(short, long, default_value_t = 1)]
+    scale: usize,
+    /// The directory of the Montblanc workspace.
+    #[arg(short, long, default_value = ".")]
+    workspace: PathBuf,
+    /// The target directory where the shared libraries are built, defaults to
+    /// `<output-dir>/target`.
+    #[arg(short, long)]
+    target_dir: Option<PathBuf>,
+    /// Point to the libraries built in release mode.
+    #[arg(short, long)]
+    release: bool,
+}
+
+fn absolute(path: &Path) -> Result<PathBuf> {
+    Ok(if path.is_absolute() {
+        path.to_path_buf()
+    } else {
+        std::env::current_dir()?.join(path)
+    })
+}
+
+/// The manifest of the generated workspace, with the dependencies and the profiles of the
+/// `workspace` one.
+fn workspace_manifest(workspace: &Path, members: &[String]) -> Result<String> {
+    let path = workspace.join("Cargo.toml");
+    let manifest = fs::read_to_string(&path)
+        .with_context(|| format!("Could not read {}", path.display()))?
+        .parse::<toml::Value>()
+        .with_context(|| format!("Could not parse {}", path.display()))?;
+
+    let mut section = toml::value::Table::new();
+    section.insert(
+        "members".to_string(),
+        toml::Value::Array(members.iter().cloned().map(toml::Value::String).collect()),
+    );
+    if let Some(dependencies) = manifest
+        .get("workspace")
+        .and_then(|workspace| workspace.get("dependencies"))
+    {
+        section.insert("dependencies".to_string(), dependencies.clone());
+    }
+    let mut workspace = toml::value::Table::new();
+    workspace.insert("workspace".to_string(), toml::Value::Table(section));
+    let mut generated = toml::to_string(&workspace)?;
+
+    if let Some(profile) = manifest.get("profile") {
+        let mut profiles = toml::value::Table::new();
+        profiles.insert("profile".to_string(), profile.clone());
+        generated.push('\n');
+        generated.push_str(&toml::to_string(&profiles)?);
+    }
+    Ok(generated)
+}
+
+fn write(path: &Path, content: &str) -> Result<()> {
+    fs::write(path, content).with_context(|| format!("Could not write {}", path.display()))
+}
+
+fn main() -> Result<()> {
+    let args = Args::parse();
+    if args.scale == 0 {
+        bail!("The scale must be at least 1");
+    }
+
+    let content = fs::read_to_string(&args.topology)
+        .with_context(|| format!("Could not read {}", args.topology.display()))?;
+    let topology = topology::parse(&content)
+        .map_err(|e| anyhow!("{}:{}: {}", args.topology.display(), e.line, e.message))?;
+
+    let workspace = args
+        .workspace
+        .canonicalize()
+        .with_context(|| format!("Could not find {}", args.workspace.display()))?;
+    let datatypes = workspace.join("datatypes");
+    if !datatypes.join("Cargo.toml").exists() {
+        bail!(
+            "Could not find the datatypes crate in {}",
+            workspace.display()
+        );
+    }
+
+    fs::create_dir_all(&args.output_dir)
+        .with_context(|| format!("Could not create {}", args.output_dir.display()))?;
+    let output_dir = args.output_dir.canonicalize()?;
+    let target_dir = match &args.target_dir {
+        Some(target_dir) => absolute(target_dir)?,
+        None => output_dir.join("target"),
+    };
+    let library_dir = target_dir.join(if args.release { "release" } else { "debug" });
+
+    let mut members = Vec::new();
+    for node in &topology.nodes {
+        let crate_dir = output_dir.join(node.crate_name());
+        fs::create_dir_all(crate_dir.join("src"))
+            .with_context(|| format!("Could not create {}", crate_dir.display()))?;
+        write(&crate_dir.join("Cargo.toml"), &node.manifest(&datatypes))?;
+        write(
+            &crate_dir.join("src").join("lib.rs"),
+            &node.source(&topology.name),
+        )?;
+        write(
+            &crate_dir.join(format!("{}.yml", node.crate_name())),
+            &node.definition().descriptor(&library_dir),
+        )?;
+        println!("{} -> {}", node.name, crate_dir.display());
+        members.push(node.crate_name());
+    }
+    write(
+        &output_dir.join("Cargo.toml"),
+        &workspace_manifest(&workspace, &members)?,
+    )?;
+
+    let flow = if args.scale > 1 {
+        output_dir.join(format!(
+            "{}-x{}.yml",
+            topology.name.to_lowercase(),
+            args.scale
+        ))
+    } else {
+        output_dir.join(format!("{}.yml", topology.name.to_lowercase()))
+    };
+    write(&flow, &topology.flow(args.scale, &output_dir))?;
+    println!(
+        "{} x{} ({} nodes) -> {}",
+        topology.name,
+        args.scale,
+        topology.nodes.len() * args.scale,
+        flow.display()
+    );
+
+    Ok(())
+}
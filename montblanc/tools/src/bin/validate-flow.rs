@@ -0,0 +1,80 @@
+//
+// Copyright (c) 2022 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+use anyhow::{anyhow, Result};
+use clap::Parser;
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::process::ExitCode;
+use tools::flow::{self, Severity};
+
+/// Checks a data flow against the descriptors of its nodes, without launching it.
+///
+/// The links between the ports of `datatypes` are also checked to connect ports of the same type,
+/// and nodes using the same codec.
+#[derive(Parser)]
+struct Args {
+    /// The data flow files to check.
+    #[arg(required = true)]
+    flows: Vec<PathBuf>,
+    /// Overrides a variable of the data flow, e.g. `--var BASE_DIR=$PWD`.
+    #[arg(short, long = "var", value_parser = parse_var)]
+    vars: Vec<(String, String)>,
+    /// Treat warnings as errors.
+    #[arg(short = 'W', long)]
+    deny_warnings: bool,
+}
+
+fn parse_var(var: &str) -> Result<(String, String)> {
+    let (name, value) = var
+        .split_once('=')
+        .ok_or_else(|| anyhow!("expected `NAME=VALUE`, found `{}`", var))?;
+    Ok((name.trim().to_string(), value.to_string()))
+}
+
+fn main() -> ExitCode {
+    let args = Args::parse();
+    let vars = args.vars.into_iter().collect::<HashMap<_, _>>();
+
+    let mut failed = false;
+    for path in &args.flows {
+        let mut diagnostics = Vec::new();
+        if let Some(flow) = flow::load(path, &vars, &mut diagnostics) {
+            diagnostics.extend(flow::validate(&flow));
+            diagnostics.extend(flow::check_types(&flow, datatypes::port_type));
+            diagnostics.extend(flow::check_codecs(&flow));
+        }
+
+        for diagnostic in &diagnostics {
+            eprintln!("{}", diagnostic);
+        }
+
+        let deny_warnings = args.deny_warnings;
+        let errors = diagnostics
+            .iter()
+            .filter(|d| d.severity == Severity::Error || deny_warnings)
+            .count();
+        if errors > 0 {
+            failed = true;
+        } else {
+            println!("{}: OK", path.display());
+        }
+    }
+
+    if failed {
+        ExitCode::FAILURE
+    } else {
+        ExitCode::SUCCESS
+    }
+}
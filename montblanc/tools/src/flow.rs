@@ -0,0 +1,633 @@
+//
+// Copyright (c) 2022 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+use crate::nodes::NodeKind;
+use crate::yaml::{self, Node};
+use datatypes::codec::Codec;
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Severity {
+    Error,
+    Warning,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    pub file: PathBuf,
+    pub line: usize,
+    pub message: String,
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let severity = match self.severity {
+            Severity::Error => "error",
+            Severity::Warning => "warning",
+        };
+        // Problems that are not located in the file, e.g. when it cannot be read, have no line.
+        if self.line == 0 {
+            write!(f, "{}: {}: {}", self.file.display(), severity, self.message)
+        } else {
+            write!(
+                f,
+                "{}:{}: {}: {}",
+                self.file.display(),
+                self.line,
+                severity,
+                self.message
+            )
+        }
+    }
+}
+
+/// A port of a node, with the line where it is declared.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Port {
+    pub id: String,
+    pub line: usize,
+}
+
+/// A node of a data flow, with the ports found in its descriptor.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FlowNode {
+    pub id: String,
+    pub kind: NodeKind,
+    pub line: usize,
+    /// The file where the ports are declared: the descriptor of the node or, for built-in nodes,
+    /// the data flow itself.
+    pub ports_file: PathBuf,
+    pub inputs: Vec<Port>,
+    pub outputs: Vec<Port>,
+    /// Whether the ports could be resolved. The links of unresolved nodes are not checked.
+    pub resolved: bool,
+    /// Whether the node is built in Zenoh-Flow, e.g. `builtin://zenoh`, rather than loaded from a
+    /// library.
+    pub builtin: bool,
+    /// The `configuration` of the node in the data flow, with its variables expanded.
+    pub configuration: Option<serde_json::Value>,
+    /// The codec of the messages of the node, as set in its configuration. Built-in nodes forward
+    /// messages as they are and have none.
+    pub codec: Option<String>,
+}
+
+/// One end of a link: a node and one of its ports.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Endpoint {
+    pub node: String,
+    pub port: String,
+    pub line: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Link {
+    pub from: Endpoint,
+    pub to: Endpoint,
+    pub line: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Flow {
+    pub path: PathBuf,
+    pub nodes: Vec<FlowNode>,
+    pub links: Vec<Link>,
+}
+
+/// Accumulates the diagnostics of a file.
+struct Reporter<'a> {
+    file: PathBuf,
+    diagnostics: &'a mut Vec<Diagnostic>,
+}
+
+impl<'a> Reporter<'a> {
+    fn report(&mut self, severity: Severity, line: usize, message: String) {
+        self.diagnostics.push(Diagnostic {
+            severity,
+            file: self.file.clone(),
+            line,
+            message,
+        });
+    }
+
+    fn error(&mut self, line: usize, message: String) {
+        self.report(Severity::Error, line, message);
+    }
+
+    fn yaml_error(&mut self, error: yaml::Error) {
+        self.error(error.line, error.message);
+    }
+}
+
+/// Reads the YAML file at `path` and expands its variables.
+///
+/// The variables declared in the file are overridden by `vars`.
+fn load_yaml(
+    path: &Path,
+    vars: &HashMap<String, String>,
+    diagnostics: &mut Vec<Diagnostic>,
+) -> Option<(Node, HashMap<String, String>)> {
+    let mut reporter = Reporter {
+        file: path.to_path_buf(),
+        diagnostics,
+    };
+
+    let content = match fs::read_to_string(path) {
+        Ok(content) => content,
+        Err(e) => {
+            reporter.error(0, format!("could not read file: {}", e));
+            return None;
+        }
+    };
+
+    let mut all_vars = match yaml::parse(&content) {
+        Ok(raw) => yaml::vars(&raw),
+        Err(e) => {
+            reporter.yaml_error(e);
+            return None;
+        }
+    };
+    all_vars.extend(vars.iter().map(|(k, v)| (k.clone(), v.clone())));
+
+    let tree = yaml::expand_vars(&content, &all_vars).and_then(|content| yaml::parse(&content));
+    match tree {
+        Ok(tree) => Some((tree, all_vars)),
+        Err(e) => {
+            reporter.yaml_error(e);
+            None
+        }
+    }
+}
+
+fn ports(node: Option<&Node>, reporter: &mut Reporter) -> Vec<Port> {
+    let node = match node {
+        Some(node) => node,
+        None => return Vec::new(),
+    };
+    let sequence = match node.as_sequence() {
+        Some(sequence) => sequence,
+        None => {
+            reporter.error(node.line, "expected a list of ports".to_string());
+            return Vec::new();
+        }
+    };
+
+    sequence
+        .iter()
+        .filter_map(|port| {
+            // Composite operators declare their ports as mappings with an `id`.
+            let id = port
+                .as_str()
+                .or_else(|| port.get("id").and_then(Node::as_str));
+            match id {
+                Some(id) => Some(Port {
+                    id: id.to_string(),
+                    line: port.line,
+                }),
+                None => {
+                    reporter.error(port.line, "invalid port".to_string());
+                    None
+                }
+            }
+        })
+        .collect()
+}
+
+/// Returns the ports of the node declared in `entry`, either from its descriptor or, for the
+/// built-in Zenoh nodes, from its `key-expressions`.
+fn resolve_node(
+    entry: &Node,
+    kind: NodeKind,
+    flow_path: &Path,
+    vars: &HashMap<String, String>,
+    reporter: &mut Reporter,
+) -> Option<(PathBuf, Vec<Port>, Vec<Port>)> {
+    let descriptor = match entry.get("descriptor") {
+        Some(descriptor) => descriptor,
+        None => {
+            reporter.error(entry.line, "missing `descriptor`".to_string());
+            return None;
+        }
+    };
+    let uri = descriptor.as_str().unwrap_or_default();
+
+    if uri == "builtin://zenoh" {
+        let key_expressions = entry
+            .get("configuration")
+            .and_then(|configuration| configuration.get("key-expressions"));
+        let ports = key_expressions
+            .and_then(Node::as_mapping)
+            .map(|entries| {
+                entries
+                    .iter()
+                    .filter_map(|(k, _)| {
+                        Some(Port {
+                            id: k.as_str()?.to_string(),
+                            line: k.line,
+                        })
+                    })
+                    .collect::<Vec<_>>()
+            })
+            .unwrap_or_default();
+        if ports.is_empty() {
+            reporter.error(
+                descriptor.line,
+                "a built-in Zenoh node requires `configuration.key-expressions`".to_string(),
+            );
+        }
+
+        return match kind {
+            NodeKind::Source => Some((flow_path.to_path_buf(), Vec::new(), ports)),
+            NodeKind::Sink => Some((flow_path.to_path_buf(), ports, Vec::new())),
+            NodeKind::Operator => {
+                reporter.error(
+                    descriptor.line,
+                    "`builtin://zenoh` can only be used by sources and sinks".to_string(),
+                );
+                None
+            }
+        };
+    }
+
+    let path = match uri.strip_prefix("file://") {
+        Some(path) => flow_path
+            .parent()
+            .unwrap_or_else(|| Path::new("."))
+            .join(path),
+        None => {
+            reporter.error(descriptor.line, format!("unsupported descriptor `{}`", uri));
+            return None;
+        }
+    };
+
+    let (tree, _) = match load_yaml(&path, vars, reporter.diagnostics) {
+        Some(loaded) => loaded,
+        None => {
+            reporter.error(
+                descriptor.line,
+                format!("could not load descriptor `{}`", path.display()),
+            );
+            return None;
+        }
+    };
+
+    let mut descriptor_reporter = Reporter {
+        file: path.clone(),
+        diagnostics: reporter.diagnostics,
+    };
+    let inputs = ports(tree.get("inputs"), &mut descriptor_reporter);
+    let outputs = ports(tree.get("outputs"), &mut descriptor_reporter);
+    Some((path, inputs, outputs))
+}
+
+fn endpoint(link: &Node, side: &str, port_key: &str, reporter: &mut Reporter) -> Option<Endpoint> {
+    let end = match link.get(side) {
+        Some(end) => end,
+        None => {
+            reporter.error(link.line, format!("link without `{}`", side));
+            return None;
+        }
+    };
+
+    let node = end.get("node").and_then(Node::as_str);
+    let port = end.get(port_key);
+    match (node, port.and_then(Node::as_str)) {
+        (Some(node), Some(id)) => Some(Endpoint {
+            node: node.to_string(),
+            port: id.to_string(),
+            line: port.map_or(end.line, |port| port.line),
+        }),
+        _ => {
+            reporter.error(
+                end.line,
+                format!("`{}` requires both `node` and `{}`", side, port_key),
+            );
+            None
+        }
+    }
+}
+
+/// Loads the data flow at `path` and the descriptors of its nodes.
+///
+/// The variables of the data flow, and of the descriptors, are overridden by `vars`. Problems
+/// preventing the data flow from being loaded are pushed to `diagnostics`.
+pub fn load(
+    path: &Path,
+    vars: &HashMap<String, String>,
+    diagnostics: &mut Vec<Diagnostic>,
+) -> Option<Flow> {
+    let (tree, flow_vars) = load_yaml(path, vars, diagnostics)?;
+    let mut reporter = Reporter {
+        file: path.to_path_buf(),
+        diagnostics,
+    };
+
+    let mut nodes = Vec::new();
+    for (section, kind) in [
+        ("sources", NodeKind::Source),
+        ("operators", NodeKind::Operator),
+        ("sinks", NodeKind::Sink),
+    ] {
+        let entries = match tree.get(section) {
+            Some(entries) => entries,
+            None => continue,
+        };
+        let entries = match entries.as_sequence() {
+            Some(entries) => entries,
+            None => {
+                reporter.error(entries.line, format!("`{}` must be a list", section));
+                continue;
+            }
+        };
+
+        for entry in entries {
+            let id = match entry.get("id").and_then(Node::as_str) {
+                Some(id) => id.to_string(),
+                None => {
+                    reporter.error(entry.line, "missing `id`".to_string());
+                    continue;
+                }
+            };
+
+            let builtin = entry
+                .get("descriptor")
+                .and_then(Node::as_str)
+                .map_or(false, |uri| uri.starts_with("builtin://"));
+            let configuration = entry.get("configuration").map(Node::to_json);
+            let codec = if builtin {
+                None
+            } else {
+                Some(
+                    entry
+                        .get("configuration")
+                        .and_then(|configuration| configuration.get("codec"))
+                        .and_then(Node::as_str)
+                        .unwrap_or_else(|| Codec::default().name())
+                        .to_string(),
+                )
+            };
+
+            let node = match resolve_node(entry, kind, path, &flow_vars, &mut reporter) {
+                Some((ports_file, inputs, outputs)) => FlowNode {
+                    id,
+                    kind,
+                    line: entry.line,
+                    ports_file,
+                    inputs,
+                    outputs,
+                    resolved: true,
+                    codec,
+                    builtin,
+                    configuration,
+                },
+                None => FlowNode {
+                    id,
+                    kind,
+                    line: entry.line,
+                    ports_file: path.to_path_buf(),
+                    inputs: Vec::new(),
+                    outputs: Vec::new(),
+                    resolved: false,
+                    codec,
+                    builtin,
+                    configuration,
+                },
+            };
+            nodes.push(node);
+        }
+    }
+
+    let mut links = Vec::new();
+    if let Some(entries) = tree.get("links") {
+        match entries.as_sequence() {
+            Some(entries) => {
+                for link in entries {
+                    let from = endpoint(link, "from", "output", &mut reporter);
+                    let to = endpoint(link, "to", "input", &mut reporter);
+                    if let (Some(from), Some(to)) = (from, to) {
+                        links.push(Link {
+                            from,
+                            to,
+                            line: link.line,
+                        });
+                    }
+                }
+            }
+            None => reporter.error(entries.line, "`links` must be a list".to_string()),
+        }
+    }
+
+    Some(Flow {
+        path: path.to_path_buf(),
+        nodes,
+        links,
+    })
+}
+
+/// Checks the consistency of the nodes and links of a data flow:
+/// - node ids are unique,
+/// - sources have no inputs and sinks no outputs,
+/// - links connect existing ports of existing nodes,
+/// - every input receives data from exactly one output,
+/// - every output is connected (a warning is emitted otherwise).
+pub fn validate(flow: &Flow) -> Vec<Diagnostic> {
+    let mut diagnostics = Vec::new();
+    let mut reporter = Reporter {
+        file: flow.path.clone(),
+        diagnostics: &mut diagnostics,
+    };
+
+    let mut nodes: HashMap<&str, &FlowNode> = HashMap::new();
+    for node in &flow.nodes {
+        if let Some(first) = nodes.get(node.id.as_str()) {
+            reporter.error(
+                node.line,
+                format!(
+                    "duplicate node id `{}` (first declared at line {})",
+                    node.id, first.line
+                ),
+            );
+            continue;
+        }
+        nodes.insert(&node.id, node);
+
+        let unexpected = match node.kind {
+            NodeKind::Source => node.inputs.first().map(|port| ("source", "input", port)),
+            NodeKind::Sink => node.outputs.first().map(|port| ("sink", "output", port)),
+            NodeKind::Operator => None,
+        };
+        if let Some((kind, direction, port)) = unexpected {
+            reporter.diagnostics.push(Diagnostic {
+                severity: Severity::Error,
+                file: node.ports_file.clone(),
+                line: port.line,
+                message: format!(
+                    "{} `{}` declares the {} `{}`",
+                    kind, node.id, direction, port.id
+                ),
+            });
+        }
+    }
+
+    let mut connected_inputs: HashMap<(&str, &str), usize> = HashMap::new();
+    let mut connected_outputs: HashSet<(&str, &str)> = HashSet::new();
+
+    for link in &flow.links {
+        let from = nodes.get(link.from.node.as_str());
+        let to = nodes.get(link.to.node.as_str());
+
+        match from {
+            None => reporter.error(link.from.line, format!("unknown node `{}`", link.from.node)),
+            Some(node)
+                if node.resolved && !node.outputs.iter().any(|port| port.id == link.from.port) =>
+            {
+                reporter.error(
+                    link.from.line,
+                    format!(
+                        "unknown output `{}` of node `{}`",
+                        link.from.port, link.from.node
+                    ),
+                )
+            }
+            Some(_) => {
+                connected_outputs.insert((&link.from.node, &link.from.port));
+            }
+        }
+
+        match to {
+            None => reporter.error(link.to.line, format!("unknown node `{}`", link.to.node)),
+            Some(node)
+                if node.resolved && !node.inputs.iter().any(|port| port.id == link.to.port) =>
+            {
+                reporter.error(
+                    link.to.line,
+                    format!(
+                        "unknown input `{}` of node `{}`",
+                        link.to.port, link.to.node
+                    ),
+                )
+            }
+            Some(_) => {
+                if let Some(first) =
+                    connected_inputs.insert((&link.to.node, &link.to.port), link.line)
+                {
+                    reporter.error(
+                        link.to.line,
+                        format!(
+                            "input `{}` of node `{}` is already connected (line {}): fan-in is \
+                             not supported",
+                            link.to.port, link.to.node, first
+                        ),
+                    );
+                    connected_inputs.insert((&link.to.node, &link.to.port), first);
+                }
+            }
+        }
+    }
+
+    for node in nodes.values() {
+        for input in &node.inputs {
+            if !connected_inputs.contains_key(&(node.id.as_str(), input.id.as_str())) {
+                reporter.error(
+                    node.line,
+                    format!(
+                        "input `{}` of node `{}` is not connected",
+                        input.id, node.id
+                    ),
+                );
+            }
+        }
+        for output in &node.outputs {
+            if !connected_outputs.contains(&(node.id.as_str(), output.id.as_str())) {
+                reporter.report(
+                    Severity::Warning,
+                    node.line,
+                    format!(
+                        "output `{}` of node `{}` is not connected",
+                        output.id, node.id
+                    ),
+                );
+            }
+        }
+    }
+
+    diagnostics.sort_by(|a, b| (&a.file, a.line).cmp(&(&b.file, b.line)));
+    diagnostics
+}
+
+/// Checks that the ports connected by each link exchange the same type of messages.
+///
+/// `port_type` returns the type of a port from its name; the links involving ports of unknown
+/// type are not checked.
+pub fn check_types<'a>(
+    flow: &Flow,
+    port_type: impl Fn(&str) -> Option<&'a str>,
+) -> Vec<Diagnostic> {
+    let mut diagnostics = Vec::new();
+    let mut reporter = Reporter {
+        file: flow.path.clone(),
+        diagnostics: &mut diagnostics,
+    };
+
+    for link in &flow.links {
+        if let (Some(from), Some(to)) = (port_type(&link.from.port), port_type(&link.to.port)) {
+            if from != to {
+                reporter.error(
+                    link.line,
+                    format!(
+                        "`{}.{}` sends `{}` messages but `{}.{}` expects `{}`",
+                        link.from.node, link.from.port, from, link.to.node, link.to.port, to
+                    ),
+                );
+            }
+        }
+    }
+
+    diagnostics
+}
+
+/// Checks that the nodes connected by each link encode their messages with the same codec.
+pub fn check_codecs(flow: &Flow) -> Vec<Diagnostic> {
+    let mut diagnostics = Vec::new();
+    let mut reporter = Reporter {
+        file: flow.path.clone(),
+        diagnostics: &mut diagnostics,
+    };
+
+    let codecs = flow
+        .nodes
+        .iter()
+        .filter_map(|node| Some((node.id.as_str(), node.codec.as_deref()?)))
+        .collect::<HashMap<_, _>>();
+    for link in &flow.links {
+        let from = codecs.get(link.from.node.as_str());
+        let to = codecs.get(link.to.node.as_str());
+        if let (Some(from), Some(to)) = (from, to) {
+            if from != to {
+                reporter.error(
+                    link.line,
+                    format!(
+                        "`{}` encodes its messages with `{}` but `{}` decodes them with `{}`",
+                        link.from.node, from, link.to.node, to
+                    ),
+                );
+            }
+        }
+    }
+
+    diagnostics
+}
@@ -0,0 +1,348 @@
+//
+// Copyright (c) 2022 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+use anyhow::{anyhow, bail, Context, Result};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use syn::visit::Visit;
+use syn::{Expr, ExprCall, ExprMethodCall, Item, ItemStruct, Lit};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NodeKind {
+    Source,
+    Operator,
+    Sink,
+}
+
+/// A zenoh-flow node, as declared in the sources of its crate.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NodeDefinition {
+    /// The name of the structure exported with `#[export_source]`, `#[export_operator]` or
+    /// `#[export_sink]`.
+    pub id: String,
+    pub kind: NodeKind,
+    /// The name of the library, as found in the name of the shared library.
+    pub library: String,
+    pub inputs: Vec<String>,
+    pub outputs: Vec<String>,
+}
+
+impl NodeDefinition {
+    /// The file name of the shared library of this node on the current platform.
+    pub fn library_file_name(&self) -> String {
+        format!(
+            "{}{}{}",
+            std::env::consts::DLL_PREFIX,
+            self.library,
+            std::env::consts::DLL_SUFFIX
+        )
+    }
+
+    /// The YAML descriptor of this node, its shared library being located in `library_dir`.
+    pub fn descriptor(&self, library_dir: &Path) -> String {
+        let uri = format!(
+            "file://{}",
+            library_dir.join(self.library_file_name()).display()
+        );
+        let inputs = self.inputs.iter().map(String::as_str).collect::<Vec<_>>();
+        let outputs = self.outputs.iter().map(String::as_str).collect::<Vec<_>>();
+        datatypes::node_descriptor(&self.id, &uri, &inputs, &outputs)
+    }
+}
+
+fn read_manifest(path: &Path) -> Result<toml::Value> {
+    fs::read_to_string(path)
+        .with_context(|| format!("Could not read {}", path.display()))?
+        .parse::<toml::Value>()
+        .with_context(|| format!("Could not parse {}", path.display()))
+}
+
+/// Returns the directories of the members of the Cargo workspace located in `workspace`.
+pub fn workspace_members(workspace: &Path) -> Result<Vec<PathBuf>> {
+    let manifest = read_manifest(&workspace.join("Cargo.toml"))?;
+    let members = manifest
+        .get("workspace")
+        .and_then(|workspace| workspace.get("members"))
+        .and_then(|members| members.as_array())
+        .ok_or_else(|| anyhow!("{} does not declare a workspace", workspace.display()))?;
+
+    members
+        .iter()
+        .map(|member| {
+            member
+                .as_str()
+                .map(|member| workspace.join(member))
+                .ok_or_else(|| anyhow!("Invalid workspace member: {}", member))
+        })
+        .collect()
+}
+
+/// Collects the `&str` statics and constants declared in a file, such as the port constants of
+/// `datatypes`.
+pub fn port_constants(file: &syn::File) -> HashMap<String, String> {
+    let mut constants = HashMap::new();
+    for item in &file.items {
+        let (ident, expr) = match item {
+            Item::Static(item) => (&item.ident, &item.expr),
+            Item::Const(item) => (&item.ident, &item.expr),
+            _ => continue,
+        };
+        if let Expr::Lit(expr) = expr.as_ref() {
+            if let Lit::Str(value) = &expr.lit {
+                constants.insert(ident.to_string(), value.value());
+            }
+        }
+    }
+    constants
+}
+
+pub fn parse_file(path: &Path) -> Result<syn::File> {
+    let content =
+        fs::read_to_string(path).with_context(|| format!("Could not read {}", path.display()))?;
+    syn::parse_file(&content).with_context(|| format!("Could not parse {}", path.display()))
+}
+
+/// Returns the value of a port id: either a string literal or the name of a constant.
+fn resolve_port(expr: &Expr, constants: &HashMap<String, String>) -> Option<String> {
+    match expr {
+        Expr::Lit(expr) => match &expr.lit {
+            Lit::Str(value) => Some(value.value()),
+            _ => None,
+        },
+        Expr::Path(expr) => expr
+            .path
+            .segments
+            .last()
+            .and_then(|segment| constants.get(&segment.ident.to_string()).cloned()),
+        Expr::Reference(expr) => resolve_port(&expr.expr, constants),
+        _ => None,
+    }
+}
+
+fn push_unique(ports: &mut Vec<String>, port: String) {
+    if !ports.contains(&port) {
+        ports.push(port);
+    }
+}
+
+/// Finds the ports taken in the constructor of nodes that do not derive `Ports`, i.e. calls to
+/// `inputs.take(..)`, `outputs.take(..)`, `take_input(&mut inputs, .., codec, statistics)` and
+/// `take_output(&mut outputs, .., codec, statistics)`.
+struct TakenPorts<'a> {
+    constants: &'a HashMap<String, String>,
+    inputs: Vec<String>,
+    outputs: Vec<String>,
+    unresolved: Vec<String>,
+}
+
+impl<'a> TakenPorts<'a> {
+    fn record(&mut self, is_input: bool, expr: &Expr) {
+        match resolve_port(expr, self.constants) {
+            Some(port) if is_input => push_unique(&mut self.inputs, port),
+            Some(port) => push_unique(&mut self.outputs, port),
+            None => self.unresolved.push(quote_expr(expr)),
+        }
+    }
+}
+
+fn quote_expr(expr: &Expr) -> String {
+    match expr {
+        Expr::Path(expr) => expr
+            .path
+            .segments
+            .iter()
+            .map(|segment| segment.ident.to_string())
+            .collect::<Vec<_>>()
+            .join("::"),
+        _ => "<expression>".to_string(),
+    }
+}
+
+impl<'a, 'ast> Visit<'ast> for TakenPorts<'a> {
+    fn visit_expr_method_call(&mut self, call: &'ast ExprMethodCall) {
+        if call.method == "take" && call.args.len() == 1 {
+            if let Expr::Path(receiver) = call.receiver.as_ref() {
+                if receiver.path.is_ident("inputs") {
+                    self.record(true, &call.args[0]);
+                } else if receiver.path.is_ident("outputs") {
+                    self.record(false, &call.args[0]);
+                }
+            }
+        }
+        syn::visit::visit_expr_method_call(self, call);
+    }
+
+    fn visit_expr_call(&mut self, call: &'ast ExprCall) {
+        if let Expr::Path(function) = call.func.as_ref() {
+            let name = function.path.segments.last().map(|s| s.ident.to_string());
+            if call.args.len() == 4 {
+                match name.as_deref() {
+                    Some("take_input") => self.record(true, &call.args[1]),
+                    Some("take_output") => self.record(false, &call.args[1]),
+                    _ => (),
+                }
+            }
+        }
+        syn::visit::visit_expr_call(self, call);
+    }
+}
+
+fn exported_kind(item: &ItemStruct) -> Option<NodeKind> {
+    item.attrs.iter().find_map(|attr| {
+        let name = attr.path().segments.last()?.ident.to_string();
+        match name.as_str() {
+            "export_source" => Some(NodeKind::Source),
+            "export_operator" => Some(NodeKind::Operator),
+            "export_sink" => Some(NodeKind::Sink),
+            _ => None,
+        }
+    })
+}
+
+/// Returns the ports declared, with `#[input(..)]` and `#[output(..)]`, on the fields of a
+/// structure deriving `Ports`.
+fn derived_ports(
+    item: &ItemStruct,
+    constants: &HashMap<String, String>,
+) -> Result<Option<(Vec<String>, Vec<String>)>> {
+    let derives_ports = item.attrs.iter().any(|attr| {
+        attr.path().is_ident("derive")
+            && attr
+                .parse_args_with(
+                    syn::punctuated::Punctuated::<syn::Path, syn::Token![,]>::parse_terminated,
+                )
+                .map(|paths| {
+                    paths
+                        .iter()
+                        .any(|path| path.segments.last().map_or(false, |s| s.ident == "Ports"))
+                })
+                .unwrap_or(false)
+    });
+    if !derives_ports {
+        return Ok(None);
+    }
+
+    let mut inputs = Vec::new();
+    let mut outputs = Vec::new();
+    for field in &item.fields {
+        for attr in &field.attrs {
+            let is_input = if attr.path().is_ident("input") {
+                true
+            } else if attr.path().is_ident("output") {
+                false
+            } else {
+                continue;
+            };
+            let expr = attr.parse_args::<Expr>()?;
+            let port = resolve_port(&expr, constants).ok_or_else(|| {
+                anyhow!(
+                    "Could not resolve the port `{}` of `{}`",
+                    quote_expr(&expr),
+                    item.ident
+                )
+            })?;
+            if is_input {
+                push_unique(&mut inputs, port);
+            } else {
+                push_unique(&mut outputs, port);
+            }
+        }
+    }
+
+    Ok(Some((inputs, outputs)))
+}
+
+fn library_name(crate_dir: &Path) -> Result<Option<String>> {
+    let manifest = read_manifest(&crate_dir.join("Cargo.toml"))?;
+    let lib = manifest.get("lib");
+    let is_cdylib = lib
+        .and_then(|lib| lib.get("crate-type"))
+        .and_then(|types| types.as_array())
+        .map_or(false, |types| {
+            types.iter().any(|t| t.as_str() == Some("cdylib"))
+        });
+    if !is_cdylib {
+        return Ok(None);
+    }
+
+    let name = match lib.and_then(|lib| lib.get("name")).and_then(|n| n.as_str()) {
+        Some(name) => name.to_string(),
+        None => manifest
+            .get("package")
+            .and_then(|package| package.get("name"))
+            .and_then(|name| name.as_str())
+            .ok_or_else(|| anyhow!("{} has no package name", crate_dir.display()))?
+            .to_string(),
+    };
+
+    Ok(Some(name.replace('-', "_")))
+}
+
+/// Extracts the node defined in the crate located in `crate_dir`.
+///
+/// `Ok(None)` is returned if the crate does not produce a shared library, e.g. `datatypes`.
+/// `constants` are the port constants the sources of the crate may refer to, the constants of the
+/// crate itself are added to them.
+pub fn scan_crate(
+    crate_dir: &Path,
+    constants: &HashMap<String, String>,
+) -> Result<Option<NodeDefinition>> {
+    let library = match library_name(crate_dir)? {
+        Some(library) => library,
+        None => return Ok(None),
+    };
+
+    let file = parse_file(&crate_dir.join("src/lib.rs"))?;
+    let mut constants = constants.clone();
+    constants.extend(port_constants(&file));
+
+    let (item, kind) = file
+        .items
+        .iter()
+        .find_map(|item| match item {
+            Item::Struct(item) => exported_kind(item).map(|kind| (item, kind)),
+            _ => None,
+        })
+        .ok_or_else(|| anyhow!("No exported node found in {}", crate_dir.display()))?;
+
+    let (inputs, outputs) = match derived_ports(item, &constants)? {
+        Some(ports) => ports,
+        None => {
+            let mut taken = TakenPorts {
+                constants: &constants,
+                inputs: Vec::new(),
+                outputs: Vec::new(),
+                unresolved: Vec::new(),
+            };
+            taken.visit_file(&file);
+            if !taken.unresolved.is_empty() {
+                bail!(
+                    "Could not resolve the ports {:?} of `{}`",
+                    taken.unresolved,
+                    item.ident
+                );
+            }
+            (taken.inputs, taken.outputs)
+        }
+    };
+
+    Ok(Some(NodeDefinition {
+        id: item.ident.to_string(),
+        kind,
+        library,
+        inputs,
+        outputs,
+    }))
+}
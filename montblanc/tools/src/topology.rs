@@ -0,0 +1,771 @@
+//
+// Copyright (c) 2022 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+//! Compact descriptions of data flows, from which the crates of their nodes, the descriptors of
+//! the nodes and the data flow are generated.
+//!
+//! As in ROS 2, the nodes are linked through topics: every input of a node is linked to the output
+//! of the same name.
+//!
+//! ```yaml
+//! name: Montblanc
+//! nodes:
+//!   - name: Cordoba
+//!     kind: source
+//!     period_ms: 100
+//!     outputs:
+//!       Amazon: Float32
+//!   - name: Lyon
+//!     kind: operator
+//!     trigger: Amazon
+//!     inputs:
+//!       Amazon: Float32
+//!     outputs:
+//!       Tigris: Float32
+//! ```
+//!
+//! Sources send a message on each of their outputs every `period_ms`. Operators do the same when
+//! they have a `period_ms`, or else whenever they receive a message on their `trigger` input.
+//! Sinks only receive messages.
+
+use crate::nodes::{NodeDefinition, NodeKind};
+use crate::yaml::{self, Node};
+use std::collections::{HashMap, HashSet};
+use std::fmt::Write;
+use std::path::Path;
+
+/// A port of a node, and the `data_types` message exchanged on it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PortSpec {
+    pub name: String,
+    pub message: String,
+    pub line: usize,
+}
+
+/// When the outputs of a node are sent.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Trigger {
+    /// Every `period_ms` milliseconds.
+    Period(u64),
+    /// Whenever a message is received on the input.
+    Input(String),
+    /// Never: the node is a sink.
+    None,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NodeSpec {
+    pub name: String,
+    pub kind: NodeKind,
+    pub inputs: Vec<PortSpec>,
+    pub outputs: Vec<PortSpec>,
+    pub trigger: Trigger,
+    pub line: usize,
+}
+
+/// A link from the output of a node to the input of another, both being called `port`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TopicLink {
+    pub from: String,
+    pub to: String,
+    pub port: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Topology {
+    pub name: String,
+    pub nodes: Vec<NodeSpec>,
+}
+
+fn error<T>(line: usize, message: impl Into<String>) -> yaml::Result<T> {
+    Err(yaml::Error {
+        line,
+        message: message.into(),
+    })
+}
+
+/// Whether `name` can be the name of a node or a port: it is turned into Rust identifiers.
+fn is_identifier(name: &str) -> bool {
+    let mut chars = name.chars();
+    matches!(chars.next(), Some(c) if c.is_ascii_alphabetic())
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+fn scalar<'a>(node: &'a Node, what: &str) -> yaml::Result<&'a str> {
+    match node.as_str() {
+        Some(value) => Ok(value),
+        None => error(node.line, format!("`{}` must be a string", what)),
+    }
+}
+
+fn parse_ports(node: Option<&Node>, what: &str) -> yaml::Result<Vec<PortSpec>> {
+    let node = match node {
+        Some(node) => node,
+        None => return Ok(Vec::new()),
+    };
+    let entries = match node.as_mapping() {
+        Some(entries) => entries,
+        None => {
+            return error(
+                node.line,
+                format!("`{}` must map the name of each port to its message", what),
+            )
+        }
+    };
+
+    let mut ports: Vec<PortSpec> = Vec::new();
+    for (name, message) in entries {
+        let name = scalar(name, "port")?;
+        if !is_identifier(name) {
+            return error(
+                message.line,
+                format!(
+                    "invalid port name `{}`: expected letters, digits and `_`",
+                    name
+                ),
+            );
+        }
+        if ports.iter().any(|port| port.name == name) {
+            return error(message.line, format!("port `{}` is declared twice", name));
+        }
+        // The ports are turned into fields named after them in snake case.
+        if let Some(port) = ports
+            .iter()
+            .find(|port| snake_case(&port.name) == snake_case(name))
+        {
+            return error(
+                message.line,
+                format!(
+                    "ports `{}` and `{}` would have the same field `{}`",
+                    port.name,
+                    name,
+                    snake_case(name)
+                ),
+            );
+        }
+        let message_name = scalar(message, "message")?;
+        if !datatypes::MESSAGES.contains(&message_name) {
+            return error(
+                message.line,
+                format!(
+                    "unknown message `{}`, expected one of: {}",
+                    message_name,
+                    datatypes::MESSAGES.join(", ")
+                ),
+            );
+        }
+        ports.push(PortSpec {
+            name: name.to_string(),
+            message: message_name.to_string(),
+            line: message.line,
+        });
+    }
+    Ok(ports)
+}
+
+const NODE_KEYS: &[&str] = &["name", "kind", "inputs", "outputs", "period_ms", "trigger"];
+
+fn parse_node(node: &Node) -> yaml::Result<NodeSpec> {
+    let entries = match node.as_mapping() {
+        Some(entries) => entries,
+        None => return error(node.line, "a node must be a mapping"),
+    };
+    for (key, _) in entries {
+        let name = scalar(key, "key")?;
+        if !NODE_KEYS.contains(&name) {
+            return error(key.line, format!("unknown key `{}`", name));
+        }
+    }
+
+    let name = match node.get("name") {
+        Some(name) => scalar(name, "name")?,
+        None => return error(node.line, "missing `name`"),
+    };
+    if !is_identifier(name) {
+        return error(
+            node.line,
+            format!(
+                "invalid node name `{}`: expected letters, digits and `_`",
+                name
+            ),
+        );
+    }
+
+    let kind = match node.get("kind").map(|kind| (kind, kind.as_str())) {
+        Some((_, Some("source"))) => NodeKind::Source,
+        Some((_, Some("operator"))) => NodeKind::Operator,
+        Some((_, Some("sink"))) => NodeKind::Sink,
+        Some((kind, _)) => {
+            return error(kind.line, "`kind` must be `source`, `operator` or `sink`")
+        }
+        None => return error(node.line, format!("missing `kind` for node `{}`", name)),
+    };
+
+    let inputs = parse_ports(node.get("inputs"), "inputs")?;
+    let outputs = parse_ports(node.get("outputs"), "outputs")?;
+    let period = match node.get("period_ms") {
+        Some(period) => match period
+            .as_str()
+            .and_then(|period| period.parse::<u64>().ok())
+        {
+            Some(period) if period > 0 => Some(period),
+            _ => return error(period.line, "`period_ms` must be a positive number"),
+        },
+        None => None,
+    };
+    let trigger = match node.get("trigger") {
+        Some(trigger) => Some((scalar(trigger, "trigger")?, trigger.line)),
+        None => None,
+    };
+
+    let trigger = match (kind, period, trigger) {
+        (NodeKind::Source, Some(period), None) => Trigger::Period(period),
+        (NodeKind::Source, _, _) => {
+            return error(
+                node.line,
+                format!("source `{}` requires a `period_ms`, and no `trigger`", name),
+            )
+        }
+        (NodeKind::Operator, Some(period), None) => Trigger::Period(period),
+        (NodeKind::Operator, None, Some((trigger, line))) => {
+            if !inputs.iter().any(|input| input.name == trigger) {
+                return error(
+                    line,
+                    format!("`{}` is not an input of operator `{}`", trigger, name),
+                );
+            }
+            Trigger::Input(trigger.to_string())
+        }
+        (NodeKind::Operator, _, _) => {
+            return error(
+                node.line,
+                format!(
+                    "operator `{}` requires either a `period_ms` or a `trigger`",
+                    name
+                ),
+            )
+        }
+        (NodeKind::Sink, None, None) => Trigger::None,
+        (NodeKind::Sink, _, _) => {
+            return error(
+                node.line,
+                format!("sink `{}` cannot have a `period_ms` or a `trigger`", name),
+            )
+        }
+    };
+
+    match kind {
+        NodeKind::Source if !inputs.is_empty() || outputs.is_empty() => error(
+            node.line,
+            format!("source `{}` requires outputs, and no inputs", name),
+        ),
+        NodeKind::Operator if inputs.is_empty() || outputs.is_empty() => error(
+            node.line,
+            format!("operator `{}` requires inputs and outputs", name),
+        ),
+        NodeKind::Sink if inputs.is_empty() || !outputs.is_empty() => error(
+            node.line,
+            format!("sink `{}` requires inputs, and no outputs", name),
+        ),
+        _ => Ok(NodeSpec {
+            name: name.to_string(),
+            kind,
+            inputs,
+            outputs,
+            trigger,
+            line: node.line,
+        }),
+    }
+}
+
+/// Parses and checks the topology described in `content`.
+///
+/// Every input must be linked to an output exchanging the same message, and every output must be
+/// sent by a single node.
+pub fn parse(content: &str) -> yaml::Result<Topology> {
+    let tree = yaml::parse(content)?;
+    let name = match tree.get("name") {
+        Some(name) => scalar(name, "name")?,
+        None => return error(tree.line, "missing `name`"),
+    };
+    if !is_identifier(name) {
+        return error(
+            tree.line,
+            format!(
+                "invalid topology name `{}`: expected letters, digits and `_`",
+                name
+            ),
+        );
+    }
+    let entries = match tree.get("nodes").and_then(Node::as_sequence) {
+        Some(entries) => entries,
+        None => return error(tree.line, "`nodes` must be a list of nodes"),
+    };
+
+    let mut nodes: Vec<NodeSpec> = Vec::new();
+    let mut crates = HashSet::new();
+    for entry in entries {
+        let node = parse_node(entry)?;
+        if !crates.insert(crate_name(&node.name)) {
+            return error(
+                node.line,
+                format!("node `{}` has the same crate as another node", node.name),
+            );
+        }
+        nodes.push(node);
+    }
+
+    let mut publishers: HashMap<&str, (&NodeSpec, &PortSpec)> = HashMap::new();
+    for node in &nodes {
+        for output in &node.outputs {
+            if let Some((other, _)) = publishers.insert(&output.name, (node, output)) {
+                return error(
+                    output.line,
+                    format!(
+                        "`{}` is sent by both `{}` and `{}`",
+                        output.name, other.name, node.name
+                    ),
+                );
+            }
+        }
+    }
+    for node in &nodes {
+        for input in &node.inputs {
+            match publishers.get(input.name.as_str()) {
+                None => {
+                    return error(
+                        input.line,
+                        format!("no node sends `{}` to `{}`", input.name, node.name),
+                    )
+                }
+                Some((publisher, output)) if output.message != input.message => {
+                    return error(
+                        input.line,
+                        format!(
+                            "`{}` sends `{}` messages on `{}` but `{}` expects `{}`",
+                            publisher.name, output.message, input.name, node.name, input.message
+                        ),
+                    )
+                }
+                Some(_) => (),
+            }
+        }
+    }
+
+    Ok(Topology {
+        name: name.to_string(),
+        nodes,
+    })
+}
+
+/// `name` in snake case, e.g. `ImageConverter` becomes `image_converter`.
+fn snake_case(name: &str) -> String {
+    let mut snake = String::with_capacity(name.len() + 4);
+    let mut previous: Option<char> = None;
+    for c in name.chars() {
+        if c.is_ascii_uppercase()
+            && matches!(previous, Some(p) if p.is_ascii_lowercase() || p.is_ascii_digit())
+        {
+            snake.push('_');
+        }
+        snake.push(c.to_ascii_lowercase());
+        previous = Some(c);
+    }
+    snake
+}
+
+/// `name` in upper camel case, e.g. `image_converter` becomes `ImageConverter`.
+fn camel_case(name: &str) -> String {
+    name.split('_')
+        .map(|word| {
+            let mut chars = word.chars();
+            match chars.next() {
+                Some(first) => first.to_ascii_uppercase().to_string() + chars.as_str(),
+                None => String::new(),
+            }
+        })
+        .collect()
+}
+
+/// The name of the crate of the node `name`, which is also the name of its directory.
+pub fn crate_name(name: &str) -> String {
+    snake_case(name).replace('_', "-")
+}
+
+impl NodeSpec {
+    pub fn crate_name(&self) -> String {
+        crate_name(&self.name)
+    }
+
+    /// The name of the library of the node, as found in the name of its shared library.
+    pub fn library(&self) -> String {
+        snake_case(&self.name)
+    }
+
+    /// The name of the structure implementing the node.
+    pub fn struct_name(&self) -> String {
+        camel_case(&self.name)
+    }
+
+    /// The node as declared in the sources generated, from which its descriptor is generated.
+    pub fn definition(&self) -> NodeDefinition {
+        NodeDefinition {
+            id: self.struct_name(),
+            kind: self.kind,
+            library: self.library(),
+            inputs: self.inputs.iter().map(|input| input.name.clone()).collect(),
+            outputs: self
+                .outputs
+                .iter()
+                .map(|output| output.name.clone())
+                .collect(),
+        }
+    }
+
+    fn uses_select(&self) -> bool {
+        !self.inputs.is_empty()
+    }
+
+    /// The `Cargo.toml` of the crate of the node, `datatypes` being found at `datatypes`.
+    pub fn manifest(&self, datatypes: &Path) -> String {
+        let mut manifest = format!(
+            "[package]\n\
+             name = \"{}\"\n\
+             version = \"0.1.0\"\n\
+             edition = \"2018\"\n\
+             \n\
+             [lib]\n\
+             name = \"{}\"\n\
+             crate-type=[\"cdylib\"]\n\
+             path=\"src/lib.rs\"\n\
+             \n\
+             [dependencies]\n\
+             async-std = {{ workspace = true }}\n\
+             async-trait = {{ workspace = true }}\n\
+             datatypes = {{ path = \"{}\" }}\n",
+            self.crate_name(),
+            self.library(),
+            datatypes.display()
+        );
+        if self.uses_select() {
+            manifest.push_str("futures = { workspace = true }\n");
+        }
+        manifest.push_str("zenoh-flow = { workspace = true }\n");
+        manifest
+    }
+
+    /// The sources of the node.
+    pub fn source(&self, topology: &str) -> String {
+        let name = self.struct_name();
+        let mut source = format!(
+            "// Generated by `gen-topology` from the `{}` topology.\n\n",
+            topology
+        );
+
+        let periodic = matches!(self.trigger, Trigger::Period(_));
+        let mut imports = Vec::new();
+        if self.kind == NodeKind::Source {
+            imports.push("use async_std::sync::Mutex;".to_string());
+        }
+        imports.push("use datatypes::data_types;".to_string());
+        match self.kind {
+            NodeKind::Source => {
+                imports.push("use datatypes::datagen::DataGen;".to_string());
+                imports.push("use datatypes::publication::Publication;".to_string());
+            }
+            NodeKind::Operator => {
+                imports.push("use datatypes::datagen::{DataGen, Generate};".to_string())
+            }
+            NodeKind::Sink => (),
+        }
+        imports.push("use datatypes::stats::Statistics;".to_string());
+        if periodic {
+            imports.push("use datatypes::ticker::Ticker;".to_string());
+        }
+        imports.push("use datatypes::Ports;".to_string());
+        if self.uses_select() {
+            imports.push("use futures::prelude::*;".to_string());
+            imports.push("use futures::select;".to_string());
+        }
+        if periodic {
+            imports.push("use std::time::Duration;".to_string());
+        }
+        imports.push("use zenoh_flow::prelude::*;".to_string());
+        source.push_str(&imports.join("\n"));
+        source.push_str("\n\n");
+
+        if self.kind == NodeKind::Operator {
+            self.write_state(&mut source);
+        }
+
+        let export = match self.kind {
+            NodeKind::Source => "export_source",
+            NodeKind::Operator => "export_operator",
+            NodeKind::Sink => "export_sink",
+        };
+        let _ = writeln!(
+            source,
+            "#[{}]\n#[derive(Ports)]\npub struct {} {{",
+            export, name
+        );
+        for input in &self.inputs {
+            let _ = writeln!(
+                source,
+                "    #[input(\"{}\")]\n    input_{}: Input<data_types::{}>,",
+                input.name,
+                snake_case(&input.name),
+                input.message
+            );
+        }
+        for output in &self.outputs {
+            let _ = writeln!(
+                source,
+                "    #[output(\"{}\")]\n    output_{}: Output<data_types::{}>,",
+                output.name,
+                snake_case(&output.name),
+                output.message
+            );
+        }
+        source.push_str("    #[statistics]\n    statistics: Statistics,\n");
+        if let Trigger::Period(period) = self.trigger {
+            let _ = writeln!(
+                source,
+                "    #[configuration(Ticker::every(Duration::from_millis({})))]\n    \
+                 ticker: Ticker,",
+                period
+            );
+        }
+        match self.kind {
+            NodeKind::Source => source.push_str(
+                "    #[configuration(Publication::without_payload())]\n    \
+                 publication: Publication,\n    \
+                 #[generate]\n    datagen: Mutex<DataGen>,\n",
+            ),
+            NodeKind::Operator => {
+                let _ = writeln!(source, "    #[generate]\n    state: {}State,", name);
+            }
+            NodeKind::Sink => (),
+        }
+        source.push_str("}\n\n");
+
+        let _ = writeln!(
+            source,
+            "#[async_trait::async_trait]\nimpl Node for {} {{\n    \
+             async fn iteration(&self) -> Result<()> {{",
+            name
+        );
+        match self.kind {
+            NodeKind::Source => self.write_source_iteration(&mut source),
+            NodeKind::Operator | NodeKind::Sink => self.write_select(&mut source),
+        }
+        source.push_str("        Ok(())\n    }\n}\n");
+        source
+    }
+
+    /// The state of an operator: the message it sends on each output, generated once.
+    fn write_state(&self, source: &mut String) {
+        let name = self.struct_name();
+        let _ = writeln!(source, "#[derive(Debug, Clone)]\nstruct {}State {{", name);
+        for output in &self.outputs {
+            let _ = writeln!(
+                source,
+                "    {}_data: data_types::{},",
+                snake_case(&output.name),
+                output.message
+            );
+        }
+        let _ = writeln!(
+            source,
+            "}}\n\nimpl Generate for {}State {{\n    \
+             fn generate(datagen: &mut DataGen) -> Self {{\n        {}State {{",
+            name, name
+        );
+        for output in &self.outputs {
+            let _ = writeln!(
+                source,
+                "            {}_data: datagen.gen(),",
+                snake_case(&output.name)
+            );
+        }
+        source.push_str("        }\n    }\n}\n\n");
+    }
+
+    fn write_source_iteration(&self, source: &mut String) {
+        source.push_str(
+            "        self.ticker.tick().await;\n        \
+             for _ in 0..self.publication.burst_size {\n            \
+             let timestamp = datatypes::latency::now();\n",
+        );
+        for output in &self.outputs {
+            let port = snake_case(&output.name);
+            let _ = write!(
+                source,
+                "            let {port}: data_types::{message} = \
+                 self.datagen.lock().await.gen();\n            \
+                 self.statistics.sent(\"{name}\");\n            \
+                 self.output_{port}.send({port}, Some(timestamp)).await?;\n",
+                port = port,
+                message = output.message,
+                name = output.name
+            );
+        }
+        source.push_str("        }\n");
+    }
+
+    /// Sends the state of an operator on its outputs, `timestamp` being an `Option<u64>`.
+    fn write_send_state(&self, source: &mut String, indent: &str, timestamp: &str) {
+        for output in &self.outputs {
+            let port = snake_case(&output.name);
+            let _ = write!(
+                source,
+                "{indent}self.statistics.sent(\"{name}\");\n\
+                 {indent}self.output_{port}\
+                 .send(self.state.{port}_data.clone(), {timestamp})\
+                 .await?;\n",
+                indent = indent,
+                name = output.name,
+                port = port,
+                timestamp = timestamp
+            );
+        }
+    }
+
+    fn write_select(&self, source: &mut String) {
+        source.push_str("        select! {\n");
+        let arms = self.inputs.len() + matches!(self.trigger, Trigger::Period(_)) as usize;
+        for (index, input) in self.inputs.iter().enumerate() {
+            let port = snake_case(&input.name);
+            let triggered = self.trigger == Trigger::Input(input.name.clone());
+            let _ = write!(
+                source,
+                "            msg = self.input_{port}.recv().fuse() => {{\n                \
+                 if let Ok((Message::Data(_), {ts})) = msg {{\n                    \
+                 self.statistics.received(\"{name}\");\n",
+                port = port,
+                ts = if triggered { "ts" } else { "_ts" },
+                name = input.name
+            );
+            if triggered {
+                source.push_str(
+                    "                    let timestamp = Some(ts.get_time().as_u64());\n",
+                );
+                self.write_send_state(source, "                    ", "timestamp");
+            }
+            let separator = if index + 1 < arms { "," } else { "" };
+            let _ = writeln!(source, "                }}\n            }}{}", separator);
+        }
+        if let Trigger::Period(period) = self.trigger {
+            let _ = writeln!(
+                source,
+                "            // Every {}ms by default, send the state, which none of the \
+                 inputs changes: like\n            \
+                 // the data of a source, it is stamped by Zenoh-Flow.\n            \
+                 _ = self.ticker.tick().fuse() => {{",
+                period
+            );
+            self.write_send_state(source, "                ", "None");
+            source.push_str("            }\n");
+        }
+        source.push_str("        }\n");
+    }
+}
+
+impl Topology {
+    /// The links between the nodes, in the order of the nodes and of their inputs.
+    pub fn links(&self) -> Vec<TopicLink> {
+        let publishers = self
+            .nodes
+            .iter()
+            .flat_map(|node| {
+                node.outputs
+                    .iter()
+                    .map(move |output| (output.name.as_str(), node.name.as_str()))
+            })
+            .collect::<HashMap<_, _>>();
+
+        self.nodes
+            .iter()
+            .flat_map(|node| node.inputs.iter().map(move |input| (node, input)))
+            .filter_map(|(node, input)| {
+                Some(TopicLink {
+                    from: publishers.get(input.name.as_str())?.to_string(),
+                    to: node.name.clone(),
+                    port: input.name.clone(),
+                })
+            })
+            .collect()
+    }
+
+    /// The data flow of `scale` copies of the topology, the nodes of which are described in
+    /// `<base_dir>/<crate>/<crate>.yml`.
+    ///
+    /// When there are several copies, the ids of the nodes of the `i`-th copy end with `_<i>`.
+    pub fn flow(&self, scale: usize, base_dir: &Path) -> String {
+        let id = |name: &str, copy: usize| {
+            if scale > 1 {
+                format!("{}_{}", name, copy)
+            } else {
+                name.to_string()
+            }
+        };
+        let name = if scale > 1 {
+            format!("{}x{}", self.name, scale)
+        } else {
+            self.name.clone()
+        };
+
+        let mut flow = format!(
+            "flow: {}\nvars:\n BASE_DIR: \"{}\"\n",
+            name,
+            base_dir.display()
+        );
+        for (section, kind) in [
+            ("operators", NodeKind::Operator),
+            ("sources", NodeKind::Source),
+            ("sinks", NodeKind::Sink),
+        ] {
+            let nodes = self.nodes.iter().filter(|node| node.kind == kind);
+            if nodes.clone().next().is_none() {
+                continue;
+            }
+            let _ = writeln!(flow, "\n{}:", section);
+            for copy in 1..=scale {
+                for node in nodes.clone() {
+                    let _ = writeln!(
+                        flow,
+                        "  - id : {}\n    descriptor: \"file://{{{{BASE_DIR}}}}/{}/{}.yml\"\n",
+                        id(&node.name, copy),
+                        node.crate_name(),
+                        node.crate_name()
+                    );
+                }
+            }
+        }
+
+        flow.push_str("links:\n");
+        for copy in 1..=scale {
+            for link in self.links() {
+                let _ = writeln!(
+                    flow,
+                    "- from:\n    node : {}\n    output : {}\n  to:\n    node : {}\n    input : {}",
+                    id(&link.from, copy),
+                    link.port,
+                    id(&link.to, copy),
+                    link.port
+                );
+            }
+        }
+        flow
+    }
+}
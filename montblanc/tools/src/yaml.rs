@@ -0,0 +1,252 @@
+//
+// Copyright (c) 2022 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+//! A minimal YAML tree that remembers, for every node, the line where it starts.
+
+use std::collections::HashMap;
+use std::fmt;
+use yaml_rust::parser::{Event, MarkedEventReceiver, Parser};
+use yaml_rust::scanner::{Marker, TScalarStyle};
+use yaml_rust::Yaml;
+
+/// An error located at a given line of a YAML document.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Error {
+    pub line: usize,
+    pub message: String,
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.message)
+    }
+}
+
+impl std::error::Error for Error {}
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    /// A plain scalar, whose type depends on its content, e.g. `42`, `true` or `name`.
+    Scalar(String),
+    /// A quoted, literal or folded scalar: always a string.
+    String(String),
+    Sequence(Vec<Node>),
+    Mapping(Vec<(Node, Node)>),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Node {
+    pub value: Value,
+    /// The line, starting at 1, where this node starts.
+    pub line: usize,
+}
+
+impl Node {
+    pub fn as_str(&self) -> Option<&str> {
+        match &self.value {
+            Value::Scalar(scalar) | Value::String(scalar) => Some(scalar),
+            _ => None,
+        }
+    }
+
+    pub fn as_sequence(&self) -> Option<&[Node]> {
+        match &self.value {
+            Value::Sequence(sequence) => Some(sequence),
+            _ => None,
+        }
+    }
+
+    pub fn as_mapping(&self) -> Option<&[(Node, Node)]> {
+        match &self.value {
+            Value::Mapping(mapping) => Some(mapping),
+            _ => None,
+        }
+    }
+
+    /// Returns the value associated to `key` if this node is a mapping.
+    pub fn get(&self, key: &str) -> Option<&Node> {
+        self.as_mapping()?
+            .iter()
+            .find(|(k, _)| k.as_str() == Some(key))
+            .map(|(_, v)| v)
+    }
+
+    /// Converts this node to JSON, resolving the type of its plain scalars as YAML does. The keys
+    /// of mappings that are not strings are dropped.
+    pub fn to_json(&self) -> serde_json::Value {
+        match &self.value {
+            Value::Scalar(scalar) => match Yaml::from_str(scalar) {
+                Yaml::Integer(integer) => integer.into(),
+                Yaml::Real(real) => real
+                    .parse::<f64>()
+                    .ok()
+                    .and_then(serde_json::Number::from_f64)
+                    .map_or_else(|| scalar.clone().into(), serde_json::Value::Number),
+                Yaml::Boolean(boolean) => boolean.into(),
+                Yaml::Null => serde_json::Value::Null,
+                _ => scalar.clone().into(),
+            },
+            Value::String(string) => string.clone().into(),
+            Value::Sequence(nodes) => nodes.iter().map(Node::to_json).collect(),
+            Value::Mapping(entries) => entries
+                .iter()
+                .filter_map(|(k, v)| Some((k.as_str()?.to_string(), v.to_json())))
+                .collect::<serde_json::Map<_, _>>()
+                .into(),
+        }
+    }
+}
+
+enum Frame {
+    Sequence(Vec<Node>, usize),
+    Mapping(Vec<(Node, Node)>, Option<Node>, usize),
+}
+
+#[derive(Default)]
+struct Builder {
+    stack: Vec<Frame>,
+    anchors: HashMap<usize, Node>,
+    documents: Vec<Node>,
+    error: Option<Error>,
+}
+
+impl Builder {
+    fn push(&mut self, node: Node, anchor: usize) {
+        if anchor > 0 {
+            self.anchors.insert(anchor, node.clone());
+        }
+
+        match self.stack.last_mut() {
+            None => self.documents.push(node),
+            Some(Frame::Sequence(nodes, _)) => nodes.push(node),
+            Some(Frame::Mapping(entries, key, _)) => match key.take() {
+                None => *key = Some(node),
+                Some(k) => entries.push((k, node)),
+            },
+        }
+    }
+}
+
+impl MarkedEventReceiver for Builder {
+    fn on_event(&mut self, event: Event, marker: Marker) {
+        // yaml-rust lines start at 0 for the stream and at 1 for everything else.
+        let line = marker.line().max(1);
+        match event {
+            Event::Scalar(value, style, anchor, _) => {
+                let value = match style {
+                    TScalarStyle::Plain => Value::Scalar(value),
+                    _ => Value::String(value),
+                };
+                self.push(Node { value, line }, anchor)
+            }
+            Event::SequenceStart(anchor) => self.stack.push(Frame::Sequence(Vec::new(), anchor)),
+            Event::MappingStart(anchor) => {
+                self.stack.push(Frame::Mapping(Vec::new(), None, anchor))
+            }
+            Event::SequenceEnd | Event::MappingEnd => {
+                let (value, anchor) = match self.stack.pop() {
+                    Some(Frame::Sequence(nodes, anchor)) => (Value::Sequence(nodes), anchor),
+                    Some(Frame::Mapping(entries, _, anchor)) => (Value::Mapping(entries), anchor),
+                    None => return,
+                };
+                // The line of a collection is the line of its first element.
+                let line = match &value {
+                    Value::Sequence(nodes) => nodes.first().map(|n| n.line),
+                    Value::Mapping(entries) => entries.first().map(|(k, _)| k.line),
+                    Value::Scalar(_) | Value::String(_) => None,
+                }
+                .unwrap_or(line);
+                self.push(Node { value, line }, anchor);
+            }
+            Event::Alias(anchor) => match self.anchors.get(&anchor).cloned() {
+                Some(node) => self.push(node, 0),
+                None => {
+                    self.error = Some(Error {
+                        line,
+                        message: "unknown alias".to_string(),
+                    })
+                }
+            },
+            Event::StreamStart
+            | Event::StreamEnd
+            | Event::DocumentStart
+            | Event::DocumentEnd
+            | Event::Nothing => (),
+        }
+    }
+}
+
+/// Parses the first document of `content`.
+pub fn parse(content: &str) -> Result<Node> {
+    let mut builder = Builder::default();
+    Parser::new(content.chars())
+        .load(&mut builder, false)
+        .map_err(|e| Error {
+            line: e.marker().line(),
+            message: e.to_string(),
+        })?;
+    if let Some(error) = builder.error {
+        return Err(error);
+    }
+
+    Ok(builder.documents.into_iter().next().unwrap_or(Node {
+        value: Value::Mapping(Vec::new()),
+        line: 1,
+    }))
+}
+
+/// Replaces every `{{ VAR }}` of `content` with the value of `VAR`.
+///
+/// An error is returned if a variable is not defined.
+pub fn expand_vars(content: &str, vars: &HashMap<String, String>) -> Result<String> {
+    let mut expanded = String::with_capacity(content.len());
+    let mut rest = content;
+    while let Some(start) = rest.find("{{") {
+        let offset = content.len() - rest.len() + start;
+        let line = content[..offset].matches('\n').count() + 1;
+        let end = rest[start..]
+            .find("}}")
+            .map(|end| start + end)
+            .ok_or_else(|| Error {
+                line,
+                message: "unterminated `{{`".to_string(),
+            })?;
+        let name = rest[start + 2..end].trim();
+        let value = vars.get(name).ok_or_else(|| Error {
+            line,
+            message: format!("undefined variable `{}`", name),
+        })?;
+        expanded.push_str(&rest[..start]);
+        expanded.push_str(value);
+        rest = &rest[end + 2..];
+    }
+    expanded.push_str(rest);
+
+    Ok(expanded)
+}
+
+/// Returns the `vars` section of a YAML document as a map.
+pub fn vars(node: &Node) -> HashMap<String, String> {
+    node.get("vars")
+        .and_then(Node::as_mapping)
+        .map(|entries| {
+            entries
+                .iter()
+                .filter_map(|(k, v)| Some((k.as_str()?.to_string(), v.as_str()?.to_string())))
+                .collect()
+        })
+        .unwrap_or_default()
+}
@@ -0,0 +1,452 @@
+//
+// Copyright (c) 2022 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+use tempfile::TempDir;
+use tools::flow::{self, Diagnostic, Flow, Severity};
+
+/// Writes the data flow `content`, and the node descriptors `descriptors` next to it, then loads
+/// the data flow.
+fn load(content: &str, descriptors: &[(&str, &str)]) -> (Option<Flow>, Vec<Diagnostic>, TempDir) {
+    load_with_vars(content, descriptors, &HashMap::new())
+}
+
+/// As `load`, overriding the variables of the data flow with `vars`.
+fn load_with_vars(
+    content: &str,
+    descriptors: &[(&str, &str)],
+    vars: &HashMap<String, String>,
+) -> (Option<Flow>, Vec<Diagnostic>, TempDir) {
+    let dir = TempDir::new().expect("Could not create a temporary directory");
+    for (name, descriptor) in descriptors {
+        fs::write(dir.path().join(name), descriptor).expect("Could not write a descriptor");
+    }
+    let path = dir.path().join("flow.yml");
+    fs::write(&path, content).expect("Could not write the data flow");
+
+    let mut diagnostics = Vec::new();
+    let flow = flow::load(&path, vars, &mut diagnostics);
+    (flow, diagnostics, dir)
+}
+
+/// The file name, severity, line and message of each diagnostic.
+fn summarize(diagnostics: &[Diagnostic]) -> Vec<(String, Severity, usize, String)> {
+    diagnostics
+        .iter()
+        .map(|diagnostic| {
+            (
+                diagnostic
+                    .file
+                    .file_name()
+                    .map(|name| name.to_string_lossy().into_owned())
+                    .unwrap_or_default(),
+                diagnostic.severity,
+                diagnostic.line,
+                diagnostic.message.clone(),
+            )
+        })
+        .collect()
+}
+
+static DESCRIPTORS: &[(&str, &str)] = &[
+    (
+        "source.yml",
+        "id: Source\nuri: file://source.so\noutputs: [Out]\n",
+    ),
+    (
+        "operator.yml",
+        "id: Operator\nuri: file://operator.so\ninputs: [In]\noutputs: [Out]\n",
+    ),
+    ("sink.yml", "id: Sink\nuri: file://sink.so\ninputs: [In]\n"),
+];
+
+/// A source, an operator and a sink, on lines 3, 6 and 9, followed by `links` from line 11.
+fn with_links(links: &[(&str, &str, &str, &str)]) -> String {
+    let mut content = "\
+flow: test
+sources:
+  - id: Source
+    descriptor: file://source.yml
+operators:
+  - id: Operator
+    descriptor: file://operator.yml
+sinks:
+  - id: Sink
+    descriptor: file://sink.yml
+links:
+"
+    .to_string();
+    // Each link takes 6 lines: the first one starts at line 12, the second at line 18...
+    for (from, output, to, input) in links {
+        content.push_str(&format!(
+            "  - from:\n      node: {}\n      output: {}\n",
+            from, output
+        ));
+        content.push_str(&format!(
+            "    to:\n      node: {}\n      input: {}\n",
+            to, input
+        ));
+    }
+    content
+}
+
+/// The links of the flow of `with_links` with every port connected.
+const CONNECTED: [(&str, &str, &str, &str); 2] = [
+    ("Source", "Out", "Operator", "In"),
+    ("Operator", "Out", "Sink", "In"),
+];
+
+#[test]
+fn reports_links_between_ports_of_different_types() {
+    let (flow, diagnostics, _dir) = load(
+        "\
+flow: mistyped
+sources:
+  - id: Source
+    descriptor: file://source.yml
+sinks:
+  - id: Sink
+    descriptor: file://sink.yml
+links:
+  - from:
+      node: Source
+      output: Amazon
+    to:
+      node: Sink
+      input: Tagus
+",
+        &[
+            (
+                "source.yml",
+                "id: Source\nuri: file://source.so\noutputs: [Amazon]\n",
+            ),
+            (
+                "sink.yml",
+                "id: Sink\nuri: file://sink.so\ninputs: [Tagus]\n",
+            ),
+        ],
+    );
+    assert_eq!(diagnostics, Vec::new());
+    let flow = flow.unwrap();
+    assert_eq!(flow::validate(&flow), Vec::new());
+
+    let diagnostics = flow::check_types(&flow, datatypes::port_type);
+    let messages = diagnostics
+        .iter()
+        .map(|diagnostic| (diagnostic.line, diagnostic.message.as_str()))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        messages,
+        [(
+            9,
+            "`Source.Amazon` sends `Float32` messages but `Sink.Tagus` expects `Pose`"
+        )]
+    );
+}
+
+#[test]
+fn reports_inconsistent_flows_with_their_line() {
+    let error = |line, message: &str| ("flow.yml", Severity::Error, line, message.to_string());
+    let warning = |line, message: &str| ("flow.yml", Severity::Warning, line, message.to_string());
+
+    let cases = vec![
+        ("connected", with_links(&CONNECTED), vec![]),
+        (
+            "duplicate id",
+            with_links(&CONNECTED).replace(
+                "links:\n",
+                "  - id: Operator\n    descriptor: file://sink.yml\nlinks:\n",
+            ),
+            vec![error(
+                11,
+                "duplicate node id `Operator` (first declared at line 6)",
+            )],
+        ),
+        (
+            "unknown node",
+            with_links(&[
+                CONNECTED[0],
+                CONNECTED[1],
+                ("Source", "Out", "Nowhere", "In"),
+            ]),
+            vec![error(29, "unknown node `Nowhere`")],
+        ),
+        (
+            "unknown output",
+            with_links(&[("Source", "Nile", "Operator", "In"), CONNECTED[1]]),
+            vec![
+                warning(3, "output `Out` of node `Source` is not connected"),
+                error(14, "unknown output `Nile` of node `Source`"),
+            ],
+        ),
+        (
+            "unknown input",
+            with_links(&[CONNECTED[0], ("Operator", "Out", "Sink", "Nile")]),
+            vec![
+                error(9, "input `In` of node `Sink` is not connected"),
+                error(23, "unknown input `Nile` of node `Sink`"),
+            ],
+        ),
+        (
+            "fan-in",
+            with_links(&[CONNECTED[0], CONNECTED[1], ("Source", "Out", "Sink", "In")]),
+            vec![error(
+                29,
+                "input `In` of node `Sink` is already connected (line 18): fan-in is not \
+                 supported",
+            )],
+        ),
+        (
+            "unconnected ports",
+            with_links(&CONNECTED[..1]),
+            vec![
+                warning(6, "output `Out` of node `Operator` is not connected"),
+                error(9, "input `In` of node `Sink` is not connected"),
+            ],
+        ),
+        (
+            "no links",
+            with_links(&[]).replace("links:\n", ""),
+            vec![
+                warning(3, "output `Out` of node `Source` is not connected"),
+                error(6, "input `In` of node `Operator` is not connected"),
+                warning(6, "output `Out` of node `Operator` is not connected"),
+                error(9, "input `In` of node `Sink` is not connected"),
+            ],
+        ),
+    ];
+
+    for (case, content, expected) in cases {
+        let (flow, diagnostics, _dir) = load(&content, DESCRIPTORS);
+        assert_eq!(diagnostics, Vec::new(), "{}", case);
+        let diagnostics = flow::validate(&flow.expect(case));
+        let expected = expected
+            .into_iter()
+            .map(|(file, severity, line, message)| (file.to_string(), severity, line, message))
+            .collect::<Vec<_>>();
+        assert_eq!(summarize(&diagnostics), expected, "{}", case);
+    }
+}
+
+#[test]
+fn reports_the_ports_sources_and_sinks_cannot_have_in_their_descriptor() {
+    let content = with_links(&CONNECTED)
+        .replace("file://source.yml", "file://bad-source.yml")
+        .replace("file://sink.yml", "file://bad-sink.yml");
+    let (flow, diagnostics, _dir) = load(
+        &content,
+        &[
+            DESCRIPTORS[0],
+            DESCRIPTORS[1],
+            (
+                "bad-source.yml",
+                "id: Source\nuri: file://source.so\ninputs: [Tagus]\noutputs: [Out]\n",
+            ),
+            (
+                "bad-sink.yml",
+                "id: Sink\nuri: file://sink.so\ninputs: [In]\noutputs:\n  - Tagus\n",
+            ),
+        ],
+    );
+    assert_eq!(diagnostics, Vec::new());
+    let diagnostics = flow::validate(&flow.unwrap());
+    let messages = summarize(&diagnostics)
+        .into_iter()
+        .filter(|(_, severity, _, _)| *severity == Severity::Error)
+        .map(|(file, _, line, message)| (file, line, message))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        messages,
+        [
+            (
+                "bad-sink.yml".to_string(),
+                5,
+                "sink `Sink` declares the output `Tagus`".to_string()
+            ),
+            (
+                "bad-source.yml".to_string(),
+                3,
+                "source `Source` declares the input `Tagus`".to_string()
+            ),
+            (
+                "flow.yml".to_string(),
+                3,
+                "input `Tagus` of node `Source` is not connected".to_string()
+            ),
+        ]
+    );
+}
+
+#[test]
+fn reports_flows_it_cannot_load_with_their_line() {
+    let cases = [
+        (
+            "syntax error",
+            "flow: test\nsources:\n  - id: [Source\n",
+            vec![("flow.yml", 4, "")],
+        ),
+        (
+            "undefined variable",
+            "flow: test\nsources:\n  - id: Source\n    descriptor: file://{{ DIR }}/source.yml\n",
+            vec![("flow.yml", 4, "undefined variable `DIR`")],
+        ),
+        (
+            "missing id",
+            "flow: test\nsources:\n  - descriptor: file://source.yml\n",
+            vec![("flow.yml", 3, "missing `id`")],
+        ),
+        (
+            "missing descriptor",
+            "flow: test\nsinks:\n  - id: Sink\n",
+            vec![("flow.yml", 3, "missing `descriptor`")],
+        ),
+        (
+            "unsupported descriptor",
+            "flow: test\nsinks:\n  - id: Sink\n    descriptor: https://sink.yml\n",
+            vec![("flow.yml", 4, "unsupported descriptor `https://sink.yml`")],
+        ),
+        (
+            "missing descriptor file",
+            "flow: test\nsinks:\n  - id: Sink\n    descriptor: file://nowhere.yml\n",
+            vec![
+                ("nowhere.yml", 0, "could not read file: "),
+                ("flow.yml", 4, "could not load descriptor `"),
+            ],
+        ),
+        (
+            "invalid ports",
+            "flow: test\nsources:\n  - id: Source\n    descriptor: file://bad.yml\n",
+            vec![
+                ("bad.yml", 3, "expected a list of ports"),
+                ("bad.yml", 5, "invalid port"),
+            ],
+        ),
+        (
+            "not a list",
+            "flow: test\nsources:\n  id: Source\nlinks: {}\n",
+            vec![
+                ("flow.yml", 3, "`sources` must be a list"),
+                ("flow.yml", 4, "`links` must be a list"),
+            ],
+        ),
+        (
+            "incomplete links",
+            "\
+flow: test
+links:
+  - from:
+      node: Source
+  - to:
+      node: Sink
+      input: In
+",
+            vec![
+                ("flow.yml", 4, "`from` requires both `node` and `output`"),
+                ("flow.yml", 3, "link without `to`"),
+                ("flow.yml", 5, "link without `from`"),
+            ],
+        ),
+    ];
+
+    for (case, content, expected) in cases.iter() {
+        let (_, diagnostics, _dir) = load(
+            content,
+            &[
+                DESCRIPTORS[0],
+                DESCRIPTORS[2],
+                (
+                    "bad.yml",
+                    "id: Bad\nuri: file://bad.so\ninputs: In\noutputs:\n  - [Out]\n",
+                ),
+            ],
+        );
+        let diagnostics = summarize(&diagnostics);
+        assert_eq!(
+            diagnostics.len(),
+            expected.len(),
+            "{}: {:?}",
+            case,
+            diagnostics
+        );
+        for ((file, severity, line, message), (expected_file, expected_line, prefix)) in
+            diagnostics.iter().zip(expected)
+        {
+            assert_eq!(
+                (file.as_str(), *severity, *line),
+                (*expected_file, Severity::Error, *expected_line),
+                "{}: {}",
+                case,
+                message
+            );
+            assert!(message.starts_with(prefix), "{}: {}", case, message);
+        }
+    }
+}
+
+#[test]
+fn expands_the_variables_of_the_flow_and_of_the_descriptors() {
+    let content = "\
+flow: test
+vars:
+  DIR: .
+  PORT: Out
+sources:
+  - id: Source
+    descriptor: file://{{ DIR }}/source.yml
+";
+    let descriptors = [(
+        "source.yml",
+        "id: Source\nuri: file://source.so\noutputs: [{{ PORT }}]\n",
+    )];
+    let outputs = |vars: &[(&str, &str)]| {
+        let vars = vars
+            .iter()
+            .map(|(name, value)| (name.to_string(), value.to_string()))
+            .collect();
+        let (flow, diagnostics, _dir) = load_with_vars(content, &descriptors, &vars);
+        assert_eq!(diagnostics, Vec::new());
+        flow.unwrap().nodes[0]
+            .outputs
+            .iter()
+            .map(|port| (port.id.clone(), port.line))
+            .collect::<Vec<_>>()
+    };
+
+    assert_eq!(outputs(&[]), [("Out".to_string(), 3)]);
+    assert_eq!(outputs(&[("PORT", "Nile")]), [("Nile".to_string(), 3)]);
+}
+
+#[test]
+fn accepts_the_montblanc_flow() {
+    let workspace = Path::new(env!("CARGO_MANIFEST_DIR")).join("..");
+    let vars = [(
+        "BASE_DIR".to_string(),
+        workspace.to_string_lossy().into_owned(),
+    )]
+    .iter()
+    .cloned()
+    .collect();
+
+    let mut diagnostics = Vec::new();
+    let flow = flow::load(&workspace.join("montblanc.yml"), &vars, &mut diagnostics)
+        .expect("Could not load montblanc.yml");
+    diagnostics.extend(flow::validate(&flow));
+    diagnostics.extend(flow::check_types(&flow, datatypes::port_type));
+    diagnostics.extend(flow::check_codecs(&flow));
+
+    assert_eq!(diagnostics, Vec::new());
+    assert_eq!(flow.nodes.len(), 20);
+}
@@ -0,0 +1,55 @@
+//
+// Copyright (c) 2022 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+use std::path::Path;
+use tools::nodes::{self, NodeKind};
+
+/// The kind and the ports of the node of the crate `name` of this workspace.
+fn scan(name: &str) -> (NodeKind, Vec<String>, Vec<String>) {
+    let workspace = Path::new(env!("CARGO_MANIFEST_DIR")).join("..");
+    let constants =
+        nodes::port_constants(&nodes::parse_file(&workspace.join("datatypes/src/lib.rs")).unwrap());
+    let node = nodes::scan_crate(&workspace.join(name), &constants)
+        .unwrap()
+        .unwrap_or_else(|| panic!("{} is not a node", name));
+    (node.kind, node.inputs, node.outputs)
+}
+
+#[test]
+fn finds_the_ports_declared_with_the_derive() {
+    assert_eq!(
+        scan("lyon"),
+        (
+            NodeKind::Operator,
+            vec!["Amazon".into()],
+            vec!["Tigris".into()]
+        )
+    );
+}
+
+#[test]
+fn finds_the_ports_taken_in_the_constructor() {
+    assert_eq!(
+        scan("arequipa"),
+        (NodeKind::Sink, vec!["Arkansas".into()], vec![])
+    );
+    assert_eq!(
+        scan("validator"),
+        (
+            NodeKind::Operator,
+            vec!["Input".into()],
+            vec!["Valid".into(), "Invalid".into()]
+        )
+    );
+}
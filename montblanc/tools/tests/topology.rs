@@ -0,0 +1,238 @@
+//
+// Copyright (c) 2022 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+use std::collections::{BTreeSet, HashMap};
+use std::fs;
+use std::path::{Path, PathBuf};
+use tools::flow;
+use tools::topology::{self, Topology};
+
+fn workspace() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("..")
+}
+
+fn montblanc() -> Topology {
+    let path = workspace().join("tools/topologies/montblanc.yml");
+    topology::parse(&fs::read_to_string(path).unwrap()).unwrap()
+}
+
+/// A topology with a source and the given operator, sending `Float32` messages on `Amazon`.
+fn with_operator(operator: &str) -> String {
+    format!(
+        "\
+name: Test
+nodes:
+  - name: Cordoba
+    kind: source
+    period_ms: 100
+    outputs:
+      Amazon: Float32
+{}",
+        operator
+    )
+}
+
+#[test]
+fn reports_invalid_topologies_with_their_line() {
+    let cases = [
+        (
+            "duplicate publisher",
+            with_operator(
+                "  - name: Lyon
+    kind: operator
+    trigger: Amazon
+    inputs:
+      Amazon: Float32
+    outputs:
+      Amazon: Float32
+",
+            ),
+            14,
+            "`Amazon` is sent by both `Cordoba` and `Lyon`",
+        ),
+        (
+            "type mismatch",
+            with_operator(
+                "  - name: Lyon
+    kind: operator
+    trigger: Amazon
+    inputs:
+      Amazon: Pose
+    outputs:
+      Tigris: Float32
+",
+            ),
+            12,
+            "`Cordoba` sends `Float32` messages on `Amazon` but `Lyon` expects `Pose`",
+        ),
+        (
+            "no publisher",
+            with_operator(
+                "  - name: Lyon
+    kind: operator
+    trigger: Amazon
+    inputs:
+      Amazon: Float32
+      Nile: Float32
+    outputs:
+      Tigris: Float32
+",
+            ),
+            13,
+            "no node sends `Nile` to `Lyon`",
+        ),
+        (
+            "trigger not an input",
+            with_operator(
+                "  - name: Lyon
+    kind: operator
+    trigger: Nile
+    inputs:
+      Amazon: Float32
+    outputs:
+      Tigris: Float32
+",
+            ),
+            10,
+            "`Nile` is not an input of operator `Lyon`",
+        ),
+        (
+            "trigger and period",
+            with_operator(
+                "  - name: Lyon
+    kind: operator
+    trigger: Amazon
+    period_ms: 100
+    inputs:
+      Amazon: Float32
+    outputs:
+      Tigris: Float32
+",
+            ),
+            8,
+            "operator `Lyon` requires either a `period_ms` or a `trigger`",
+        ),
+        (
+            "crate name clash",
+            with_operator(
+                "  - name: ImageConverter
+    kind: sink
+    inputs:
+      Amazon: Float32
+  - name: Image_Converter
+    kind: sink
+    inputs:
+      Amazon: Float32
+",
+            ),
+            12,
+            "node `Image_Converter` has the same crate as another node",
+        ),
+        (
+            "field name clash",
+            with_operator(
+                "  - name: Lyon
+    kind: operator
+    trigger: Amazon
+    inputs:
+      Amazon: Float32
+    outputs:
+      FooBar: Float32
+      Foo_Bar: Float32
+",
+            ),
+            15,
+            "ports `FooBar` and `Foo_Bar` would have the same field `foo_bar`",
+        ),
+        (
+            "unknown message",
+            with_operator(
+                "  - name: Lyon
+    kind: operator
+    trigger: Amazon
+    inputs:
+      Amazon: Float32
+    outputs:
+      Tigris: Float16
+",
+            ),
+            14,
+            "unknown message `Float16`",
+        ),
+    ];
+
+    for (case, content, line, message) in cases.iter() {
+        let error = topology::parse(content).expect_err(case);
+        assert_eq!(error.line, *line, "{}: {}", case, error);
+        assert!(
+            error.message.starts_with(message),
+            "{}: {}, expected: {}",
+            case,
+            error,
+            message
+        );
+    }
+}
+
+#[test]
+fn links_the_nodes_as_the_montblanc_flow() {
+    let mut diagnostics = Vec::new();
+    let vars = HashMap::from([(
+        "BASE_DIR".to_string(),
+        workspace().to_string_lossy().into_owned(),
+    )]);
+    let flow = flow::load(&workspace().join("montblanc.yml"), &vars, &mut diagnostics)
+        .expect("Could not load montblanc.yml");
+    assert_eq!(diagnostics, Vec::new());
+
+    let expected = flow
+        .links
+        .iter()
+        .map(|link| {
+            assert_eq!(link.from.port, link.to.port);
+            (
+                link.from.node.clone(),
+                link.to.node.clone(),
+                link.from.port.clone(),
+            )
+        })
+        .collect::<BTreeSet<_>>();
+    let links = montblanc()
+        .links()
+        .into_iter()
+        .map(|link| (link.from, link.to, link.port))
+        .collect::<BTreeSet<_>>();
+    assert_eq!(links, expected);
+}
+
+#[test]
+fn generates_valid_rust() {
+    let topology = montblanc();
+    for node in &topology.nodes {
+        let source = node.source(&topology.name);
+        if let Err(e) = syn::parse_file(&source) {
+            panic!("{}: {}\n{}", node.name, e, source);
+        }
+    }
+}
+
+#[test]
+fn generates_a_flow_of_every_copy() {
+    let topology = montblanc();
+    let flow = topology.flow(2, Path::new("/tmp/montblanc"));
+    let links = topology.links().len();
+    assert_eq!(flow.matches("- from:").count(), 2 * links);
+    assert_eq!(flow.matches("node : Lyon_2\n").count(), 2);
+    assert!(flow.starts_with("flow: Montblancx2\n"));
+}
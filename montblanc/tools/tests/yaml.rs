@@ -0,0 +1,204 @@
+//
+// Copyright (c) 2022 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+use serde_json::json;
+use std::collections::HashMap;
+use tools::yaml::{self, Node};
+
+fn vars(vars: &[(&str, &str)]) -> HashMap<String, String> {
+    vars.iter()
+        .map(|(name, value)| (name.to_string(), value.to_string()))
+        .collect()
+}
+
+/// Follows `path` from `node`: a key for the mappings, an index for the sequences.
+fn at<'a>(node: &'a Node, path: &[&str]) -> &'a Node {
+    path.iter()
+        .fold(node, |node, step| match step.parse::<usize>() {
+            Ok(index) => &node.as_sequence().expect("Not a sequence")[index],
+            Err(_) => node.get(step).expect("No such key"),
+        })
+}
+
+#[test]
+fn locates_every_node_at_its_line() {
+    let tree = yaml::parse(
+        "\
+# A comment.
+flow: test
+
+sources:
+  - id: Source
+    ports: [A, B]
+  - id: Other
+    configuration:
+      nested:
+        - 1
+links: []
+anchored: &anchor
+  key: value
+alias: *anchor
+",
+    )
+    .unwrap();
+
+    let cases: &[(&[&str], usize)] = &[
+        (&[], 2),
+        (&["flow"], 2),
+        (&["sources"], 5),
+        (&["sources", "0"], 5),
+        (&["sources", "0", "id"], 5),
+        (&["sources", "0", "ports"], 6),
+        (&["sources", "0", "ports", "1"], 6),
+        (&["sources", "1"], 7),
+        (&["sources", "1", "configuration"], 9),
+        (&["sources", "1", "configuration", "nested", "0"], 10),
+        (&["links"], 11),
+        (&["anchored"], 13),
+        (&["alias"], 13),
+    ];
+    for (path, line) in cases.iter() {
+        assert_eq!(at(&tree, path).line, *line, "{:?}", path);
+    }
+}
+
+#[test]
+fn reports_syntax_errors_with_their_line() {
+    let cases = [
+        ("unclosed sequence", "a: 1\nb: [1, 2\n", 3),
+        ("bad indentation", "a:\n  b: 1\n c: 2\n", 3),
+        ("unknown alias", "a: 1\nb: *nowhere\n", 2),
+    ];
+    for (case, content, line) in cases.iter() {
+        let error = yaml::parse(content).expect_err(case);
+        assert_eq!(error.line, *line, "{}: {}", case, error);
+    }
+}
+
+#[test]
+fn keeps_the_type_of_the_scalars() {
+    let tree = yaml::parse(
+        "\
+integer: 42
+real: 0.5
+boolean: true
+null: ~
+plain: name
+quoted: \"42\"
+list: [1, two]
+",
+    )
+    .unwrap();
+    assert_eq!(
+        tree.to_json(),
+        json!({
+            "integer": 42,
+            "real": 0.5,
+            "boolean": true,
+            "null": null,
+            "plain": "name",
+            "quoted": "42",
+            "list": [1, "two"],
+        })
+    );
+    assert_eq!(yaml::parse("").unwrap().to_json(), json!({}));
+}
+
+#[test]
+fn expands_the_variables() {
+    let defined = vars(&[("BASE_DIR", "/montblanc"), ("EMPTY", "")]);
+    let cases = [
+        ("no variable", "a: b", "a: b"),
+        (
+            "spaces",
+            "uri: {{ BASE_DIR }}/lib.so",
+            "uri: /montblanc/lib.so",
+        ),
+        (
+            "no spaces",
+            "uri: {{BASE_DIR}}/lib.so",
+            "uri: /montblanc/lib.so",
+        ),
+        (
+            "twice",
+            "{{ BASE_DIR }}:{{ BASE_DIR }}",
+            "/montblanc:/montblanc",
+        ),
+        ("empty", "a{{ EMPTY }}b", "ab"),
+        ("not expanded again", "{{ BASE_DIR }}}}", "/montblanc}}"),
+    ];
+    for (case, content, expanded) in cases.iter() {
+        assert_eq!(
+            yaml::expand_vars(content, &defined).as_deref(),
+            Ok(*expanded),
+            "{}",
+            case
+        );
+    }
+}
+
+#[test]
+fn reports_the_variables_it_cannot_expand_with_their_line() {
+    let defined = vars(&[("BASE_DIR", "/montblanc")]);
+    let cases = [
+        (
+            "undefined",
+            "a: 1\nb: {{ UNDEFINED }}\n",
+            2,
+            "undefined variable `UNDEFINED`",
+        ),
+        (
+            "unterminated",
+            "a: {{ BASE_DIR }}\n\nb: {{ BASE_DIR\n",
+            3,
+            "unterminated `{{`",
+        ),
+        (
+            "after a multiline value",
+            "a: |\n  x\n  y\nb: {{ X }}\n",
+            4,
+            "undefined variable `X`",
+        ),
+    ];
+    for (case, content, line, message) in cases.iter() {
+        let error = yaml::expand_vars(content, &defined).expect_err(case);
+        assert_eq!(
+            (error.line, error.message.as_str()),
+            (*line, *message),
+            "{}",
+            case
+        );
+    }
+}
+
+#[test]
+fn reads_the_vars_section() {
+    let tree = yaml::parse(
+        "\
+vars:
+  BASE_DIR: /montblanc
+  QUOTED: \"a b\"
+  LIST: [1, 2]
+",
+    )
+    .unwrap();
+    assert_eq!(
+        yaml::vars(&tree),
+        vars(&[("BASE_DIR", "/montblanc"), ("QUOTED", "a b")])
+    );
+    assert_eq!(
+        yaml::vars(&yaml::parse("vars: []").unwrap()),
+        HashMap::new()
+    );
+}
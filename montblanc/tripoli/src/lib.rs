@@ -14,11 +14,13 @@
 
 use async_std::sync::Mutex;
 use datatypes::data_types;
+use datatypes::datagen::{DataGen, Generate};
+use datatypes::derived::{self, Outputs};
+use datatypes::stats::Statistics;
+use datatypes::Ports;
 use datatypes::{COLUMBIA_PORT, GODAVARI_PORT, LOIRE_PORT};
 use futures::prelude::*;
 use futures::select;
-use prost::Message as _;
-use rand::random;
 use std::sync::Arc;
 use zenoh_flow::prelude::*;
 
@@ -28,60 +30,57 @@ struct TripoliState {
     columbia_last_val: data_types::Image,
 }
 
+impl Generate for TripoliState {
+    fn generate(datagen: &mut DataGen) -> Self {
+        TripoliState {
+            pointcloud2_data: datagen.gen(),
+            columbia_last_val: datagen.gen(),
+        }
+    }
+}
+
 #[export_operator]
+#[derive(Ports)]
 pub struct Tripoli {
+    #[input(COLUMBIA_PORT)]
     input_columbia: Input<data_types::Image>,
+    #[input(GODAVARI_PORT)]
     input_godavari: Input<data_types::LaserScan>,
+    #[output(LOIRE_PORT)]
     output_loire: Output<data_types::PointCloud2>,
+    #[statistics]
+    statistics: Statistics,
+    #[configuration(Outputs::default())]
+    outputs: Outputs,
+    #[generate]
     state: Arc<Mutex<TripoliState>>,
 }
 
-#[async_trait::async_trait]
-impl Operator for Tripoli {
-    async fn new(
-        _context: Context,
-        _configuration: Option<Configuration>,
-        mut inputs: Inputs,
-        mut outputs: Outputs,
-    ) -> Result<Self> {
-        Ok(Self {
-            input_columbia: inputs
-                .take(COLUMBIA_PORT)
-                .unwrap_or_else(|| panic!("No Input called '{}' found", COLUMBIA_PORT))
-                .typed(|buf| Ok(data_types::Image::decode(buf)?)),
-            input_godavari: inputs
-                .take(GODAVARI_PORT)
-                .unwrap_or_else(|| panic!("No Output called '{}' found", GODAVARI_PORT))
-                .typed(|buf| Ok(data_types::LaserScan::decode(buf)?)),
-            output_loire: outputs
-                .take(LOIRE_PORT)
-                .unwrap_or_else(|| panic!("No Output called '{}' found", LOIRE_PORT))
-                .typed(|buf, v: &data_types::PointCloud2| {
-                    buf.resize(v.encoded_len(), 0);
-                    Ok(v.encode(buf)?)
-                }),
-            state: Arc::new(Mutex::new(TripoliState {
-                pointcloud2_data: random(),
-                columbia_last_val: random(),
-            })),
-        })
-    }
-}
-
 #[async_trait::async_trait]
 impl Node for Tripoli {
     async fn iteration(&self) -> Result<()> {
         select! {
             msg = self.input_columbia.recv().fuse() => {
                 if let Ok((Message::Data(inner_data), _ts)) = msg {
+                    self.statistics.received(COLUMBIA_PORT);
                     self.state.lock().await.columbia_last_val = (*inner_data).clone();
                 }
             },
             msg = self.input_godavari.recv().fuse() => {
-                if let Ok((Message::Data(_inner_data), _ts)) = msg {
+                if let Ok((Message::Data(inner_data), ts)) = msg {
+                    self.statistics.received(GODAVARI_PORT);
                     let guard_state = self.state.lock().await;
+                    let pointcloud2 = match self.outputs {
+                        Outputs::Random => guard_state.pointcloud2_data.clone(),
+                        // The points hit by the scan, colored by the latest image of Delhi.
+                        Outputs::Derived => {
+                            derived::colored_cloud(&inner_data, &guard_state.columbia_last_val)
+                                .map_err(|e| zferror!(ErrorKind::InvalidData, "{}", e))?
+                        }
+                    };
 
-                    self.output_loire.send(guard_state.pointcloud2_data.clone(), None).await?;
+                    self.statistics.sent(LOIRE_PORT);
+                    self.output_loire.send(pointcloud2, Some(ts.get_time().as_u64())).await?;
                 }
             }
         }
@@ -0,0 +1,215 @@
+//
+// Copyright (c) 2022 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+use datatypes::codec::{Codec, Payload};
+use datatypes::data_types;
+use datatypes::stats::Statistics;
+use datatypes::validation::Validate;
+use datatypes::{Configurable, Named};
+use zenoh_flow::prelude::*;
+
+static INPUT_PORT: &str = "Input";
+static VALID_PORT: &str = "Valid";
+static INVALID_PORT: &str = "Invalid";
+
+static MESSAGE_KEY: &str = "message";
+static INVALID_KEY: &str = "invalid";
+
+/// The ports of the validator, for messages of type `T`.
+struct Ports<T> {
+    input: Input<T>,
+    valid: Output<T>,
+    /// `None` if invalid messages are dropped.
+    invalid: Option<Output<T>>,
+}
+
+impl<T> Ports<T>
+where
+    T: Payload + Named + Validate + Clone,
+{
+    fn new(
+        inputs: &mut Inputs,
+        outputs: &mut Outputs,
+        codec: Codec,
+        statistics: &Statistics,
+        drop_invalid: bool,
+    ) -> Result<Self> {
+        Ok(Self {
+            input: datatypes::take_input(inputs, INPUT_PORT, codec, statistics)?,
+            valid: datatypes::take_output(outputs, VALID_PORT, codec, statistics)?,
+            invalid: if drop_invalid {
+                None
+            } else {
+                Some(datatypes::take_output(
+                    outputs,
+                    INVALID_PORT,
+                    codec,
+                    statistics,
+                )?)
+            },
+        })
+    }
+
+    async fn forward(&self, statistics: &Statistics) -> Result<()> {
+        let (message, ts) = self.input.recv().await?;
+        if let Message::Data(data) = message {
+            statistics.received(INPUT_PORT);
+            let timestamp = Some(ts.get_time().as_u64());
+            let (port, output) = match data.validate() {
+                Ok(()) => (VALID_PORT, &self.valid),
+                Err(_) => match &self.invalid {
+                    Some(invalid) => (INVALID_PORT, invalid),
+                    None => return Ok(()),
+                },
+            };
+            statistics.sent(port);
+            output.send((*data).clone(), timestamp).await?;
+        }
+        Ok(())
+    }
+}
+
+/// The messages the validator checks.
+enum Validated {
+    Image(Ports<data_types::Image>),
+    PointCloud2(Ports<data_types::PointCloud2>),
+    LaserScan(Ports<data_types::LaserScan>),
+    Quaternion(Ports<data_types::Quaternion>),
+}
+
+/// Forwards the valid messages it receives on `Valid`, and the invalid ones on `Invalid`.
+///
+/// The type of the messages is set by the `message` key of its configuration: `Image`,
+/// `PointCloud2`, `LaserScan` or `Quaternion`. If `invalid` is set to `drop` in the configuration,
+/// invalid messages are dropped instead and `Invalid` does not need to be connected.
+#[export_operator]
+pub struct Validator {
+    validated: Validated,
+    statistics: Statistics,
+}
+
+#[async_trait::async_trait]
+impl Node for Validator {
+    async fn iteration(&self) -> Result<()> {
+        match &self.validated {
+            Validated::Image(ports) => ports.forward(&self.statistics).await,
+            Validated::PointCloud2(ports) => ports.forward(&self.statistics).await,
+            Validated::LaserScan(ports) => ports.forward(&self.statistics).await,
+            Validated::Quaternion(ports) => ports.forward(&self.statistics).await,
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl Operator for Validator {
+    async fn new(
+        _context: Context,
+        configuration: Option<Configuration>,
+        mut inputs: Inputs,
+        mut outputs: Outputs,
+    ) -> Result<Self> {
+        let setting = |key: &str| {
+            configuration
+                .as_ref()
+                .and_then(|configuration| configuration.get(key))
+                .map(|value| {
+                    value.as_str().ok_or_else(|| {
+                        zferror!(
+                            ErrorKind::ConfigurationError,
+                            "`{}` must be a string, found: {}",
+                            key,
+                            value
+                        )
+                    })
+                })
+                .transpose()
+        };
+
+        let drop_invalid = match setting(INVALID_KEY)? {
+            None | Some("flag") => false,
+            Some("drop") => true,
+            Some(other) => {
+                return Err(zferror!(
+                    ErrorKind::ConfigurationError,
+                    "`{}` must be `flag` or `drop`, found: {}",
+                    INVALID_KEY,
+                    other
+                )
+                .into())
+            }
+        };
+
+        let ports = if drop_invalid {
+            vec![VALID_PORT]
+        } else {
+            vec![VALID_PORT, INVALID_PORT]
+        };
+        let statistics =
+            Statistics::new("Validator", &[INPUT_PORT], &ports, configuration.as_ref())?;
+
+        let codec = Codec::default().configure(configuration.as_ref())?;
+        let (inputs, outputs) = (&mut inputs, &mut outputs);
+        let validated = match setting(MESSAGE_KEY)? {
+            Some("Image") => Validated::Image(Ports::new(
+                inputs,
+                outputs,
+                codec,
+                &statistics,
+                drop_invalid,
+            )?),
+            Some("PointCloud2") => Validated::PointCloud2(Ports::new(
+                inputs,
+                outputs,
+                codec,
+                &statistics,
+                drop_invalid,
+            )?),
+            Some("LaserScan") => Validated::LaserScan(Ports::new(
+                inputs,
+                outputs,
+                codec,
+                &statistics,
+                drop_invalid,
+            )?),
+            Some("Quaternion") => Validated::Quaternion(Ports::new(
+                inputs,
+                outputs,
+                codec,
+                &statistics,
+                drop_invalid,
+            )?),
+            Some(other) => {
+                return Err(zferror!(
+                    ErrorKind::ConfigurationError,
+                    "Cannot validate `{}` messages",
+                    other
+                )
+                .into())
+            }
+            None => {
+                return Err(zferror!(
+                    ErrorKind::MissingConfiguration,
+                    "`{}` must be set to the type of the messages to validate",
+                    MESSAGE_KEY
+                )
+                .into())
+            }
+        };
+
+        Ok(Self {
+            validated,
+            statistics,
+        })
+    }
+}
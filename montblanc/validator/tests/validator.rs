@@ -0,0 +1,108 @@
+//
+// Copyright (c) 2022 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+use datatypes::codec::Codec;
+use datatypes::data_types::LaserScan;
+use serde_json::json;
+use validator::Validator;
+use zenoh_flow::prelude::Result;
+use zf_test_harness::{Harness, NodeUnderTest};
+
+static INPUT_PORT: &str = "Input";
+static VALID_PORT: &str = "Valid";
+static INVALID_PORT: &str = "Invalid";
+
+/// A validator of `LaserScan`, with the given `invalid` setting.
+async fn validator(invalid: &str) -> Result<NodeUnderTest<Validator>> {
+    let codec = Codec::default();
+    let mut harness = Harness::new()
+        .configuration(json!({ "message": "LaserScan", "invalid": invalid }))
+        .input(INPUT_PORT, move |buffer: &mut Vec<u8>, scan: &LaserScan| {
+            Ok(codec.encode(scan, buffer)?)
+        })
+        .output(VALID_PORT, move |bytes| {
+            Ok(codec.decode::<LaserScan>(bytes)?)
+        });
+    if invalid == "flag" {
+        harness = harness.output(INVALID_PORT, move |bytes| {
+            Ok(codec.decode::<LaserScan>(bytes)?)
+        });
+    }
+    harness.operator::<Validator>().await
+}
+
+/// A scan of `ranges` ranges, from -1 to 1 radian: only valid if there are 5 of them.
+fn scan(ranges: usize) -> LaserScan {
+    LaserScan {
+        header: None,
+        angle_min: -1.0,
+        angle_max: 1.0,
+        angle_increment: 0.5,
+        time_increment: 0.0,
+        scan_time: 0.1,
+        range_min: 0.1,
+        range_max: 30.0,
+        ranges: vec![1.0; ranges],
+        intensities: vec![],
+    }
+}
+
+#[async_std::test]
+async fn flags_the_invalid_messages() -> Result<()> {
+    let node = validator("flag").await?;
+
+    node.send(INPUT_PORT, scan(4), None).await?;
+    node.iteration().await?;
+    assert_eq!(node.recv::<LaserScan>(INVALID_PORT).await?, scan(4));
+
+    node.send(INPUT_PORT, scan(5), None).await?;
+    node.iteration().await?;
+    assert_eq!(node.recv::<LaserScan>(VALID_PORT).await?, scan(5));
+    Ok(())
+}
+
+#[async_std::test]
+async fn drops_the_invalid_messages() -> Result<()> {
+    let node = validator("drop").await?;
+
+    node.send(INPUT_PORT, scan(4), None).await?;
+    node.iteration().await?;
+    node.send(INPUT_PORT, scan(5), None).await?;
+    node.iteration().await?;
+    // The invalid scan, sent first, was not forwarded on `Valid`.
+    assert_eq!(node.recv::<LaserScan>(VALID_PORT).await?, scan(5));
+    Ok(())
+}
+
+#[async_std::test]
+async fn rejects_unknown_settings() {
+    let codec = Codec::default();
+    let harness = |configuration| {
+        Harness::new()
+            .configuration(configuration)
+            .input(INPUT_PORT, move |buffer: &mut Vec<u8>, scan: &LaserScan| {
+                Ok(codec.encode(scan, buffer)?)
+            })
+            .output(VALID_PORT, move |bytes| {
+                Ok(codec.decode::<LaserScan>(bytes)?)
+            })
+    };
+
+    let invalid = harness(json!({ "message": "LaserScan", "invalid": "ignore" }));
+    assert!(invalid.operator::<Validator>().await.is_err());
+    let message = harness(json!({ "message": "Twist", "invalid": "drop" }));
+    assert!(message.operator::<Validator>().await.is_err());
+    let missing = harness(json!({ "invalid": "drop" }));
+    assert!(missing.operator::<Validator>().await.is_err());
+}
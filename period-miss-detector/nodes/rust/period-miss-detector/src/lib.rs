@@ -17,24 +17,93 @@ use async_std::sync::{Arc, Mutex};
 use prost::Message as pMessage;
 use std::time::{Duration, Instant};
 use zenoh_flow::{anyhow, prelude::*};
+use zf_clock::Clock;
+
+static PERIOD_MS_KEY: &str = "period_ms";
+static TOLERANCE_MS_KEY: &str = "tolerance_ms";
+static DEFAULT_VALUE_KEY: &str = "default_value";
 
 #[export_operator]
 pub struct PeriodMissDetector {
     input: Input<String>,
     output: Output<String>,
     period_duration: Duration,
+    tolerance: Duration,
+    default_value: String,
     next_period: Arc<Mutex<Instant>>,
+    clock: Clock,
+}
+
+/// Returns the value, in milliseconds, associated with `key` in the configuration.
+///
+/// `Ok(None)` is returned if the key is absent, an error if it is present but is not a positive
+/// integer.
+fn get_millis(configuration: &Configuration, key: &str) -> Result<Option<Duration>> {
+    match configuration.get(key) {
+        None => Ok(None),
+        Some(value) => value
+            .as_u64()
+            .map(|millis| Some(Duration::from_millis(millis)))
+            .ok_or_else(|| {
+                zferror!(
+                    ErrorKind::ConfigurationError,
+                    "'{}' must be a positive integer (in milliseconds), found: {}",
+                    key,
+                    value
+                )
+                .into()
+            }),
+    }
 }
 
 #[async_trait::async_trait]
 impl Operator for PeriodMissDetector {
     async fn new(
         _context: Context,
-        _configuration: Option<Configuration>,
+        configuration: Option<Configuration>,
         mut inputs: Inputs,
         mut outputs: Outputs,
     ) -> Result<Self> {
-        let period_duration = Duration::from_secs(5);
+        let configuration = configuration.ok_or_else(|| {
+            zferror!(
+                ErrorKind::MissingConfiguration,
+                "A configuration with at least '{}' is required",
+                PERIOD_MS_KEY
+            )
+        })?;
+
+        let period_duration = get_millis(&configuration, PERIOD_MS_KEY)?.ok_or_else(|| {
+            zferror!(
+                ErrorKind::MissingConfiguration,
+                "Missing '{}' in configuration",
+                PERIOD_MS_KEY
+            )
+        })?;
+        if period_duration.is_zero() {
+            return Err(zferror!(
+                ErrorKind::ConfigurationError,
+                "'{}' must be strictly greater than 0",
+                PERIOD_MS_KEY
+            )
+            .into());
+        }
+
+        let tolerance = get_millis(&configuration, TOLERANCE_MS_KEY)?.unwrap_or_default();
+
+        let default_value = match configuration.get(DEFAULT_VALUE_KEY) {
+            None => 0.0,
+            Some(value) => value.as_f64().ok_or_else(|| {
+                zferror!(
+                    ErrorKind::ConfigurationError,
+                    "'{}' must be a number, found: {}",
+                    DEFAULT_VALUE_KEY,
+                    value
+                )
+            })?,
+        };
+
+        let clock = Clock::from_configuration(Some(&configuration))?;
+
         Ok(PeriodMissDetector {
             input: inputs
                 .take("in")
@@ -47,9 +116,12 @@ impl Operator for PeriodMissDetector {
             // CAVEAT: There can be a delay between the moment the node is created and the moment it
             // is actually run.
             next_period: Arc::new(Mutex::new(
-                Instant::now().checked_add(period_duration).unwrap(),
+                clock.now().checked_add(period_duration).unwrap(),
             )),
+            clock,
             period_duration,
+            tolerance,
+            default_value: format!("(default) {default_value}\n"),
         })
     }
 }
@@ -58,24 +130,29 @@ impl Operator for PeriodMissDetector {
 impl Node for PeriodMissDetector {
     async fn iteration(&self) -> Result<()> {
         let mut next_period = self.next_period.lock().await;
-        let now = Instant::now();
+        let now = self.clock.now();
 
-        let sleep_duration = match next_period.checked_duration_since(now) {
+        // The default value is only sent once the tolerance, on top of the period, has elapsed:
+        // this leaves some slack for the jitter of the sensor.
+        let deadline = next_period
+            .checked_add(self.tolerance)
+            .expect("Could not add duration");
+        let sleep_duration = match deadline.checked_duration_since(now) {
             Some(sleep_duration) => sleep_duration,
             None => {
                 *next_period = next_period
                     .checked_add(self.period_duration)
                     .expect("Could not add duration");
-                self.period_duration
+                self.period_duration + self.tolerance
             }
         };
 
         drop(next_period); // explicitely release the lock
 
         let default = async {
-            async_std::task::sleep(sleep_duration).await;
+            self.clock.sleep(sleep_duration).await;
             self.output
-                .send(String::from("(default) 0\n"), None)
+                .send(self.default_value.clone(), None)
                 .await
                 .expect("output channel disconnected");
 
@@ -98,7 +175,7 @@ impl Node for PeriodMissDetector {
                     // than `period_duration` away) we can safely increase the value of
                     // `next_period` by a single period.
                     let mut next_period = self.next_period.lock().await;
-                    let now = Instant::now();
+                    let now = self.clock.now();
                     if let Some(interval) = next_period.checked_duration_since(now) {
                         if interval < self.period_duration {
                             *next_period = next_period.checked_add(self.period_duration).unwrap();
@@ -0,0 +1,99 @@
+//
+// Copyright (c) 2022 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+use period_miss_detector::PeriodMissDetector;
+use prost::Message;
+use serde_json::{json, Value};
+use std::time::Duration;
+use zenoh_flow::prelude::Result;
+use zf_test_harness::{Harness, ManualClock, NodeUnderTest};
+
+const PERIOD: Duration = Duration::from_millis(100);
+const TOLERANCE: Duration = Duration::from_millis(10);
+
+async fn period_miss_detector(
+    configuration: Value,
+    clock: &ManualClock,
+) -> Result<NodeUnderTest<PeriodMissDetector>> {
+    Harness::new()
+        .configuration(configuration)
+        .clock(clock)
+        .input("in", |buffer: &mut Vec<u8>, value: &String| {
+            buffer.extend_from_slice(value.as_bytes());
+            Ok(())
+        })
+        .output("out", |bytes| Ok(String::decode(bytes)?))
+        .operator::<PeriodMissDetector>()
+        .await
+}
+
+fn configuration() -> Value {
+    json!({
+        "period_ms": PERIOD.as_millis() as u64,
+        "tolerance_ms": TOLERANCE.as_millis() as u64,
+        "default_value": 3.5,
+    })
+}
+
+#[async_std::test]
+async fn forwards_a_value_received_within_the_period() -> Result<()> {
+    let clock = ManualClock::new();
+    let node = period_miss_detector(configuration(), &clock).await?;
+
+    node.send("in", "1.5\n".to_string(), None).await?;
+    node.iteration().await?;
+    assert_eq!(node.recv::<String>("out").await?, "Received: 1.5\n");
+    Ok(())
+}
+
+#[async_std::test]
+async fn sends_the_default_value_once_the_tolerance_elapsed() -> Result<()> {
+    let clock = ManualClock::new();
+    let node = period_miss_detector(configuration(), &clock).await?;
+
+    let mut iteration = Box::pin(node.iteration());
+    assert!(futures::poll!(&mut iteration).is_pending());
+    clock.advance(PERIOD);
+    assert!(futures::poll!(&mut iteration).is_pending());
+    clock.advance(TOLERANCE);
+    iteration.await?;
+
+    assert_eq!(node.recv::<String>("out").await?, "(default) 3.5\n");
+    Ok(())
+}
+
+#[async_std::test]
+async fn sends_the_default_value_at_each_missed_period() -> Result<()> {
+    let clock = ManualClock::new();
+    let node = period_miss_detector(configuration(), &clock).await?;
+
+    for _ in 0..3 {
+        let advance = async {
+            clock.asleep().await;
+            clock.advance(PERIOD + TOLERANCE);
+        };
+        let (iteration, _) = futures::join!(node.iteration(), advance);
+        iteration?;
+        assert_eq!(node.recv::<String>("out").await?, "(default) 3.5\n");
+    }
+    Ok(())
+}
+
+#[async_std::test]
+async fn requires_a_period() {
+    let clock = ManualClock::new();
+    let configuration = json!({ "tolerance_ms": TOLERANCE.as_millis() as u64 });
+
+    assert!(period_miss_detector(configuration, &clock).await.is_err());
+}
@@ -0,0 +1,77 @@
+#
+# Copyright (c) 2022 ZettaScale Technology
+#
+# This program and the accompanying materials are made available under the
+# terms of the Eclipse Public License 2.0 which is available at
+# http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+# which is available at https://www.apache.org/licenses/LICENSE-2.0.
+#
+# SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+#
+# Contributors:
+#   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+#
+
+# Prints, as encoded by pycdr2, the CDR structs that the messages of the tests of the generic
+# transcoder (nodes/rust/generic-transcoder/tests/encoding.rs) are mapped to.
+
+from dataclasses import dataclass
+from pycdr2 import IdlStruct, IdlEnum
+from pycdr2.types import int32, int64, uint32, uint8, float64, sequence
+
+
+class Color(IdlEnum, typename="test.Color"):
+    RED = 0
+    GREEN = 1
+
+
+@dataclass
+class Inner(IdlStruct, typename="test.Inner"):
+    a: int32
+
+
+# A protobuf map is mapped to a sequence of (key, value) structs.
+@dataclass
+class CountsEntry(IdlStruct, typename="test.Outer.CountsEntry"):
+    key: str
+    value: int64
+
+
+# The members are the fields of the message, sorted by field number.
+@dataclass
+class Outer(IdlStruct, typename="test.Outer"):
+    flag: bool
+    value: float64
+    inner: Inner
+    ids: sequence[uint32]
+    color: Color
+    data: sequence[uint8]
+    counts: sequence[CountsEntry]
+    name: str
+
+
+@dataclass
+class Lists(IdlStruct, typename="test.Lists"):
+    names: sequence[str]
+    inners: sequence[Inner]
+    blobs: sequence[sequence[uint8]]
+
+
+messages = {
+    "Outer": Outer(
+        flag=True,
+        value=1.5,
+        inner=Inner(a=-2),
+        ids=[7, 8],
+        color=Color.GREEN,
+        data=[0xab],
+        counts=[CountsEntry(key="k", value=3)],
+        name="hi",
+    ),
+    "Lists": Lists(names=["a", "bc"], inners=[Inner(a=1)], blobs=[[1, 2]]),
+}
+
+for name, message in messages.items():
+    for version in [1, 2]:
+        data = message.serialize(use_version_2=(version == 2))
+        print(f"{name} (XCDR{version}): {', '.join(str(byte) for byte in data)}")
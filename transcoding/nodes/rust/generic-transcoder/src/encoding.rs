@@ -0,0 +1,170 @@
+//
+// Copyright (c) 2022 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+use prost_reflect::{DynamicMessage, Kind, MessageDescriptor, Value};
+use std::collections::HashSet;
+use xcdr::{CdrVersion, CdrWriter};
+use zenoh_flow::prelude::*;
+
+/// The encodings a protobuf message can be transcoded to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Encoding {
+    Cdr(CdrVersion),
+    Json,
+    Cbor,
+}
+
+impl Encoding {
+    /// Returns the encoding whose name, case insensitive, is `name`.
+    pub fn from_name(name: &str) -> Result<Self> {
+        match name.to_lowercase().as_str() {
+            "cdr" | "xcdr1" => Ok(Encoding::Cdr(CdrVersion::Xcdr1)),
+            "xcdr2" => Ok(Encoding::Cdr(CdrVersion::Xcdr2)),
+            "json" => Ok(Encoding::Json),
+            "cbor" => Ok(Encoding::Cbor),
+            _ => Err(zferror!(
+                ErrorKind::ConfigurationError,
+                "Unknown encoding '{}', expected one of: cdr, xcdr1, xcdr2, json, cbor",
+                name
+            )
+            .into()),
+        }
+    }
+
+    /// Returns an error if the messages described by `descriptor` cannot be encoded.
+    ///
+    /// Messages containing themselves through singular fields, e.g. `message Node { Node child =
+    /// 1; }`, cannot be encoded to CDR: absent fields being written with their default value, the
+    /// encoding of such a message would never end. Repeated and map fields end on empty sequences,
+    /// so `message Tree { repeated Tree children = 1; }` or `google.protobuf.Struct` can be
+    /// encoded.
+    pub fn check(&self, descriptor: &MessageDescriptor) -> Result<()> {
+        match self {
+            Encoding::Cdr(_) => {
+                check_not_recursive(descriptor, &mut Vec::new(), &mut HashSet::new())
+            }
+            Encoding::Json | Encoding::Cbor => Ok(()),
+        }
+    }
+
+    pub fn encode(&self, message: &DynamicMessage, buffer: &mut Vec<u8>) -> Result<()> {
+        match self {
+            Encoding::Cdr(version) => {
+                let mut writer = CdrWriter::new(buffer, *version);
+                write_message(&mut writer, message);
+                Ok(())
+            }
+            Encoding::Json => serde_json::to_writer(buffer, message)
+                .map_err(|e| zferror!(ErrorKind::SerializationError, "{:?}", e).into()),
+            Encoding::Cbor => ciborium::ser::into_writer(message, buffer)
+                .map_err(|e| zferror!(ErrorKind::SerializationError, "{:?}", e).into()),
+        }
+    }
+}
+
+/// Returns an error if `descriptor`, or a message it contains, contains itself through singular
+/// fields.
+///
+/// `path` holds the full names of the messages containing `descriptor`, and `checked` the ones
+/// already found not to be recursive.
+fn check_not_recursive(
+    descriptor: &MessageDescriptor,
+    path: &mut Vec<String>,
+    checked: &mut HashSet<String>,
+) -> Result<()> {
+    let name = descriptor.full_name();
+    if checked.contains(name) {
+        return Ok(());
+    }
+    if let Some(start) = path.iter().position(|message| message == name) {
+        return Err(zferror!(
+            ErrorKind::ConfigurationError,
+            "Recursive messages cannot be encoded to CDR: {} -> {}",
+            path[start..].join(" -> "),
+            name
+        )
+        .into());
+    }
+
+    path.push(name.to_string());
+    for field in descriptor.fields() {
+        // The sequences of repeated and map fields may be empty: they cannot recurse forever.
+        if field.is_list() || field.is_map() {
+            continue;
+        }
+        if let Kind::Message(message) = field.kind() {
+            check_not_recursive(&message, path, checked)?;
+        }
+    }
+    path.pop();
+    checked.insert(name.to_string());
+    Ok(())
+}
+
+/// A protobuf message is mapped to a final CDR struct whose members are its fields, sorted by
+/// field number. Absent fields are written with their default value.
+fn write_message(writer: &mut CdrWriter, message: &DynamicMessage) {
+    let mut fields = message.descriptor().fields().collect::<Vec<_>>();
+    fields.sort_by_key(|field| field.number());
+
+    for field in fields {
+        let value = message.get_field(&field);
+        // The entries of a map are structs: like the sequences of strings, bytes or messages, a
+        // map is delimited.
+        if field.is_map() || (field.is_list() && !is_primitive(&field.kind())) {
+            writer.write_delimited(|writer| write_value(writer, &value));
+        } else {
+            write_value(writer, &value);
+        }
+    }
+}
+
+/// Whether the values of `kind` are mapped to a primitive CDR type. Enums are, as their 32 bits
+/// value.
+fn is_primitive(kind: &Kind) -> bool {
+    !matches!(kind, Kind::String | Kind::Bytes | Kind::Message(_))
+}
+
+/// Repeated fields are mapped to sequences, maps to sequences of (key, value) structs, enums to
+/// their 32 bits value and bytes to `sequence<octet>`.
+fn write_value(writer: &mut CdrWriter, value: &Value) {
+    match value {
+        Value::Bool(v) => writer.write_bool(*v),
+        Value::I32(v) => writer.write_i32(*v),
+        Value::I64(v) => writer.write_i64(*v),
+        Value::U32(v) => writer.write_u32(*v),
+        Value::U64(v) => writer.write_u64(*v),
+        Value::F32(v) => writer.write_f32(*v),
+        Value::F64(v) => writer.write_f64(*v),
+        Value::String(v) => writer.write_string(v),
+        Value::Bytes(v) => writer.write_bytes(v),
+        Value::EnumNumber(v) => writer.write_i32(*v),
+        Value::Message(v) => write_message(writer, v),
+        Value::List(values) => {
+            writer.write_sequence_length(values.len());
+            values.iter().for_each(|v| write_value(writer, v));
+        }
+        Value::Map(entries) => {
+            // Sorting the keys makes the output deterministic.
+            let mut entries = entries.iter().collect::<Vec<_>>();
+            entries.sort_by(|(lhs, _), (rhs, _)| lhs.cmp(rhs));
+
+            writer.write_sequence_length(entries.len());
+            for (key, v) in entries {
+                write_value(writer, &Value::from(key.clone()));
+                write_value(writer, v);
+            }
+        }
+    }
+}
@@ -0,0 +1,167 @@
+//
+// Copyright (c) 2022 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+pub mod encoding;
+
+use encoding::Encoding;
+use prost_reflect::{DescriptorPool, DynamicMessage, MessageDescriptor};
+use std::path::Path;
+use zenoh_flow::prelude::*;
+
+static INPUT_PORT: &str = "in";
+static OUTPUT_PORT: &str = "out";
+
+static PROTO_KEY: &str = "proto";
+static INCLUDES_KEY: &str = "includes";
+static MESSAGE_KEY: &str = "message";
+static ENCODING_KEY: &str = "encoding";
+
+/// Transcodes the protobuf messages, whose type is described in a `.proto` file, received on its
+/// input to the encoding provided in its configuration.
+#[export_operator]
+pub struct GenericTranscoder {
+    input: Input<DynamicMessage>,
+    output: Output<DynamicMessage>,
+}
+
+fn get_str<'a>(configuration: &'a Configuration, key: &str) -> Result<&'a str> {
+    let value = configuration.get(key).ok_or_else(|| {
+        zferror!(
+            ErrorKind::MissingConfiguration,
+            "Missing '{}' in configuration",
+            key
+        )
+    })?;
+
+    value.as_str().ok_or_else(|| {
+        zferror!(
+            ErrorKind::ConfigurationError,
+            "'{}' must be a string, found: {}",
+            key,
+            value
+        )
+        .into()
+    })
+}
+
+/// Compiles the `.proto` file and returns the descriptor of the message called `message`.
+///
+/// If no include directories are provided, the directory of the `.proto` file is used.
+pub fn load_descriptor(
+    proto: &str,
+    includes: Vec<String>,
+    message: &str,
+) -> Result<MessageDescriptor> {
+    let includes = if includes.is_empty() {
+        vec![Path::new(proto)
+            .parent()
+            .unwrap_or_else(|| Path::new("."))
+            .to_string_lossy()
+            .into_owned()]
+    } else {
+        includes
+    };
+
+    let file_descriptor_set = protox::compile([proto], includes).map_err(|e| {
+        zferror!(
+            ErrorKind::ConfigurationError,
+            "Could not compile '{}': {}",
+            proto,
+            e
+        )
+    })?;
+    let pool = DescriptorPool::from_file_descriptor_set(file_descriptor_set)
+        .map_err(|e| zferror!(ErrorKind::ConfigurationError, "{}", e))?;
+
+    pool.get_message_by_name(message).ok_or_else(|| {
+        zferror!(
+            ErrorKind::ConfigurationError,
+            "No message '{}' found in '{}'",
+            message,
+            proto
+        )
+        .into()
+    })
+}
+
+#[async_trait::async_trait]
+impl Operator for GenericTranscoder {
+    async fn new(
+        _context: Context,
+        configuration: Option<Configuration>,
+        mut inputs: Inputs,
+        mut outputs: Outputs,
+    ) -> Result<Self> {
+        let configuration = configuration.ok_or_else(|| {
+            zferror!(
+                ErrorKind::MissingConfiguration,
+                "A configuration with '{}', '{}' and '{}' is required",
+                PROTO_KEY,
+                MESSAGE_KEY,
+                ENCODING_KEY
+            )
+        })?;
+
+        let includes = match configuration.get(INCLUDES_KEY) {
+            None => Vec::new(),
+            Some(value) => value
+                .as_array()
+                .and_then(|includes| {
+                    includes
+                        .iter()
+                        .map(|include| include.as_str().map(String::from))
+                        .collect::<Option<Vec<_>>>()
+                })
+                .ok_or_else(|| {
+                    zferror!(
+                        ErrorKind::ConfigurationError,
+                        "'{}' must be a list of strings, found: {}",
+                        INCLUDES_KEY,
+                        value
+                    )
+                })?,
+        };
+
+        let descriptor = load_descriptor(
+            get_str(&configuration, PROTO_KEY)?,
+            includes,
+            get_str(&configuration, MESSAGE_KEY)?,
+        )?;
+        let encoding = Encoding::from_name(get_str(&configuration, ENCODING_KEY)?)?;
+        encoding.check(&descriptor)?;
+
+        Ok(Self {
+            input: inputs
+                .take(INPUT_PORT)
+                .unwrap_or_else(|| panic!("No Input called '{}' found", INPUT_PORT))
+                .typed(move |buf| Ok(DynamicMessage::decode(descriptor.clone(), buf)?)),
+            output: outputs
+                .take(OUTPUT_PORT)
+                .unwrap_or_else(|| panic!("No Output called '{}' found", OUTPUT_PORT))
+                .typed(move |buf, v: &DynamicMessage| encoding.encode(v, buf)),
+        })
+    }
+}
+
+#[async_trait::async_trait]
+impl Node for GenericTranscoder {
+    async fn iteration(&self) -> Result<()> {
+        let (message, _) = self.input.recv().await?;
+        if let Message::Data(data) = message {
+            return self.output.send((*data).clone(), None).await;
+        }
+
+        Ok(())
+    }
+}
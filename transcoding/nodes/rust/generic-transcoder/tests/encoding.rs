@@ -0,0 +1,343 @@
+//
+// Copyright (c) 2022 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+//! The CDR encoding of known messages, and the schemas that cannot be encoded.
+//!
+//! The expected bytes are the ones `pycdr2` encodes the equivalent CDR structs to, as printed by
+//! `cdr-reference.py` at the root of the transcoding example.
+
+use generic_transcoder::encoding::Encoding;
+use generic_transcoder::load_descriptor;
+use prost_reflect::{DynamicMessage, MessageDescriptor};
+use std::fs;
+use tempfile::TempDir;
+use xcdr::CdrVersion;
+use zenoh_flow::prelude::Result;
+
+static SCHEMA: &str = r#"
+syntax = "proto3";
+package test;
+
+import "google/protobuf/struct.proto";
+
+enum Color {
+    RED = 0;
+    GREEN = 1;
+}
+
+message Inner {
+    int32 a = 1;
+}
+
+// The fields are encoded in the order of their numbers, not of their declaration.
+message Outer {
+    string name = 8;
+    bool flag = 1;
+    double value = 2;
+    Inner inner = 3;
+    repeated uint32 ids = 4;
+    Color color = 5;
+    bytes data = 6;
+    map<string, int64> counts = 7;
+}
+
+message Node {
+    Node child = 1;
+}
+
+message Ping {
+    Pong pong = 1;
+}
+
+message Pong {
+    int32 value = 1;
+    Ping ping = 2;
+}
+
+message Tree {
+    repeated Branch branches = 1;
+}
+
+message Branch {
+    map<string, Tree> trees = 1;
+}
+
+message Json {
+    google.protobuf.Struct value = 1;
+}
+
+message Lists {
+    repeated string names = 1;
+    repeated Inner inners = 2;
+    repeated bytes blobs = 3;
+}
+"#;
+
+/// Compiles `SCHEMA` and returns the descriptor of `test.<message>`.
+fn descriptor(message: &str) -> Result<MessageDescriptor> {
+    let dir = TempDir::new()?;
+    let proto = dir.path().join("test.proto");
+    fs::write(&proto, SCHEMA)?;
+    load_descriptor(
+        &proto.to_string_lossy(),
+        Vec::new(),
+        &format!("test.{}", message),
+    )
+}
+
+fn encode(encoding: Encoding, descriptor: MessageDescriptor, json: &str) -> Result<Vec<u8>> {
+    encoding.check(&descriptor)?;
+    let mut deserializer = serde_json::Deserializer::from_str(json);
+    let message = DynamicMessage::deserialize(descriptor, &mut deserializer)?;
+    deserializer.end()?;
+
+    let mut buffer = Vec::new();
+    encoding.encode(&message, &mut buffer)?;
+    Ok(buffer)
+}
+
+static OUTER: &str = r#"{
+    "name": "hi",
+    "flag": true,
+    "value": 1.5,
+    "inner": { "a": -2 },
+    "ids": [7, 8],
+    "color": "GREEN",
+    "data": "qw==",
+    "counts": { "k": "3" }
+}"#;
+
+#[test]
+fn encodes_messages_to_xcdr1() -> Result<()> {
+    let buffer = encode(
+        Encoding::Cdr(CdrVersion::Xcdr1),
+        descriptor("Outer")?,
+        OUTER,
+    )?;
+
+    #[rustfmt::skip]
+    let expected = [
+        0x00, 0x01, 0x00, 0x00,
+        // flag, value
+        1, 0, 0, 0, 0, 0, 0, 0,
+        0, 0, 0, 0, 0, 0, 0xf8, 0x3f,
+        // inner.a
+        0xfe, 0xff, 0xff, 0xff,
+        // ids
+        2, 0, 0, 0, 7, 0, 0, 0, 8, 0, 0, 0,
+        // color
+        1, 0, 0, 0,
+        // data
+        1, 0, 0, 0, 0xab, 0, 0, 0,
+        // counts: one entry ("k", 3)
+        1, 0, 0, 0,
+        2, 0, 0, 0, b'k', 0, 0, 0,
+        3, 0, 0, 0, 0, 0, 0, 0,
+        // name
+        3, 0, 0, 0, b'h', b'i', 0,
+    ];
+    assert_eq!(buffer, expected);
+    Ok(())
+}
+
+#[test]
+fn encodes_messages_to_xcdr2() -> Result<()> {
+    let buffer = encode(
+        Encoding::Cdr(CdrVersion::Xcdr2),
+        descriptor("Outer")?,
+        OUTER,
+    )?;
+
+    #[rustfmt::skip]
+    let expected = [
+        0x00, 0x07, 0x00, 0x00,
+        // flag, value
+        1, 0, 0, 0,
+        0, 0, 0, 0, 0, 0, 0xf8, 0x3f,
+        // inner.a
+        0xfe, 0xff, 0xff, 0xff,
+        // ids
+        2, 0, 0, 0, 7, 0, 0, 0, 8, 0, 0, 0,
+        // color
+        1, 0, 0, 0,
+        // data
+        1, 0, 0, 0, 0xab, 0, 0, 0,
+        // counts: the DHEADER of the sequence of entries, and one entry ("k", 3)
+        20, 0, 0, 0,
+        1, 0, 0, 0,
+        2, 0, 0, 0, b'k', 0, 0, 0,
+        3, 0, 0, 0, 0, 0, 0, 0,
+        // name
+        3, 0, 0, 0, b'h', b'i', 0,
+    ];
+    assert_eq!(buffer, expected);
+    Ok(())
+}
+
+static LISTS: &str = r#"{ "names": ["a", "bc"], "inners": [{ "a": 1 }], "blobs": ["AQI="] }"#;
+
+#[test]
+fn encodes_sequences_of_non_primitive_types_to_xcdr1() -> Result<()> {
+    let buffer = encode(
+        Encoding::Cdr(CdrVersion::Xcdr1),
+        descriptor("Lists")?,
+        LISTS,
+    )?;
+
+    #[rustfmt::skip]
+    let expected = [
+        0x00, 0x01, 0x00, 0x00,
+        // names
+        2, 0, 0, 0,
+        2, 0, 0, 0, b'a', 0, 0, 0,
+        3, 0, 0, 0, b'b', b'c', 0, 0,
+        // inners
+        1, 0, 0, 0, 1, 0, 0, 0,
+        // blobs
+        1, 0, 0, 0, 2, 0, 0, 0, 1, 2,
+    ];
+    assert_eq!(buffer, expected);
+    Ok(())
+}
+
+#[test]
+fn delimits_sequences_of_non_primitive_types_in_xcdr2() -> Result<()> {
+    let buffer = encode(
+        Encoding::Cdr(CdrVersion::Xcdr2),
+        descriptor("Lists")?,
+        LISTS,
+    )?;
+
+    #[rustfmt::skip]
+    let expected = [
+        0x00, 0x07, 0x00, 0x00,
+        // names
+        19, 0, 0, 0,
+        2, 0, 0, 0,
+        2, 0, 0, 0, b'a', 0, 0, 0,
+        3, 0, 0, 0, b'b', b'c', 0, 0,
+        // inners
+        8, 0, 0, 0,
+        1, 0, 0, 0, 1, 0, 0, 0,
+        // blobs
+        10, 0, 0, 0,
+        1, 0, 0, 0, 2, 0, 0, 0, 1, 2,
+    ];
+    assert_eq!(buffer, expected);
+    Ok(())
+}
+
+#[test]
+fn encodes_absent_fields_with_their_default_value() -> Result<()> {
+    let buffer = encode(Encoding::Cdr(CdrVersion::Xcdr1), descriptor("Outer")?, "{}")?;
+
+    #[rustfmt::skip]
+    let expected = [
+        0x00, 0x01, 0x00, 0x00,
+        0, 0, 0, 0, 0, 0, 0, 0,
+        0, 0, 0, 0, 0, 0, 0, 0,
+        0, 0, 0, 0,
+        0, 0, 0, 0,
+        0, 0, 0, 0,
+        0, 0, 0, 0,
+        0, 0, 0, 0,
+        1, 0, 0, 0, 0,
+    ];
+    assert_eq!(buffer, expected);
+    Ok(())
+}
+
+#[test]
+fn encodes_my_msg_as_the_transcoder_does() -> Result<()> {
+    let proto = concat!(env!("CARGO_MANIFEST_DIR"), "/../../../message.proto");
+    let descriptor = load_descriptor(proto, Vec::new(), "MyMsg")?;
+    let buffer = encode(
+        Encoding::Cdr(CdrVersion::Xcdr1),
+        descriptor,
+        r#"{ "uValue": "72623859790382856", "sValue": "hello" }"#,
+    )?;
+
+    #[rustfmt::skip]
+    let expected = [
+        0x00, 0x01, 0x00, 0x00,
+        0x08, 0x07, 0x06, 0x05, 0x04, 0x03, 0x02, 0x01,
+        6, 0, 0, 0, b'h', b'e', b'l', b'l', b'o', 0,
+    ];
+    assert_eq!(buffer, expected);
+    Ok(())
+}
+
+#[test]
+fn rejects_messages_containing_themselves_for_cdr() -> Result<()> {
+    let cases = [
+        ("Node", "test.Node -> test.Node"),
+        ("Ping", "test.Ping -> test.Pong -> test.Ping"),
+        ("Pong", "test.Pong -> test.Ping -> test.Pong"),
+    ];
+    for (message, cycle) in cases.iter() {
+        let error = Encoding::Cdr(CdrVersion::Xcdr1)
+            .check(&descriptor(message)?)
+            .unwrap_err();
+        assert!(
+            error.to_string().contains(&format!(
+                "Recursive messages cannot be encoded to CDR: {}",
+                cycle
+            )),
+            "{}: {}",
+            message,
+            error
+        );
+    }
+    Ok(())
+}
+
+#[test]
+fn accepts_messages_containing_themselves_through_sequences_for_cdr() -> Result<()> {
+    for message in ["Tree", "Branch", "Json"] {
+        Encoding::Cdr(CdrVersion::Xcdr1).check(&descriptor(message)?)?;
+    }
+
+    let buffer = encode(
+        Encoding::Cdr(CdrVersion::Xcdr1),
+        descriptor("Tree")?,
+        r#"{ "branches": [{ "trees": { "x": {} } }] }"#,
+    )?;
+    #[rustfmt::skip]
+    let expected = [
+        0x00, 0x01, 0x00, 0x00,
+        // branches, and the trees of the only branch
+        1, 0, 0, 0,
+        1, 0, 0, 0,
+        // the entry ("x", the tree without branches)
+        2, 0, 0, 0, b'x', 0, 0, 0,
+        0, 0, 0, 0,
+    ];
+    assert_eq!(buffer, expected);
+
+    // The encoding of a `google.protobuf.Struct` ends with its innermost values.
+    encode(
+        Encoding::Cdr(CdrVersion::Xcdr2),
+        descriptor("Json")?,
+        r#"{ "value": { "a": [1, { "b": null }] } }"#,
+    )?;
+    Ok(())
+}
+
+#[test]
+fn accepts_recursive_messages_for_json_and_cbor() -> Result<()> {
+    for encoding in [Encoding::Json, Encoding::Cbor] {
+        encoding.check(&descriptor("Node")?)?;
+    }
+    Ok(())
+}
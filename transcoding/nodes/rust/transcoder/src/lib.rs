@@ -0,0 +1,110 @@
+//
+// Copyright (c) 2022 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+use prost::Message as pMessage;
+use xcdr::{CdrVersion, CdrWriter};
+use zenoh_flow::prelude::*;
+
+pub mod message {
+    include!(concat!(env!("OUT_DIR"), "/_.rs"));
+}
+
+static INPUT_PORT: &str = "in";
+static OUTPUT_PORT: &str = "out";
+static ENCODING_KEY: &str = "encoding";
+
+/// The CDR counterpart of `MyMsg`, see the `MyStruct` class in `sub-cdr.py`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MyStruct {
+    pub u_value: u64,
+    pub s_value: String,
+}
+
+impl From<message::MyMsg> for MyStruct {
+    fn from(msg: message::MyMsg) -> Self {
+        Self {
+            u_value: msg.u_value,
+            s_value: msg.s_value,
+        }
+    }
+}
+
+impl MyStruct {
+    /// Appends the encapsulation header and the CDR encoding of this struct to `buffer`.
+    pub fn encode_cdr(&self, buffer: &mut Vec<u8>, version: CdrVersion) {
+        let mut writer = CdrWriter::new(buffer, version);
+        writer.write_u64(self.u_value);
+        writer.write_string(&self.s_value);
+    }
+}
+
+#[export_operator]
+pub struct Transcoder {
+    input: Input<message::MyMsg>,
+    output: Output<MyStruct>,
+}
+
+#[async_trait::async_trait]
+impl Operator for Transcoder {
+    async fn new(
+        _context: Context,
+        configuration: Option<Configuration>,
+        mut inputs: Inputs,
+        mut outputs: Outputs,
+    ) -> Result<Self> {
+        let version = match configuration
+            .as_ref()
+            .and_then(|configuration| configuration.get(ENCODING_KEY))
+        {
+            None => CdrVersion::Xcdr1,
+            Some(value) => CdrVersion::from_name(value.as_str().ok_or_else(|| {
+                zferror!(
+                    ErrorKind::ConfigurationError,
+                    "'{}' must be a string, found: {}",
+                    ENCODING_KEY,
+                    value
+                )
+            })?)?,
+        };
+
+        Ok(Self {
+            input: inputs
+                .take(INPUT_PORT)
+                .unwrap_or_else(|| panic!("No Input called '{}' found", INPUT_PORT))
+                .typed(|buf| Ok(message::MyMsg::decode(buf)?)),
+            output: outputs
+                .take(OUTPUT_PORT)
+                .unwrap_or_else(|| panic!("No Output called '{}' found", OUTPUT_PORT))
+                .typed(move |buf, v: &MyStruct| {
+                    v.encode_cdr(buf, version);
+                    Ok(())
+                }),
+        })
+    }
+}
+
+#[async_trait::async_trait]
+impl Node for Transcoder {
+    async fn iteration(&self) -> Result<()> {
+        let (message, _) = self.input.recv().await?;
+        if let Message::Data(data) = message {
+            return self
+                .output
+                .send(MyStruct::from((*data).clone()), None)
+                .await;
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,66 @@
+//
+// Copyright (c) 2022 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+use transcoder::message::MyMsg;
+use transcoder::MyStruct;
+use xcdr::CdrVersion;
+
+fn my_struct() -> MyStruct {
+    MyStruct::from(MyMsg {
+        u_value: 0x0102_0304_0506_0708,
+        s_value: "hello".to_string(),
+    })
+}
+
+#[test]
+fn encodes_my_msg_as_sub_cdr_decodes_it_in_xcdr1() {
+    let mut buffer = Vec::new();
+    my_struct().encode_cdr(&mut buffer, CdrVersion::Xcdr1);
+
+    #[rustfmt::skip]
+    let expected = [
+        0x00, 0x01, 0x00, 0x00,
+        0x08, 0x07, 0x06, 0x05, 0x04, 0x03, 0x02, 0x01,
+        6, 0, 0, 0, b'h', b'e', b'l', b'l', b'o', 0,
+    ];
+    assert_eq!(buffer, expected);
+}
+
+#[test]
+fn encodes_my_msg_as_sub_cdr_decodes_it_in_xcdr2() {
+    let mut buffer = Vec::new();
+    my_struct().encode_cdr(&mut buffer, CdrVersion::Xcdr2);
+
+    #[rustfmt::skip]
+    let expected = [
+        0x00, 0x07, 0x00, 0x00,
+        0x08, 0x07, 0x06, 0x05, 0x04, 0x03, 0x02, 0x01,
+        6, 0, 0, 0, b'h', b'e', b'l', b'l', b'o', 0,
+    ];
+    assert_eq!(buffer, expected);
+}
+
+#[test]
+fn encodes_empty_strings_as_a_nul_character() {
+    let mut buffer = Vec::new();
+    MyStruct::from(MyMsg::default()).encode_cdr(&mut buffer, CdrVersion::Xcdr1);
+
+    #[rustfmt::skip]
+    let expected = [
+        0x00, 0x01, 0x00, 0x00,
+        0, 0, 0, 0, 0, 0, 0, 0,
+        1, 0, 0, 0, 0,
+    ];
+    assert_eq!(buffer, expected);
+}
@@ -0,0 +1,163 @@
+//
+// Copyright (c) 2022 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+use zenoh_flow::prelude::*;
+
+/// The version of the (Extended) CDR representation used to encode a payload.
+///
+/// Only little-endian encodings of final (i.e. non-extensible) types are supported, which is what
+/// `pycdr2` produces for an `IdlStruct`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CdrVersion {
+    Xcdr1,
+    Xcdr2,
+}
+
+impl CdrVersion {
+    /// Returns the version whose name, case insensitive, is `name`.
+    pub fn from_name(name: &str) -> Result<Self> {
+        match name.to_lowercase().as_str() {
+            "xcdr1" | "cdr" => Ok(CdrVersion::Xcdr1),
+            "xcdr2" => Ok(CdrVersion::Xcdr2),
+            _ => Err(zferror!(
+                ErrorKind::ConfigurationError,
+                "Unknown CDR version '{}', expected one of: xcdr1, xcdr2",
+                name
+            )
+            .into()),
+        }
+    }
+
+    /// The 4 bytes that prefix any CDR payload: a 2-bytes representation identifier followed by
+    /// 2-bytes of options (unused, hence set to 0).
+    fn encapsulation_header(&self) -> [u8; 4] {
+        match self {
+            // CDR_LE
+            CdrVersion::Xcdr1 => [0x00, 0x01, 0x00, 0x00],
+            // PLAIN_CDR2_LE
+            CdrVersion::Xcdr2 => [0x00, 0x07, 0x00, 0x00],
+        }
+    }
+
+    /// XCDR2 caps the alignment of primitive types to 4 bytes, XCDR1 aligns them on their size.
+    fn max_alignment(&self) -> usize {
+        match self {
+            CdrVersion::Xcdr1 => 8,
+            CdrVersion::Xcdr2 => 4,
+        }
+    }
+}
+
+/// Writes little-endian CDR data at the end of a buffer.
+///
+/// The encapsulation header is written upon creation. Alignments are computed relatively to the
+/// first byte following that header, as mandated by the specification.
+pub struct CdrWriter<'a> {
+    buffer: &'a mut Vec<u8>,
+    origin: usize,
+    version: CdrVersion,
+}
+
+impl<'a> CdrWriter<'a> {
+    pub fn new(buffer: &'a mut Vec<u8>, version: CdrVersion) -> Self {
+        buffer.extend_from_slice(&version.encapsulation_header());
+        let origin = buffer.len();
+        Self {
+            buffer,
+            origin,
+            version,
+        }
+    }
+
+    fn align(&mut self, size: usize) {
+        let alignment = size.min(self.version.max_alignment());
+        let offset = self.buffer.len() - self.origin;
+        let padding = (alignment - offset % alignment) % alignment;
+        self.buffer.resize(self.buffer.len() + padding, 0);
+    }
+
+    pub fn write_bool(&mut self, value: bool) {
+        self.buffer.push(value as u8);
+    }
+
+    pub fn write_u8(&mut self, value: u8) {
+        self.buffer.push(value);
+    }
+
+    pub fn write_i32(&mut self, value: i32) {
+        self.align(4);
+        self.buffer.extend_from_slice(&value.to_le_bytes());
+    }
+
+    pub fn write_u32(&mut self, value: u32) {
+        self.align(4);
+        self.buffer.extend_from_slice(&value.to_le_bytes());
+    }
+
+    pub fn write_i64(&mut self, value: i64) {
+        self.align(8);
+        self.buffer.extend_from_slice(&value.to_le_bytes());
+    }
+
+    pub fn write_u64(&mut self, value: u64) {
+        self.align(8);
+        self.buffer.extend_from_slice(&value.to_le_bytes());
+    }
+
+    pub fn write_f32(&mut self, value: f32) {
+        self.align(4);
+        self.buffer.extend_from_slice(&value.to_le_bytes());
+    }
+
+    pub fn write_f64(&mut self, value: f64) {
+        self.align(8);
+        self.buffer.extend_from_slice(&value.to_le_bytes());
+    }
+
+    /// A string is encoded as its length, terminating NUL character included, followed by its
+    /// bytes.
+    pub fn write_string(&mut self, value: &str) {
+        self.write_u32(value.len() as u32 + 1);
+        self.buffer.extend_from_slice(value.as_bytes());
+        self.buffer.push(0);
+    }
+
+    /// A `sequence<octet>` is encoded as its length followed by its bytes.
+    pub fn write_bytes(&mut self, value: &[u8]) {
+        self.write_u32(value.len() as u32);
+        self.buffer.extend_from_slice(value);
+    }
+
+    /// Writes the length of a sequence, its elements are expected to be written right after.
+    pub fn write_sequence_length(&mut self, length: usize) {
+        self.write_u32(length as u32);
+    }
+
+    /// Writes what `write` writes, preceded in XCDR2 by its DHEADER: its size in bytes.
+    ///
+    /// XCDR2 requires a DHEADER before the sequences, and maps, whose elements are not of a
+    /// primitive type (e.g. strings, sequences or structs). XCDR1 has none.
+    pub fn write_delimited(&mut self, write: impl FnOnce(&mut Self)) {
+        if self.version == CdrVersion::Xcdr1 {
+            return write(self);
+        }
+
+        self.align(4);
+        let dheader = self.buffer.len();
+        self.buffer.extend_from_slice(&[0; 4]);
+        write(self);
+        let size = (self.buffer.len() - dheader - 4) as u32;
+        self.buffer[dheader..dheader + 4].copy_from_slice(&size.to_le_bytes());
+    }
+}
@@ -0,0 +1,158 @@
+//
+// Copyright (c) 2022 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+//! The bytes written for known structs, as laid out by the XCDR1 and XCDR2 final encodings.
+
+use xcdr::{CdrVersion, CdrWriter};
+
+fn encode(version: CdrVersion, write: impl FnOnce(&mut CdrWriter)) -> Vec<u8> {
+    let mut buffer = Vec::new();
+    write(&mut CdrWriter::new(&mut buffer, version));
+    buffer
+}
+
+/// `struct { uint64 u; string s; }` with `u = 42` and `s = "hello"`.
+fn write_u64_and_string(writer: &mut CdrWriter) {
+    writer.write_u64(42);
+    writer.write_string("hello");
+}
+
+#[test]
+fn writes_the_encapsulation_header() {
+    assert_eq!(encode(CdrVersion::Xcdr1, |_| ()), [0x00, 0x01, 0x00, 0x00]);
+    assert_eq!(encode(CdrVersion::Xcdr2, |_| ()), [0x00, 0x07, 0x00, 0x00]);
+}
+
+#[test]
+fn writes_strings_with_their_nul_character() {
+    #[rustfmt::skip]
+    let expected = [
+        0x00, 0x01, 0x00, 0x00,
+        42, 0, 0, 0, 0, 0, 0, 0,
+        6, 0, 0, 0, b'h', b'e', b'l', b'l', b'o', 0,
+    ];
+    assert_eq!(encode(CdrVersion::Xcdr1, write_u64_and_string), expected);
+}
+
+#[test]
+fn aligns_relatively_to_the_end_of_the_header() {
+    // `struct { boolean b; uint32 u; }`: the header is not counted in the padding of `u`.
+    #[rustfmt::skip]
+    let expected = [
+        0x00, 0x01, 0x00, 0x00,
+        1, 0, 0, 0,
+        7, 0, 0, 0,
+    ];
+    let written = encode(CdrVersion::Xcdr1, |writer| {
+        writer.write_bool(true);
+        writer.write_u32(7);
+    });
+    assert_eq!(written, expected);
+}
+
+/// `struct { boolean b; double d; sequence<uint32> s; sequence<octet> o; int64 i; }`.
+fn write_mixed(writer: &mut CdrWriter) {
+    writer.write_bool(true);
+    writer.write_f64(1.5);
+    writer.write_sequence_length(2);
+    writer.write_u32(7);
+    writer.write_u32(8);
+    writer.write_bytes(&[0xab]);
+    writer.write_i64(-3);
+}
+
+#[test]
+fn aligns_8_bytes_types_on_8_bytes_in_xcdr1() {
+    #[rustfmt::skip]
+    let expected = [
+        0x00, 0x01, 0x00, 0x00,
+        1, 0, 0, 0, 0, 0, 0, 0,
+        0, 0, 0, 0, 0, 0, 0xf8, 0x3f,
+        2, 0, 0, 0, 7, 0, 0, 0, 8, 0, 0, 0,
+        1, 0, 0, 0, 0xab, 0, 0, 0, 0, 0, 0, 0,
+        0xfd, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+    ];
+    assert_eq!(encode(CdrVersion::Xcdr1, write_mixed), expected);
+}
+
+#[test]
+fn aligns_8_bytes_types_on_4_bytes_in_xcdr2() {
+    #[rustfmt::skip]
+    let expected = [
+        0x00, 0x07, 0x00, 0x00,
+        1, 0, 0, 0,
+        0, 0, 0, 0, 0, 0, 0xf8, 0x3f,
+        2, 0, 0, 0, 7, 0, 0, 0, 8, 0, 0, 0,
+        1, 0, 0, 0, 0xab, 0, 0, 0,
+        0xfd, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+    ];
+    assert_eq!(encode(CdrVersion::Xcdr2, write_mixed), expected);
+}
+
+/// `struct { sequence<string> s; octet o; }` with `s = ["a", "bc"]`: the elements of the
+/// sequence are not primitive.
+fn write_strings(writer: &mut CdrWriter) {
+    writer.write_delimited(|writer| {
+        writer.write_sequence_length(2);
+        writer.write_string("a");
+        writer.write_string("bc");
+    });
+    writer.write_u8(9);
+}
+
+#[test]
+fn writes_no_dheader_in_xcdr1() {
+    #[rustfmt::skip]
+    let expected = [
+        0x00, 0x01, 0x00, 0x00,
+        2, 0, 0, 0,
+        2, 0, 0, 0, b'a', 0, 0, 0,
+        3, 0, 0, 0, b'b', b'c', 0,
+        9,
+    ];
+    assert_eq!(encode(CdrVersion::Xcdr1, write_strings), expected);
+}
+
+#[test]
+fn writes_the_size_of_delimited_types_in_xcdr2() {
+    // The DHEADER counts the bytes following it up to the end of the sequence, padding included.
+    #[rustfmt::skip]
+    let expected = [
+        0x00, 0x07, 0x00, 0x00,
+        19, 0, 0, 0,
+        2, 0, 0, 0,
+        2, 0, 0, 0, b'a', 0, 0, 0,
+        3, 0, 0, 0, b'b', b'c', 0,
+        9,
+    ];
+    assert_eq!(encode(CdrVersion::Xcdr2, write_strings), expected);
+
+    // An empty sequence is 4 bytes long: its length.
+    let written = encode(CdrVersion::Xcdr2, |writer| {
+        writer.write_bool(true);
+        writer.write_delimited(|writer| writer.write_sequence_length(0));
+    });
+    assert_eq!(
+        written,
+        [0x00, 0x07, 0x00, 0x00, 1, 0, 0, 0, 4, 0, 0, 0, 0, 0, 0, 0]
+    );
+}
+
+#[test]
+fn parses_the_version_names() {
+    assert_eq!(CdrVersion::from_name("CDR").unwrap(), CdrVersion::Xcdr1);
+    assert_eq!(CdrVersion::from_name("xcdr1").unwrap(), CdrVersion::Xcdr1);
+    assert_eq!(CdrVersion::from_name("XCDR2").unwrap(), CdrVersion::Xcdr2);
+    assert!(CdrVersion::from_name("xcdr3").is_err());
+}
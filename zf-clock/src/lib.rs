@@ -0,0 +1,308 @@
+//
+// Copyright (c) 2022 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+//! The time of a node, which a test can control.
+//!
+//! A node that reads the time or sleeps through a [`Clock`] rather than `Instant::now()`,
+//! `SystemTime::now()` and `async_std::task::sleep` uses the time of the system, unless its
+//! configuration names a [`ManualClock`] under [`CLOCK_KEY`]: time then only passes when the test
+//! advances it. A manual clock only exists in the process of the test that created it, this key is
+//! thus meant for the tests only, not for the descriptors of a data flow.
+
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard, Weak};
+use std::task::{Context, Poll, Waker};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use zenoh_flow::prelude::{zferror, Configuration, ErrorKind, Result};
+
+/// The key of the configuration naming the clock of a node, for the tests only: `system` or the
+/// [`name`](ManualClock::name) of a manual clock created by the test.
+pub static CLOCK_KEY: &str = "clock";
+
+static MANUAL_PREFIX: &str = "manual:";
+
+/// The manual clocks alive, with their id.
+static MANUAL_CLOCKS: Mutex<Vec<(u64, Weak<Shared>)>> = Mutex::new(Vec::new());
+/// The id of the next manual clock: an id is never reused, so that the name of a dropped clock
+/// cannot designate another one.
+static NEXT_MANUAL_ID: AtomicU64 = AtomicU64::new(0);
+
+/// The clock a node reads the time from and sleeps on.
+#[derive(Debug, Clone, Default)]
+pub struct Clock {
+    manual: Option<Arc<Shared>>,
+}
+
+impl Clock {
+    /// The time of the system.
+    pub fn system() -> Self {
+        Self::default()
+    }
+
+    /// The clock named under [`CLOCK_KEY`] in the configuration, or the time of the system if there
+    /// is none.
+    pub fn from_configuration(configuration: Option<&Configuration>) -> Result<Self> {
+        Ok(Self::configured(configuration)?.unwrap_or_default())
+    }
+
+    /// The clock named under [`CLOCK_KEY`] in the configuration, if any.
+    pub fn configured(configuration: Option<&Configuration>) -> Result<Option<Self>> {
+        let value = match configuration.and_then(|configuration| configuration.get(CLOCK_KEY)) {
+            Some(value) => value,
+            None => return Ok(None),
+        };
+        let invalid = || {
+            zferror!(
+                ErrorKind::ConfigurationError,
+                "'{}' must be \"system\" or the id of a manual clock, found: {}",
+                CLOCK_KEY,
+                value
+            )
+        };
+
+        let name = value.as_str().ok_or_else(invalid)?;
+        if name == "system" {
+            return Ok(Some(Self::system()));
+        }
+        let id = name
+            .strip_prefix(MANUAL_PREFIX)
+            .and_then(|id| id.parse::<u64>().ok())
+            .ok_or_else(invalid)?;
+        let shared = lock(&MANUAL_CLOCKS)
+            .iter()
+            .find(|(clock, _)| *clock == id)
+            .and_then(|(_, shared)| shared.upgrade())
+            .ok_or_else(|| {
+                zferror!(
+                    ErrorKind::ConfigurationError,
+                    "The manual clock {} was dropped",
+                    name
+                )
+            })?;
+        Ok(Some(Self {
+            manual: Some(shared),
+        }))
+    }
+
+    pub fn now(&self) -> Instant {
+        match &self.manual {
+            Some(shared) => shared.now(),
+            None => Instant::now(),
+        }
+    }
+
+    /// The wall-clock time, as a duration since the UNIX epoch, e.g. to stamp messages.
+    pub fn since_epoch(&self) -> Duration {
+        match &self.manual {
+            Some(shared) => shared.since_epoch(),
+            None => SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .expect("System time went backwards"),
+        }
+    }
+
+    pub async fn sleep(&self, duration: Duration) {
+        match &self.manual {
+            Some(shared) => {
+                let deadline = shared.now() + duration;
+                Sleep::new(shared.clone(), deadline).await
+            }
+            None => async_std::task::sleep(duration).await,
+        }
+    }
+
+    pub async fn sleep_until(&self, deadline: Instant) {
+        match &self.manual {
+            Some(shared) => Sleep::new(shared.clone(), deadline).await,
+            None => {
+                async_std::task::sleep(deadline.saturating_duration_since(Instant::now())).await
+            }
+        }
+    }
+}
+
+/// A clock that only moves when [`advance`](ManualClock::advance) is called.
+#[derive(Debug)]
+pub struct ManualClock {
+    id: u64,
+    shared: Arc<Shared>,
+}
+
+impl Default for ManualClock {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ManualClock {
+    /// A clock starting at the time of the system.
+    pub fn new() -> Self {
+        Self::starting_at(Clock::system().since_epoch())
+    }
+
+    /// A clock whose wall-clock time starts at `since_epoch`, a duration since the UNIX epoch.
+    pub fn starting_at(since_epoch: Duration) -> Self {
+        let shared = Arc::new(Shared {
+            origin: Instant::now(),
+            epoch: since_epoch,
+            state: Mutex::new(State::default()),
+        });
+        let id = NEXT_MANUAL_ID.fetch_add(1, Ordering::Relaxed);
+        let mut clocks = lock(&MANUAL_CLOCKS);
+        clocks.retain(|(_, clock)| clock.strong_count() > 0);
+        clocks.push((id, Arc::downgrade(&shared)));
+        Self { id, shared }
+    }
+
+    /// The value of [`CLOCK_KEY`] in the configuration of a node using this clock.
+    pub fn name(&self) -> String {
+        format!("{}{}", MANUAL_PREFIX, self.id)
+    }
+
+    /// A [`Clock`] reading this one.
+    pub fn clock(&self) -> Clock {
+        Clock {
+            manual: Some(self.shared.clone()),
+        }
+    }
+
+    pub fn now(&self) -> Instant {
+        self.shared.now()
+    }
+
+    pub fn since_epoch(&self) -> Duration {
+        self.shared.since_epoch()
+    }
+
+    /// Moves the clock forward, waking up the tasks whose sleep ended.
+    pub fn advance(&self, duration: Duration) {
+        let mut state = lock(&self.shared.state);
+        state.elapsed += duration;
+        // The tasks still sleeping register again when polled.
+        for (_, waker) in state.sleepers.drain(..) {
+            waker.wake();
+        }
+    }
+
+    /// Waits until a task sleeps on this clock, e.g. before advancing it past the deadline of a
+    /// node in its `iteration`.
+    pub async fn asleep(&self) {
+        Asleep {
+            shared: &self.shared,
+        }
+        .await
+    }
+}
+
+#[derive(Debug)]
+struct Shared {
+    origin: Instant,
+    epoch: Duration,
+    state: Mutex<State>,
+}
+
+#[derive(Debug, Default)]
+struct State {
+    elapsed: Duration,
+    next_sleeper: u64,
+    sleepers: Vec<(u64, Waker)>,
+    watchers: Vec<Waker>,
+}
+
+impl Shared {
+    fn now(&self) -> Instant {
+        self.origin + lock(&self.state).elapsed
+    }
+
+    fn since_epoch(&self) -> Duration {
+        self.epoch + lock(&self.state).elapsed
+    }
+}
+
+/// Locks `mutex`, ignoring the poisoning by a panicking test.
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    mutex
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+/// The sleep of a task on a manual clock.
+struct Sleep {
+    shared: Arc<Shared>,
+    deadline: Instant,
+    id: Option<u64>,
+}
+
+impl Sleep {
+    fn new(shared: Arc<Shared>, deadline: Instant) -> Self {
+        Self {
+            shared,
+            deadline,
+            id: None,
+        }
+    }
+}
+
+impl Future for Sleep {
+    type Output = ();
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        let shared = self.shared.clone();
+        let mut state = lock(&shared.state);
+        if let Some(id) = self.id.take() {
+            state.sleepers.retain(|(sleeper, _)| *sleeper != id);
+        }
+        if shared.origin + state.elapsed >= self.deadline {
+            return Poll::Ready(());
+        }
+
+        let id = state.next_sleeper;
+        state.next_sleeper += 1;
+        state.sleepers.push((id, cx.waker().clone()));
+        self.id = Some(id);
+        for watcher in state.watchers.drain(..) {
+            watcher.wake();
+        }
+        Poll::Pending
+    }
+}
+
+impl Drop for Sleep {
+    fn drop(&mut self) {
+        if let Some(id) = self.id {
+            lock(&self.shared.state)
+                .sleepers
+                .retain(|(sleeper, _)| *sleeper != id);
+        }
+    }
+}
+
+struct Asleep<'a> {
+    shared: &'a Shared,
+}
+
+impl Future for Asleep<'_> {
+    type Output = ();
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        let mut state = lock(&self.shared.state);
+        if !state.sleepers.is_empty() {
+            return Poll::Ready(());
+        }
+        state.watchers.push(cx.waker().clone());
+        Poll::Pending
+    }
+}
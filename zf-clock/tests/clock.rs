@@ -0,0 +1,98 @@
+//
+// Copyright (c) 2022 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+use futures::FutureExt;
+use serde_json::json;
+use std::time::Duration;
+use zenoh_flow::prelude::Configuration;
+use zf_clock::{Clock, ManualClock};
+
+#[async_std::test]
+async fn a_sleep_ends_when_the_clock_is_advanced_past_its_deadline() {
+    let manual = ManualClock::new();
+    let clock = manual.clock();
+    let start = clock.now();
+
+    let sleep = async {
+        clock.sleep(Duration::from_millis(100)).await;
+        clock.now()
+    };
+    let advance = async {
+        manual.asleep().await;
+        manual.advance(Duration::from_millis(60));
+        // Still sleeping: the deadline was not reached.
+        manual.asleep().await;
+        manual.advance(Duration::from_millis(60));
+    };
+    let (woken, _) = futures::join!(sleep, advance);
+
+    assert_eq!(woken - start, Duration::from_millis(120));
+}
+
+#[async_std::test]
+async fn a_sleep_past_its_deadline_ends_immediately() {
+    let manual = ManualClock::new();
+    let clock = manual.clock();
+    let deadline = clock.now() + Duration::from_millis(10);
+    manual.advance(Duration::from_millis(10));
+
+    assert!(clock.sleep_until(deadline).now_or_never().is_some());
+    assert!(clock.sleep(Duration::ZERO).now_or_never().is_some());
+}
+
+#[async_std::test]
+async fn a_cancelled_sleep_is_not_waited_for() {
+    let manual = ManualClock::new();
+    let clock = manual.clock();
+
+    assert!(clock.sleep(Duration::from_secs(1)).now_or_never().is_none());
+    assert!(manual.asleep().now_or_never().is_none());
+}
+
+#[test]
+fn the_clock_is_read_from_the_configuration() {
+    let manual = ManualClock::new();
+    let configuration = Configuration::from(json!({ "clock": manual.name() }));
+    let clock = Clock::from_configuration(Some(&configuration)).unwrap();
+    manual.advance(Duration::from_secs(3));
+    assert_eq!(clock.now(), manual.now());
+
+    let system = Clock::from_configuration(None).unwrap();
+    assert!(system.now() <= std::time::Instant::now());
+
+    let invalid = Configuration::from(json!({ "clock": "sundial" }));
+    assert!(Clock::from_configuration(Some(&invalid)).is_err());
+}
+
+#[test]
+fn the_wall_clock_time_moves_with_the_clock() {
+    let manual = ManualClock::starting_at(Duration::from_secs(1_000));
+    let clock = manual.clock();
+    assert_eq!(clock.since_epoch(), Duration::from_secs(1_000));
+
+    manual.advance(Duration::from_millis(1_500));
+    assert_eq!(clock.since_epoch(), Duration::from_millis(1_001_500));
+}
+
+#[test]
+fn the_name_of_a_dropped_clock_designates_no_other() {
+    let dropped = ManualClock::new();
+    let name = dropped.name();
+    drop(dropped);
+
+    let other = ManualClock::new();
+    assert_ne!(other.name(), name);
+    let configuration = Configuration::from(json!({ "clock": name }));
+    assert!(Clock::from_configuration(Some(&configuration)).is_err());
+}
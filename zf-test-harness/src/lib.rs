@@ -0,0 +1,496 @@
+//
+// Copyright (c) 2022 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+//! Unit tests for Zenoh-Flow nodes: creates a node with a given configuration, pushes typed
+//! messages into its inputs, drives its `iteration()` and collects what it sends on its outputs.
+//!
+//! Only the runtime of Zenoh-Flow can create the `Context`, `Inputs` and `Outputs` of a node. The
+//! harness thus instantiates, in the current process, a data flow made of the node under test, a
+//! source connected to all its inputs and a sink connected to all its outputs: the links are
+//! in-memory channels. None of these nodes runs, the test calls `iteration()` itself.
+//!
+//! ```ignore
+//! let greetings = Harness::new()
+//!     .input("name", |buffer: &mut Vec<u8>, name: &String| {
+//!         buffer.extend_from_slice(name.as_bytes());
+//!         Ok(())
+//!     })
+//!     .output("greeting", |bytes| Ok(String::decode(bytes)?))
+//!     .operator::<GreetingsMaker>()
+//!     .await?;
+//!
+//! greetings.send("name", "Sofia".to_string(), None).await?;
+//! greetings.iteration().await?;
+//! assert_eq!(greetings.recv::<String>("greeting").await?, "Ciao, Sofia!\n");
+//! ```
+//!
+//! A node whose behaviour depends on time reads it from a [`Clock`] of the `zf-clock` crate, which
+//! a test replaces with a [`ManualClock`] through [`Harness::clock`].
+
+pub use zf_clock::{Clock, ManualClock};
+
+use anyhow::{anyhow, bail, Result};
+use serde_json::{json, Value};
+use std::any::Any;
+use std::collections::HashMap;
+use std::convert::TryFrom;
+use std::sync::{Arc, Mutex};
+use uuid::Uuid;
+use zenoh::prelude::r#async::*;
+use zenoh_flow::model::descriptor::FlattenDataFlowDescriptor;
+use zenoh_flow::model::record::DataFlowRecord;
+use zenoh_flow::prelude::{
+    Configuration, Context, Input, Inputs, Message, Node, Operator, Output, Outputs, Sink, Source,
+};
+use zenoh_flow::runtime::dataflow::instance::DataFlowInstance;
+use zenoh_flow::runtime::dataflow::loader::{Loader, LoaderConfig};
+use zenoh_flow::runtime::dataflow::DataFlow;
+use zenoh_flow::runtime::RuntimeContext;
+use zenoh_flow::traits::{OperatorFactoryTrait, SinkFactoryTrait, SourceFactoryTrait};
+
+/// The id of the node under test in the data flow.
+static NODE: &str = "node";
+/// The id of the source sending on the inputs of the node under test.
+static INPUTS: &str = "zf-test-harness/inputs";
+/// The id of the sink receiving from the outputs of the node under test.
+static OUTPUTS: &str = "zf-test-harness/outputs";
+/// The name of the runtime all the nodes are mapped to.
+static RUNTIME: &str = "zf-test-harness";
+
+/// A typed `Input` or `Output`.
+type Port = Box<dyn Any + Send + Sync>;
+type TakeOutput = Box<dyn FnOnce(&mut Outputs) -> Result<Port> + Send>;
+type TakeInput = Box<dyn FnOnce(&mut Inputs) -> Result<Port> + Send>;
+/// Where a factory puts what it created, read once the data flow is instantiated.
+type Slot<T> = Arc<Mutex<Option<T>>>;
+
+/// Describes the ports and the configuration of the node under test, then creates it.
+pub struct Harness {
+    configuration: serde_json::Map<String, Value>,
+    inputs: Vec<(String, TakeOutput)>,
+    outputs: Vec<(String, TakeInput)>,
+}
+
+impl Default for Harness {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Harness {
+    pub fn new() -> Self {
+        Self {
+            configuration: serde_json::Map::new(),
+            inputs: Vec::new(),
+            outputs: Vec::new(),
+        }
+    }
+
+    /// Adds the keys of `configuration`, an object, to the configuration of the node.
+    pub fn configuration(mut self, configuration: Value) -> Self {
+        match configuration {
+            Value::Object(keys) => self.configuration.extend(keys),
+            other => panic!("The configuration of a node is an object, found: {}", other),
+        }
+        self
+    }
+
+    /// Makes the node read the time from `clock`, if it uses [`Clock::from_configuration`].
+    pub fn clock(mut self, clock: &ManualClock) -> Self {
+        self.configuration
+            .insert(zf_clock::CLOCK_KEY.to_string(), clock.name().into());
+        self
+    }
+
+    /// Adds the input `port` to the node, on which [`NodeUnderTest::send`] sends messages
+    /// serialized with `serializer`.
+    pub fn input<T: Send + Sync + 'static>(
+        mut self,
+        port: impl Into<String>,
+        serializer: impl Fn(&mut Vec<u8>, &T) -> Result<()> + Send + Sync + 'static,
+    ) -> Self {
+        let port = port.into();
+        let id = port.clone();
+        let take: TakeOutput = Box::new(move |outputs: &mut Outputs| {
+            let output = outputs
+                .take(&id)
+                .ok_or_else(|| anyhow!("No output '{}' in the harness", id))?
+                .typed(serializer);
+            Ok(Box::new(output) as Port)
+        });
+        self.inputs.push((port, take));
+        self
+    }
+
+    /// Adds the output `port` to the node, from which [`NodeUnderTest::recv`] receives messages
+    /// deserialized with `deserializer`.
+    pub fn output<T: Send + Sync + 'static>(
+        mut self,
+        port: impl Into<String>,
+        deserializer: impl Fn(&[u8]) -> Result<T> + Send + Sync + 'static,
+    ) -> Self {
+        let port = port.into();
+        let id = port.clone();
+        let take: TakeInput = Box::new(move |inputs: &mut Inputs| {
+            let input = inputs
+                .take(&id)
+                .ok_or_else(|| anyhow!("No input '{}' in the harness", id))?
+                .typed(deserializer);
+            Ok(Box::new(input) as Port)
+        });
+        self.outputs.push((port, take));
+        self
+    }
+
+    pub async fn source<N: Source + 'static>(self) -> Result<NodeUnderTest<N>> {
+        let node = Slot::default();
+        let factory = NodeFactory(node.clone());
+        self.instantiate("sources", node, move |dataflow, record| {
+            let source = record
+                .sources
+                .remove(NODE)
+                .ok_or_else(|| anyhow!("The node under test is not a source"))?;
+            dataflow.add_source(source, factory);
+            Ok(())
+        })
+        .await
+    }
+
+    pub async fn operator<N: Operator + 'static>(self) -> Result<NodeUnderTest<N>> {
+        let node = Slot::default();
+        let factory = NodeFactory(node.clone());
+        self.instantiate("operators", node, move |dataflow, record| {
+            let operator = record
+                .operators
+                .remove(NODE)
+                .ok_or_else(|| anyhow!("The node under test is not an operator"))?;
+            dataflow.add_operator(operator, factory);
+            Ok(())
+        })
+        .await
+    }
+
+    pub async fn sink<N: Sink + 'static>(self) -> Result<NodeUnderTest<N>> {
+        let node = Slot::default();
+        let factory = NodeFactory(node.clone());
+        self.instantiate("sinks", node, move |dataflow, record| {
+            let sink = record
+                .sinks
+                .remove(NODE)
+                .ok_or_else(|| anyhow!("The node under test is not a sink"))?;
+            dataflow.add_sink(sink, factory);
+            Ok(())
+        })
+        .await
+    }
+
+    /// The flattened descriptor of the data flow, the node under test being in `section`.
+    fn descriptor(&self, section: &str) -> Result<Value> {
+        let inputs = self
+            .inputs
+            .iter()
+            .map(|(port, _)| port.clone())
+            .collect::<Vec<_>>();
+        let outputs = self
+            .outputs
+            .iter()
+            .map(|(port, _)| port.clone())
+            .collect::<Vec<_>>();
+        match section {
+            "sources" if !inputs.is_empty() => bail!("A source has no inputs"),
+            "sinks" if !outputs.is_empty() => bail!("A sink has no outputs"),
+            _ => {}
+        }
+
+        let mut node = json!({
+            "id": NODE,
+            "configuration": Value::Object(self.configuration.clone()),
+        });
+        if section != "sources" {
+            node["inputs"] = inputs.clone().into();
+        }
+        if section != "sinks" {
+            node["outputs"] = outputs.clone().into();
+        }
+
+        let mut flow = json!({
+            "flow": RUNTIME,
+            "sources": [],
+            "operators": [],
+            "sinks": [],
+            "links": [],
+            "mapping": { NODE: RUNTIME },
+        });
+        flow[section] = json!([node]);
+        if !inputs.is_empty() {
+            flow["sources"] = json!([{ "id": INPUTS, "outputs": inputs }]);
+            flow["mapping"][INPUTS] = RUNTIME.into();
+        }
+        if !outputs.is_empty() {
+            flow["sinks"] = json!([{ "id": OUTPUTS, "inputs": outputs }]);
+            flow["mapping"][OUTPUTS] = RUNTIME.into();
+        }
+        let links = inputs
+            .iter()
+            .map(|port| (INPUTS, NODE, port))
+            .chain(outputs.iter().map(|port| (NODE, OUTPUTS, port)))
+            .map(|(from, to, port)| {
+                json!({
+                    "from": { "node": from, "output": port },
+                    "to": { "node": to, "input": port },
+                })
+            })
+            .collect::<Vec<_>>();
+        flow["links"] = links.into();
+        Ok(flow)
+    }
+
+    /// Creates the nodes of the data flow, the node under test being added by `add_node`.
+    async fn instantiate<N>(
+        self,
+        section: &str,
+        node: Slot<Arc<N>>,
+        add_node: impl FnOnce(&mut DataFlow, &mut DataFlowRecord) -> Result<()>,
+    ) -> Result<NodeUnderTest<N>> {
+        let descriptor: FlattenDataFlowDescriptor =
+            serde_json::from_value(self.descriptor(section)?)?;
+        let mut record = DataFlowRecord::try_from((descriptor, Uuid::new_v4()))?;
+        let (context, hlc) = runtime_context(RUNTIME).await?;
+        let mut dataflow = DataFlow::new(RUNTIME, context);
+        add_node(&mut dataflow, &mut record)?;
+
+        let senders = Slot::default();
+        if let Some(source) = record.sources.remove(INPUTS) {
+            let factory = HarnessSource {
+                ports: Mutex::new(Some(self.inputs)),
+                taken: senders.clone(),
+            };
+            dataflow.add_source(source, factory);
+        }
+        let receivers = Slot::default();
+        if let Some(sink) = record.sinks.remove(OUTPUTS) {
+            let factory = HarnessSink {
+                ports: Mutex::new(Some(self.outputs)),
+                taken: receivers.clone(),
+            };
+            dataflow.add_sink(sink, factory);
+        }
+        for link in record.links {
+            dataflow.add_link(link.from, link.to);
+        }
+
+        let instance = DataFlowInstance::try_instantiate(dataflow, hlc).await?;
+        let node = take(&node).ok_or_else(|| anyhow!("The node under test was not created"))?;
+        Ok(NodeUnderTest {
+            node,
+            senders: take(&senders).unwrap_or_default(),
+            receivers: take(&receivers).unwrap_or_default(),
+            _instance: instance,
+        })
+    }
+}
+
+/// A node created by a [`Harness`], with the ports connected to its own.
+pub struct NodeUnderTest<N> {
+    node: Arc<N>,
+    senders: HashMap<String, Port>,
+    receivers: HashMap<String, Port>,
+    // The channels of the links live as long as the instance.
+    _instance: DataFlowInstance,
+}
+
+impl<N: Node> NodeUnderTest<N> {
+    pub fn node(&self) -> &N {
+        &self.node
+    }
+
+    pub async fn iteration(&self) -> Result<()> {
+        self.node.iteration().await
+    }
+
+    /// Sends `data` to the input `port` of the node, as a node linked to it would.
+    pub async fn send<T: Send + Sync + 'static>(
+        &self,
+        port: &str,
+        data: T,
+        timestamp: Option<u64>,
+    ) -> Result<()> {
+        port::<Output<T>>(&self.senders, port, "input")?
+            .send(data, timestamp)
+            .await
+    }
+
+    /// Waits for the next data sent by the node on its output `port`, skipping the watermarks.
+    pub async fn recv<T: Clone + Send + Sync + 'static>(&self, port: &str) -> Result<T> {
+        let input = port::<Input<T>>(&self.receivers, port, "output")?;
+        loop {
+            if let (Message::Data(data), _) = input.recv().await? {
+                return Ok((*data).clone());
+            }
+        }
+    }
+}
+
+fn port<'a, P: 'static>(ports: &'a HashMap<String, Port>, port: &str, kind: &str) -> Result<&'a P> {
+    ports
+        .get(port)
+        .ok_or_else(|| anyhow!("No {} '{}' was declared in the harness", kind, port))?
+        .downcast_ref::<P>()
+        .ok_or_else(|| {
+            anyhow!(
+                "The {} '{}' is not a {}",
+                kind,
+                port,
+                std::any::type_name::<P>()
+            )
+        })
+}
+
+fn take<T>(slot: &Mutex<Option<T>>) -> Option<T> {
+    slot.lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+        .take()
+}
+
+fn put<T>(slot: &Mutex<Option<T>>, value: T) {
+    *slot.lock().unwrap_or_else(|poisoned| poisoned.into_inner()) = Some(value);
+}
+
+/// The context of a runtime called `name`, of its own, and its clock: its Zenoh peer does not look
+/// for others, the nodes it runs only communicate through in-memory channels.
+///
+/// The harness runs the node under test in such a runtime, which can run a whole data flow as well.
+pub async fn runtime_context(name: &str) -> Result<(RuntimeContext, Arc<uhlc::HLC>)> {
+    let mut config = zenoh::config::Config::default();
+    config
+        .set_mode(Some(WhatAmI::Peer))
+        .map_err(|_| anyhow!("Could not configure Zenoh as a peer"))?;
+    config
+        .scouting
+        .multicast
+        .set_enabled(Some(false))
+        .map_err(|_| anyhow!("Could not disable the scouting of Zenoh"))?;
+    let session = zenoh::open(config)
+        .res()
+        .await
+        .map_err(|e| anyhow!("Could not open a Zenoh session: {}", e))?;
+
+    let hlc = Arc::new(uhlc::HLC::default());
+    let context = RuntimeContext {
+        session: Arc::new(session),
+        hlc: hlc.clone(),
+        loader: Arc::new(Loader::new(LoaderConfig::new())),
+        runtime_name: name.into(),
+        runtime_uuid: Uuid::new_v4(),
+        shared_memory_element_size: 0,
+        shared_memory_elements: 0,
+        shared_memory_backoff: 0,
+        use_shm: false,
+    };
+    Ok((context, hlc))
+}
+
+/// Creates the node under test with its own constructor. It is not run: the test drives it.
+struct NodeFactory<N>(Slot<Arc<N>>);
+
+#[async_trait::async_trait]
+impl<N: Source + 'static> SourceFactoryTrait for NodeFactory<N> {
+    async fn new_source(
+        &self,
+        context: &mut Context,
+        configuration: &Option<Configuration>,
+        outputs: Outputs,
+    ) -> zenoh_flow::prelude::Result<Option<Arc<dyn Node>>> {
+        let node = N::new(context.clone(), configuration.clone(), outputs).await?;
+        put(&self.0, Arc::new(node));
+        Ok(None)
+    }
+}
+
+#[async_trait::async_trait]
+impl<N: Operator + 'static> OperatorFactoryTrait for NodeFactory<N> {
+    async fn new_operator(
+        &self,
+        context: &mut Context,
+        configuration: &Option<Configuration>,
+        inputs: Inputs,
+        outputs: Outputs,
+    ) -> zenoh_flow::prelude::Result<Option<Arc<dyn Node>>> {
+        let node = N::new(context.clone(), configuration.clone(), inputs, outputs).await?;
+        put(&self.0, Arc::new(node));
+        Ok(None)
+    }
+}
+
+#[async_trait::async_trait]
+impl<N: Sink + 'static> SinkFactoryTrait for NodeFactory<N> {
+    async fn new_sink(
+        &self,
+        context: &mut Context,
+        configuration: &Option<Configuration>,
+        inputs: Inputs,
+    ) -> zenoh_flow::prelude::Result<Option<Arc<dyn Node>>> {
+        let node = N::new(context.clone(), configuration.clone(), inputs).await?;
+        put(&self.0, Arc::new(node));
+        Ok(None)
+    }
+}
+
+/// The source whose outputs are linked to the inputs of the node under test.
+struct HarnessSource {
+    ports: Mutex<Option<Vec<(String, TakeOutput)>>>,
+    taken: Slot<HashMap<String, Port>>,
+}
+
+#[async_trait::async_trait]
+impl SourceFactoryTrait for HarnessSource {
+    async fn new_source(
+        &self,
+        _context: &mut Context,
+        _configuration: &Option<Configuration>,
+        mut outputs: Outputs,
+    ) -> zenoh_flow::prelude::Result<Option<Arc<dyn Node>>> {
+        let ports = take(&self.ports).unwrap_or_default();
+        let mut taken = HashMap::new();
+        for (port, take_output) in ports {
+            taken.insert(port, take_output(&mut outputs)?);
+        }
+        put(&self.taken, taken);
+        Ok(None)
+    }
+}
+
+/// The sink whose inputs are linked to the outputs of the node under test.
+struct HarnessSink {
+    ports: Mutex<Option<Vec<(String, TakeInput)>>>,
+    taken: Slot<HashMap<String, Port>>,
+}
+
+#[async_trait::async_trait]
+impl SinkFactoryTrait for HarnessSink {
+    async fn new_sink(
+        &self,
+        _context: &mut Context,
+        _configuration: &Option<Configuration>,
+        mut inputs: Inputs,
+    ) -> zenoh_flow::prelude::Result<Option<Arc<dyn Node>>> {
+        let ports = take(&self.ports).unwrap_or_default();
+        let mut taken = HashMap::new();
+        for (port, take_input) in ports {
+            taken.insert(port, take_input(&mut inputs)?);
+        }
+        put(&self.taken, taken);
+        Ok(None)
+    }
+}